@@ -7,6 +7,9 @@ use crate::utils::id::generate_id;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub run_id: String,
+    /// Run that started this one from a sub-pipeline step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
     /// Optional user-friendly key for display
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pipeline_key: Option<String>,
@@ -26,6 +29,10 @@ pub struct ExecutionResult {
     /// Collected output from the pipeline (optional).
     pub result: Option<Value>,
 
+    /// Compensating actions run after the failure, in the order they ran.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compensations: Vec<ActionResult>,
+
     /// Metadata like timing, step counts, etc.
     pub metrics: ExecutionMetrics,
 }
@@ -37,6 +44,10 @@ pub enum ExecutionStatus {
     Canceled,
     Skipped,
     Timeout,
+    /// The run failed and every completed step was compensated
+    Compensated,
+    /// The run is waiting for an external signal or a timer
+    Suspended,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,12 +91,49 @@ pub struct ActionResult {
     pub key: String,
     pub action: Option<String>,
     pub status: ExecutionStatus,
+    /// Resolved input, kept for steps that declare a compensating action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
     pub output: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
     pub duration_ms: Option<u64>,
+    /// Individual tries when the step ran under a retry policy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<ActionAttempt>,
+}
+
+/// Outcome of a single try of an action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionAttempt {
+    pub attempt: u32,
+    pub status: ExecutionStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+}
+
+impl ActionAttempt {
+    /// Records an attempt that started at `started_at` and finished now.
+    pub fn finished(attempt: u32, started_at: DateTime<Utc>, error: Option<String>) -> Self {
+        let finished_at = Utc::now();
+        Self {
+            attempt,
+            status: if error.is_none() {
+                ExecutionStatus::Success
+            } else {
+                ExecutionStatus::Failed
+            },
+            message: error,
+            started_at,
+            finished_at,
+            duration_ms: (finished_at - started_at).num_milliseconds().max(0) as u64,
+        }
+    }
 }
 
 impl ActionResult {
@@ -95,11 +143,13 @@ impl ActionResult {
             id: generate_id("action_result"),
             action: None,
             status: ExecutionStatus::Success,
+            input: None,
             output: Some(output),
             message: None,
             started_at: Some(Utc::now()),
             finished_at: Some(Utc::now()),
             duration_ms: Some(0),
+            attempts: vec![],
         }
     }
 
@@ -110,11 +160,13 @@ impl ActionResult {
             id: generate_id("action_result"),
             action: None,
             status: ExecutionStatus::Failed,
+            input: None,
             output: None,
             message: Some(message.into()),
             started_at: Some(Utc::now()),
             finished_at: Some(Utc::now()),
             duration_ms: Some(0),
+            attempts: vec![],
         }
     }
 
@@ -124,11 +176,13 @@ impl ActionResult {
             id: generate_id("action_result"),
             action: None,
             status: ExecutionStatus::Skipped,
+            input: None,
             output: None,
             message: None,
             started_at: None,
             finished_at: None,
             duration_ms: None,
+            attempts: vec![],
         }
     }
 }
@@ -6,6 +6,9 @@ pub struct ActionContext {
     pub input: Option<Value>,
     pub result: Option<Value>,
     pub config: Option<Value>,
+    /// Read-only view of the run used for JSONPath resolution:
+    /// `payload`, `<step>.output` for finished steps and the latest `output`
+    pub context: Option<Value>,
 }
 
 impl ActionContext {
@@ -15,6 +18,7 @@ impl ActionContext {
             input: Some(input),
             result: None,
             config: None,
+            context: None,
         }
     }
 
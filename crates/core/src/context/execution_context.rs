@@ -22,6 +22,10 @@ pub struct ExecutionContext {
     /// Optional run identifier for this execution.
     pub run_id: String,
 
+    /// Run that started this one from a sub-pipeline step
+    #[serde(default)]
+    pub parent_run_id: Option<String>,
+
     /// Shared scratchpad for pipeline-wide data.
     pub data: HashMap<String, Value>,
 
@@ -40,6 +44,40 @@ pub struct ExecutionContext {
 
     /// Optional error at pipeline level
     pub error: Option<String>,
+
+    /// Compensating actions run after the run failed
+    #[serde(default)]
+    pub compensations: Vec<ActionResult>,
+
+    /// Signal the run is suspended on
+    #[serde(default)]
+    pub waiting: Option<SignalWait>,
+
+    /// End of the durable sleep the run is suspended in
+    #[serde(default)]
+    pub sleeping_until: Option<DateTime<Utc>>,
+}
+
+/// A `wait_for` step the run is suspended at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalWait {
+    pub step: String,
+    pub signal: String,
+    pub since: DateTime<Utc>,
+
+    /// When the step stops waiting and takes its `otherwise` route
+    pub timeout_at: Option<DateTime<Utc>>,
+
+    /// Payload of the signal once delivered, until the step consumes it
+    #[serde(default)]
+    pub payload: Option<Value>,
+}
+
+impl SignalWait {
+    /// Whether the wait timed out at `now` without receiving its signal.
+    pub fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.payload.is_none() && self.timeout_at.is_some_and(|t| t <= now)
+    }
 }
 
 impl ExecutionContext {
@@ -48,12 +86,16 @@ impl ExecutionContext {
             environment,
             pipeline_key: pipeline_key.into(),
             run_id: generate_id("run"),
+            parent_run_id: None,
             data: HashMap::new(),
             steps: Vec::new(),
             results: HashMap::new(),
             started_at: Utc::now(),
             finished_at: None,
             error: None,
+            compensations: Vec::new(),
+            waiting: None,
+            sleeping_until: None,
             current_step: None,
         }
     }
@@ -80,11 +122,34 @@ impl ExecutionContext {
         self.data.get(key)
     }
 
+    /// Status of the finished run: `Compensated` when it failed and every
+    /// compensating action succeeded, `Suspended` while it waits for a signal
+    /// or a timer.
+    pub fn status(&self) -> ExecutionStatus {
+        if self.error.is_none() {
+            if self.waiting.is_some() || self.sleeping_until.is_some() {
+                ExecutionStatus::Suspended
+            } else {
+                ExecutionStatus::Success
+            }
+        } else if !self.compensations.is_empty()
+            && self
+                .compensations
+                .iter()
+                .all(|c| c.status == ExecutionStatus::Success)
+        {
+            ExecutionStatus::Compensated
+        } else {
+            ExecutionStatus::Failed
+        }
+    }
+
     /// Serialize the entire execution context as JSON
     pub fn as_value(&self) -> Value {
         serde_json::json!({
             "pipeline_key": self.pipeline_key,
             "run_id": self.run_id,
+            "parent_run_id": self.parent_run_id,
             "environment": self.environment,
             "data": self.data,
             "results": self.results,
@@ -92,6 +157,9 @@ impl ExecutionContext {
             "started_at": self.started_at,
             "finished_at": self.finished_at,
             "error": self.error,
+            "compensations": self.compensations,
+            "waiting": self.waiting,
+            "sleeping_until": self.sleeping_until,
         })
     }
 
@@ -113,20 +181,20 @@ impl ExecutionContext {
             .filter(|s| s.status == ExecutionStatus::Failed)
             .count();
 
+        let status = self.status();
+
         ExecutionResult {
             pipeline_key: self.pipeline_key.into(),
             run_id: self.run_id.clone(),
-            status: if self.error.is_none() {
-                ExecutionStatus::Success
-            } else {
-                ExecutionStatus::Failed
-            },
+            parent_run_id: self.parent_run_id,
+            status,
             environment: Some("local".to_string()), // TODO make this dynamic
             result: match &self.steps.last() {
                 Some(val) => val.output.clone(),
                 None => None,
             },
             steps: self.steps,
+            compensations: self.compensations,
             error: self.error,
             metrics: ExecutionMetrics {
                 started_at: self.started_at,
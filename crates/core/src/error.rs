@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// The unified error type across Rivus Core.
@@ -20,9 +21,38 @@ pub enum Error {
 
     #[error("Not found error: {0}")]
     NotFound(String),
+
+    #[error("Timeout: {0}")]
+    Timeout(String),
+}
+
+/// Field-less mirror of [`Error`] variants, used to classify failures (e.g. in retry policies).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ErrorKind {
+    Action,
+    Config,
+    Pipeline,
+    System,
+    Unsupported,
+    NotFound,
+    Timeout,
 }
 
 impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Action(_) => ErrorKind::Action,
+            Error::Config(_) => ErrorKind::Config,
+            Error::Pipeline(_) => ErrorKind::Pipeline,
+            Error::System(_) => ErrorKind::System,
+            Error::Unsupported(_) => ErrorKind::Unsupported,
+            Error::NotFound(_) => ErrorKind::NotFound,
+            Error::Timeout(_) => ErrorKind::Timeout,
+        }
+    }
+
     pub fn action(msg: impl Into<String>) -> Self {
         Self::Action(msg.into())
     }
@@ -0,0 +1,589 @@
+//! Expression language for `next_when` routing conditions.
+//!
+//! ```text
+//! $.payload.total >= 100 && ($.payload.country in ['DE', 'FR'] || !exists($.payload.vat))
+//! ```
+//!
+//! - JSONPath operands start with `$`; a path that matches nothing evaluates to `null`
+//! - Literals: `'single'` or `"double"` quoted strings, numbers, `true`, `false`, `null`, `[arrays]`
+//! - Other bare words are read as strings, as conditions were before this language;
+//!   `Condition::bare_words` lists them so validators can ask for quotes
+//! - Comparisons: `==`, `!=`, `<`, `<=`, `>`, `>=`, `in`, `contains`, `matches` (alias `=~`)
+//! - Logic: `&&`, `||`, `!` and parentheses; `&&` binds tighter than `||`
+//! - `exists($.path)` is true when the path matches, even if the value is `null`
+//! - An operand on its own is tested for truthiness
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// A parsed condition. Serializes as its source text and is parsed again on deserialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+    bare_words: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid condition '{expression}': {message} at column {column}")]
+pub struct ConditionError {
+    pub expression: String,
+    /// 1-based character column of the offending token
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        left: Operand,
+        op: CompareOp,
+        right: Operand,
+    },
+    /// The pattern is compiled at parse time
+    Matches {
+        left: Operand,
+        pattern: Regex,
+    },
+    Exists(String),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Path(String),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Left is an element of an array, a substring of a string or a key of an object
+    In,
+    /// Mirror of `In`
+    Contains,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, ConditionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            source,
+            tokens,
+            pos: 0,
+            bare_words: Vec::new(),
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(parser.error_at(token.column, "unexpected input after the expression"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+            bare_words: parser.bare_words,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Unquoted words read as strings, in source order.
+    pub fn bare_words(&self) -> &[String] {
+        &self.bare_words
+    }
+
+    /// Every JSONPath the condition reads, in source order.
+    pub fn paths(&self) -> Vec<&str> {
+        let mut paths = Vec::new();
+        self.expr.collect_paths(&mut paths);
+        paths
+    }
+
+    /// Evaluates the condition, looking JSONPath operands up through `resolve`.
+    /// `resolve` returns `None` when the path matches nothing.
+    pub fn evaluate(&self, resolve: &dyn Fn(&str) -> Option<Value>) -> bool {
+        self.expr.evaluate(resolve)
+    }
+}
+
+impl Expr {
+    fn evaluate(&self, resolve: &dyn Fn(&str) -> Option<Value>) -> bool {
+        match self {
+            Expr::And(a, b) => a.evaluate(resolve) && b.evaluate(resolve),
+            Expr::Or(a, b) => a.evaluate(resolve) || b.evaluate(resolve),
+            Expr::Not(e) => !e.evaluate(resolve),
+            Expr::Compare { left, op, right } => {
+                compare(&left.value(resolve), *op, &right.value(resolve))
+            }
+            Expr::Matches { left, pattern } => match left.value(resolve) {
+                Value::String(s) => pattern.is_match(&s),
+                _ => false,
+            },
+            Expr::Exists(path) => resolve(path).is_some(),
+            Expr::Truthy(operand) => truthy(&operand.value(resolve)),
+        }
+    }
+
+    fn collect_paths<'a>(&'a self, paths: &mut Vec<&'a str>) {
+        match self {
+            Expr::And(a, b) | Expr::Or(a, b) => {
+                a.collect_paths(paths);
+                b.collect_paths(paths);
+            }
+            Expr::Not(e) => e.collect_paths(paths),
+            Expr::Compare { left, right, .. } => {
+                paths.extend(left.path());
+                paths.extend(right.path());
+            }
+            Expr::Matches { left, .. } | Expr::Truthy(left) => paths.extend(left.path()),
+            Expr::Exists(path) => paths.push(path),
+        }
+    }
+}
+
+impl Operand {
+    fn value(&self, resolve: &dyn Fn(&str) -> Option<Value>) -> Value {
+        match self {
+            Operand::Path(path) => resolve(path).unwrap_or(Value::Null),
+            Operand::Literal(value) => value.clone(),
+        }
+    }
+
+    fn path(&self) -> Option<&str> {
+        match self {
+            Operand::Path(path) => Some(path),
+            Operand::Literal(_) => None,
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = ConditionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = ConditionError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> Self {
+        condition.source
+    }
+}
+
+impl From<ConditionError> for crate::error::Error {
+    fn from(e: ConditionError) -> Self {
+        crate::error::Error::Pipeline(e.to_string())
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Values of different types are never equal or ordered; numbers compare by value,
+/// so `1 == 1.0` holds.
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    match op {
+        CompareOp::Eq => equal(left, right),
+        CompareOp::Ne => !equal(left, right),
+        CompareOp::Lt => order(left, right) == Some(Ordering::Less),
+        CompareOp::Le => matches!(order(left, right), Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => order(left, right) == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(
+            order(left, right),
+            Some(Ordering::Greater | Ordering::Equal)
+        ),
+        CompareOp::In => contains(right, left),
+        CompareOp::Contains => contains(left, right),
+    }
+}
+
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| equal(x, y))
+        }
+        _ => left == right,
+    }
+}
+
+fn order(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn contains(haystack: &Value, needle: &Value) -> bool {
+    match (haystack, needle) {
+        (Value::Array(items), _) => items.iter().any(|item| equal(item, needle)),
+        (Value::String(s), Value::String(part)) => s.contains(part.as_str()),
+        (Value::Object(map), Value::String(key)) => map.contains_key(key),
+        _ => false,
+    }
+}
+
+/// `null`, `false`, `0`, `""`, `[]` and `{}` are false; everything else is true.
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Path(String),
+    Str(String),
+    Number(serde_json::Number),
+    Word(String),
+    Compare(CompareOp),
+    Match,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ConditionError> {
+    let chars: Vec<char> = source.chars().collect();
+    let error = |column: usize, message: String| ConditionError {
+        expression: source.to_string(),
+        column: column + 1,
+        message,
+    };
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '[' => TokenKind::LBracket,
+            ']' => TokenKind::RBracket,
+            ',' => TokenKind::Comma,
+            '&' if next == Some('&') => TokenKind::And,
+            '|' if next == Some('|') => TokenKind::Or,
+            '=' if next == Some('=') => TokenKind::Compare(CompareOp::Eq),
+            '=' if next == Some('~') => TokenKind::Match,
+            '!' if next == Some('=') => TokenKind::Compare(CompareOp::Ne),
+            '!' => TokenKind::Not,
+            '<' if next == Some('=') => TokenKind::Compare(CompareOp::Le),
+            '<' => TokenKind::Compare(CompareOp::Lt),
+            '>' if next == Some('=') => TokenKind::Compare(CompareOp::Ge),
+            '>' => TokenKind::Compare(CompareOp::Gt),
+            '$' => {
+                i = path_end(&chars, i);
+                TokenKind::Path(chars[start..i].iter().collect())
+            }
+            '\'' | '"' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error(start, "unterminated string".into())),
+                        Some('\\') if i + 1 < chars.len() => {
+                            value.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(&q) if q == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&other) => {
+                            value.push(other);
+                            i += 1;
+                        }
+                    }
+                }
+                TokenKind::Str(value)
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '+' | '-'))
+                {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                match serde_json::from_str::<Value>(&text) {
+                    Ok(Value::Number(n)) => TokenKind::Number(n),
+                    _ => return Err(error(start, format!("invalid number '{}'", text))),
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                TokenKind::Word(chars[start..i].iter().collect())
+            }
+            other => return Err(error(start, format!("unexpected character '{}'", other))),
+        };
+
+        // Fixed-width tokens have not advanced yet
+        if i == start {
+            i += match kind {
+                TokenKind::And
+                | TokenKind::Or
+                | TokenKind::Match
+                | TokenKind::Compare(
+                    CompareOp::Eq | CompareOp::Ne | CompareOp::Le | CompareOp::Ge,
+                ) => 2,
+                _ => 1,
+            };
+        }
+        tokens.push(Token {
+            kind,
+            column: start + 1,
+        });
+    }
+    Ok(tokens)
+}
+
+/// A path runs until whitespace, an operator or a closing delimiter; brackets
+/// (and quoted strings inside them) may contain anything.
+fn path_end(chars: &[char], mut i: usize) -> usize {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    while i < chars.len() {
+        let c = chars[i];
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') if depth > 0 => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') if depth > 0 => depth -= 1,
+            (None, _) if depth > 0 => {}
+            (None, c) if c.is_whitespace() || "()],=!<>&|~".contains(c) => break,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    bare_words: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek().is_some_and(|t| &t.kind == kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error_at(&self, column: usize, message: impl Into<String>) -> ConditionError {
+        ConditionError {
+            expression: self.source.to_string(),
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Error at the current token, or just past the end of the input.
+    fn error(&self, message: impl Into<String>) -> ConditionError {
+        let column = self
+            .peek()
+            .map_or(self.source.chars().count() + 1, |t| t.column);
+        self.error_at(column, message)
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<(), ConditionError> {
+        if self.eat(&kind) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {}", what)))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.and()?;
+        while self.eat(&TokenKind::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.unary()?;
+        while self.eat(&TokenKind::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ConditionError> {
+        if self.eat(&TokenKind::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&TokenKind::LParen) {
+            let expr = self.or()?;
+            self.expect(TokenKind::RParen, "')'")?;
+            return Ok(expr);
+        }
+        if self
+            .peek()
+            .is_some_and(|t| t.kind == TokenKind::Word("exists".into()))
+        {
+            self.pos += 1;
+            self.expect(TokenKind::LParen, "'(' after exists")?;
+            let Some(Token {
+                kind: TokenKind::Path(path),
+                ..
+            }) = self.next()
+            else {
+                self.pos -= 1;
+                return Err(self.error("exists() takes a JSONPath starting with '$'"));
+            };
+            self.expect(TokenKind::RParen, "')'")?;
+            return Ok(Expr::Exists(path));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ConditionError> {
+        let left = self.operand()?;
+        let op = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Compare(op)) => *op,
+            Some(TokenKind::Word(w)) if w == "in" => CompareOp::In,
+            Some(TokenKind::Word(w)) if w == "contains" => CompareOp::Contains,
+            Some(TokenKind::Match) => return self.matches(left),
+            Some(TokenKind::Word(w)) if w == "matches" => return self.matches(left),
+            _ => return Ok(Expr::Truthy(left)),
+        };
+        self.pos += 1;
+        let right = self.operand()?;
+        Ok(Expr::Compare { left, op, right })
+    }
+
+    fn matches(&mut self, left: Operand) -> Result<Expr, ConditionError> {
+        self.pos += 1;
+        let column = self.peek().map(|t| t.column);
+        match self.next().map(|t| t.kind) {
+            Some(TokenKind::Str(pattern)) => {
+                let pattern = Regex::new(&pattern).map_err(|e| {
+                    self.error_at(column.unwrap_or_default(), format!("invalid regex: {}", e))
+                })?;
+                Ok(Expr::Matches { left, pattern })
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a quoted regex pattern"))
+            }
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, ConditionError> {
+        if let Some(TokenKind::Path(path)) = self.peek().map(|t| &t.kind) {
+            let path = path.clone();
+            self.pos += 1;
+            return Ok(Operand::Path(path));
+        }
+        self.literal().map(Operand::Literal)
+    }
+
+    fn literal(&mut self) -> Result<Value, ConditionError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("expected a value"));
+        };
+        let value = match token.kind {
+            TokenKind::Str(s) => Value::String(s),
+            TokenKind::Number(n) => Value::Number(n),
+            TokenKind::Word(w) if w == "true" => Value::Bool(true),
+            TokenKind::Word(w) if w == "false" => Value::Bool(false),
+            TokenKind::Word(w) if w == "null" => Value::Null,
+            TokenKind::LBracket => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(&TokenKind::RBracket) {
+                    loop {
+                        items.push(self.literal()?);
+                        if self.eat(&TokenKind::RBracket) {
+                            break;
+                        }
+                        self.expect(TokenKind::Comma, "',' or ']'")?;
+                    }
+                }
+                return Ok(Value::Array(items));
+            }
+            TokenKind::Word(w) => {
+                self.bare_words.push(w.clone());
+                Value::String(w)
+            }
+            _ => return Err(self.error("expected a value")),
+        };
+        self.pos += 1;
+        Ok(value)
+    }
+}
@@ -18,4 +18,10 @@ pub trait PipelineHook: Send + Sync + 'static {
     async fn failed(&self, context: &mut ExecutionContext);
     async fn canceled(&self, context: &mut ExecutionContext);
     async fn start(&self, context: &mut ExecutionContext);
+
+    /// Called before `failed` once every compensating action of a failed run succeeded.
+    async fn compensated(&self, _context: &mut ExecutionContext) {}
+
+    /// Called before `failed` when a compensating action of a failed run failed.
+    async fn compensation_failed(&self, _context: &mut ExecutionContext) {}
 }
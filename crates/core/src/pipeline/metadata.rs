@@ -26,10 +26,10 @@ pub struct ActionMetadata {
 
 impl ActionMetadata {
     pub fn compute_duration(&mut self) {
-        if let (Some(start), Some(end)) = (self.started_at, self.finished_at) {
-            if let Ok(d) = end.duration_since(start) {
-                self.duration = Some(d);
-            }
+        if let (Some(start), Some(end)) = (self.started_at, self.finished_at)
+            && let Ok(d) = end.duration_since(start)
+        {
+            self.duration = Some(d);
         }
     }
 }
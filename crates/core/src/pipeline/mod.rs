@@ -1,4 +1,8 @@
+pub mod condition;
 pub mod hook;
 pub mod metadata;
+#[allow(clippy::module_inception)]
 pub mod pipeline;
+pub mod retry;
 pub mod state;
+pub mod template;
@@ -1,10 +1,55 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Clone, Serialize, Deserialize)]
+use super::condition::Condition;
+use super::retry::RetryPolicy;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pipeline {
     pub key: String,
     pub steps: Vec<PipelineStep>,
+
+    /// How the engine decides which step runs next
+    #[serde(default)]
+    pub mode: ExecutionMode,
+
+    /// Upper bound on steps running at once in DAG mode (unbounded when unset)
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+
+    /// Deadline for the whole run
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// Times routing may enter one step before the run fails
+    /// (`DEFAULT_MAX_VISITS_PER_STEP` when unset)
+    #[serde(default)]
+    pub max_visits_per_step: Option<u32>,
+
+    /// Steps a routed run may take in total before it fails (`DEFAULT_MAX_STEPS` when unset)
+    #[serde(default)]
+    pub max_steps: Option<u32>,
+}
+
+/// Visits per step allowed when a pipeline sets no `max_visits_per_step`.
+pub const DEFAULT_MAX_VISITS_PER_STEP: u32 = 100;
+
+/// Step budget of a run when a pipeline sets no `max_steps`.
+pub const DEFAULT_MAX_STEPS: u32 = 10_000;
+
+/// Scheduling strategy for a pipeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ExecutionMode {
+    /// Follow `next`, `next_when`, `otherwise` and `on_error` from the first step
+    #[default]
+    Routed,
+    /// Run every step once its `depends_on` steps succeeded, ignoring routing fields
+    Dag,
 }
 
 pub struct PipelineBuilder {
@@ -17,9 +62,77 @@ impl Pipeline {
             pipeline: Pipeline {
                 key: key.into(),
                 steps: vec![],
+                mode: ExecutionMode::default(),
+                max_concurrency: None,
+                timeout_ms: None,
+                max_visits_per_step: None,
+                max_steps: None,
             },
         }
     }
+
+    /// Returns step keys in dependency order (Kahn's algorithm, ties keep declaration order).
+    /// Fails on dependencies that reference unknown steps or form a cycle.
+    pub fn dependency_order(&self) -> Result<Vec<String>, String> {
+        let mut in_degree: HashMap<&str, usize> = HashMap::new();
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for step in &self.steps {
+            in_degree.entry(step.key.as_str()).or_insert(0);
+        }
+
+        for step in &self.steps {
+            for dep in &step.depends_on {
+                if !in_degree.contains_key(dep.as_str()) {
+                    return Err(format!(
+                        "Step '{}' depends on undefined step '{}'",
+                        step.key, dep
+                    ));
+                }
+                *in_degree.entry(step.key.as_str()).or_insert(0) += 1;
+                dependents
+                    .entry(dep.as_str())
+                    .or_default()
+                    .push(step.key.as_str());
+            }
+        }
+
+        let mut queue: VecDeque<&str> = self
+            .steps
+            .iter()
+            .map(|s| s.key.as_str())
+            .filter(|k| in_degree[k] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.steps.len());
+
+        while let Some(key) = queue.pop_front() {
+            order.push(key.to_string());
+            for dependent in dependents.get(key).into_iter().flatten() {
+                let degree = in_degree
+                    .get_mut(dependent)
+                    .expect("dependent is a known step");
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        if order.len() < self.steps.len() {
+            let cyclic: Vec<&str> = self
+                .steps
+                .iter()
+                .map(|s| s.key.as_str())
+                .filter(|k| in_degree[k] > 0)
+                .collect();
+            return Err(format!(
+                "Dependency cycle between steps: {}",
+                cyclic.join(", ")
+            ));
+        }
+
+        Ok(order)
+    }
 }
 
 impl PipelineBuilder {
@@ -33,6 +146,31 @@ impl PipelineBuilder {
         self
     }
 
+    pub fn mode(mut self, mode: ExecutionMode) -> Self {
+        self.pipeline.mode = mode;
+        self
+    }
+
+    pub fn max_concurrency(mut self, limit: usize) -> Self {
+        self.pipeline.max_concurrency = Some(limit);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.pipeline.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn max_visits_per_step(mut self, limit: u32) -> Self {
+        self.pipeline.max_visits_per_step = Some(limit);
+        self
+    }
+
+    pub fn max_steps(mut self, limit: u32) -> Self {
+        self.pipeline.max_steps = Some(limit);
+        self
+    }
+
     pub fn build(self) -> Pipeline {
         self.pipeline
     }
@@ -60,14 +198,155 @@ pub struct PipelineStep {
     /// Optional fallback linear path
     #[serde(default)]
     pub next: Option<String>,
+
+    /// Branches started concurrently once this step completes
+    #[serde(default)]
+    pub parallel: Option<ParallelBranches>,
+
+    /// Steps that must succeed before this one runs in DAG mode
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// Retry behaviour when the action fails
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+
+    /// Maximum duration of a single attempt
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// Runs another pipeline instead of an action
+    #[serde(default)]
+    pub pipeline: Option<SubPipeline>,
+
+    /// Runs the step once per element of an array
+    #[serde(default)]
+    pub for_each: Option<ForEach>,
+
+    /// Runs the step repeatedly while a condition holds
+    #[serde(default, rename = "while")]
+    pub repeat: Option<WhileLoop>,
+
+    /// Action that undoes this step when a later failure fails the run
+    #[serde(default)]
+    pub compensate: Option<String>,
+
+    /// Suspends the run until an external signal arrives
+    #[serde(default)]
+    pub wait_for: Option<WaitFor>,
+
+    /// Pauses the run for this long
+    #[serde(default)]
+    pub sleep_ms: Option<u64>,
+
+    /// Pauses the run until the timestamp this JSONPath or template selects
+    #[serde(default)]
+    pub wait_until: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConditionalNext {
-    pub when: String,
+    pub when: Condition,
     pub next: String,
 }
 
+/// Fan-out declaration: every branch starts at its own step key and runs
+/// until it routes into `join`, where execution continues once `wait` is satisfied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ParallelBranches {
+    pub branches: Vec<String>,
+    pub join: String,
+    #[serde(default)]
+    pub wait: JoinMode,
+}
+
+/// How many branches a join waits for before continuing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum JoinMode {
+    /// Wait for every branch to succeed
+    #[default]
+    All,
+    /// Continue as soon as one branch succeeds
+    Any,
+    /// Continue once the given number of branches succeeded
+    Count(usize),
+}
+
+/// Child pipeline invocation: the child runs as its own run, linked to the
+/// parent by `parent_run_id`, and its `ExecutionResult` becomes the step output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SubPipeline {
+    /// Key of the pipeline to run
+    pub key: String,
+
+    /// Child payload; JSONPaths and `${...}` templates resolve against the parent run.
+    /// The parent's payload is passed on when unset.
+    #[serde(default)]
+    pub input: Option<Value>,
+}
+
+/// Signal gate: the run is checkpointed as suspended until the signal is
+/// delivered, and the signal payload becomes the step output. Once
+/// `timeout_ms` passes without it, the run continues at `otherwise`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WaitFor {
+    /// Name the signal is delivered under
+    pub signal: String,
+
+    /// How long to wait before taking `otherwise`
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Map over a collection: the step's action or sub-pipeline runs once per
+/// element, which params and inputs read as `$.item` (and its position as
+/// `$.index`). The step output is the array of iteration outputs, in element order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ForEach {
+    /// JSONPath selecting the array, e.g. `$.payload.records`
+    pub items: String,
+
+    /// Elements processed at once; one at a time when unset
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+/// Repeats the step while `when` holds, checking before every iteration.
+/// The condition sees the latest iteration as `$.<step>.output` and the
+/// iteration count as `$.index`. The step output is the array of iteration outputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhileLoop {
+    pub when: Condition,
+
+    /// Guard against runaway loops; the step fails when more iterations are needed
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+}
+
+/// `max_iterations` of a `while` loop that does not set one.
+pub const DEFAULT_MAX_ITERATIONS: u32 = 100;
+
+fn default_max_iterations() -> u32 {
+    DEFAULT_MAX_ITERATIONS
+}
+
+impl JoinMode {
+    /// Number of successful branches required out of `total`.
+    pub fn required(&self, total: usize) -> usize {
+        match self {
+            JoinMode::All => total,
+            JoinMode::Any => total.min(1),
+            JoinMode::Count(n) => (*n).min(total),
+        }
+    }
+}
+
 pub struct PipelineStepBuilder {
     step: PipelineStep,
 }
@@ -79,16 +358,11 @@ impl PipelineStep {
         config: serde_json::Value,
         params: serde_json::Value,
     ) -> Self {
-        Self {
-            key: key.into(),
-            action: action.into(),
-            config: config,
-            params,
-            next: None,
-            on_error: None,
-            next_when: vec![],
-            otherwise: None,
-        }
+        let (key, action): (String, String) = (key.into(), action.into());
+        Self::builder(&key, &action)
+            .config(config)
+            .params(params)
+            .build()
     }
 
     pub fn builder(key: &str, action: &str) -> PipelineStepBuilder {
@@ -102,6 +376,17 @@ impl PipelineStep {
                 otherwise: None,
                 on_error: None,
                 next: None,
+                parallel: None,
+                depends_on: vec![],
+                retry: None,
+                timeout_ms: None,
+                pipeline: None,
+                for_each: None,
+                repeat: None,
+                compensate: None,
+                wait_for: None,
+                sleep_ms: None,
+                wait_until: None,
             },
         }
     }
@@ -133,14 +418,99 @@ impl PipelineStepBuilder {
         self
     }
 
-    pub fn when(mut self, condition: impl Into<String>, next: impl Into<String>) -> Self {
+    /// Routes to `next` when `condition` holds. Conditions used to be taken as
+    /// strings; parse them first, e.g. `.when("$.total > 100".parse()?, "review")`.
+    pub fn when(mut self, condition: Condition, next: impl Into<String>) -> Self {
         self.step.next_when.push(ConditionalNext {
-            when: condition.into(),
+            when: condition,
             next: next.into(),
         });
         self
     }
 
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.step.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.step.retry = Some(policy);
+        self
+    }
+
+    pub fn depends_on(mut self, key: impl Into<String>) -> Self {
+        self.step.depends_on.push(key.into());
+        self
+    }
+
+    pub fn parallel<I, S>(mut self, branches: I, join: impl Into<String>, wait: JoinMode) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.step.parallel = Some(ParallelBranches {
+            branches: branches.into_iter().map(Into::into).collect(),
+            join: join.into(),
+            wait,
+        });
+        self
+    }
+
+    /// Runs the pipeline registered as `key` with `input` as its payload.
+    pub fn pipeline(mut self, key: impl Into<String>, input: Option<Value>) -> Self {
+        self.step.pipeline = Some(SubPipeline {
+            key: key.into(),
+            input,
+        });
+        self
+    }
+
+    /// Runs the step once per element of the array at `items`.
+    pub fn for_each(mut self, items: impl Into<String>, concurrency: Option<usize>) -> Self {
+        self.step.for_each = Some(ForEach {
+            items: items.into(),
+            concurrency,
+        });
+        self
+    }
+
+    /// Repeats the step while `condition` holds, at most `max_iterations` times.
+    pub fn repeat_while(mut self, condition: Condition, max_iterations: u32) -> Self {
+        self.step.repeat = Some(WhileLoop {
+            when: condition,
+            max_iterations,
+        });
+        self
+    }
+
+    /// Undoes the step with the `action` if the run fails after it succeeded.
+    pub fn compensate(mut self, action: impl Into<String>) -> Self {
+        self.step.compensate = Some(action.into());
+        self
+    }
+
+    /// Suspends the run until `signal` arrives, or until `timeout` passes.
+    pub fn wait_for(mut self, signal: impl Into<String>, timeout: Option<Duration>) -> Self {
+        self.step.wait_for = Some(WaitFor {
+            signal: signal.into(),
+            timeout_ms: timeout.map(|t| t.as_millis() as u64),
+        });
+        self
+    }
+
+    /// Pauses the run for `duration`.
+    pub fn sleep(mut self, duration: Duration) -> Self {
+        self.step.sleep_ms = Some(duration.as_millis() as u64);
+        self
+    }
+
+    /// Pauses the run until the RFC 3339 timestamp or epoch milliseconds
+    /// selected by `timestamp`.
+    pub fn wait_until(mut self, timestamp: impl Into<String>) -> Self {
+        self.step.wait_until = Some(timestamp.into());
+        self
+    }
+
     pub fn build(self) -> PipelineStep {
         self.step
     }
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ErrorKind;
+
+/// Retry behaviour for a single step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+
+    #[serde(default)]
+    pub backoff: Backoff,
+
+    /// Base delay between attempts
+    #[serde(default)]
+    pub delay_ms: u64,
+
+    /// Growth factor for exponential backoff
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+
+    /// Upper bound for a single delay
+    #[serde(default)]
+    pub max_delay_ms: Option<u64>,
+
+    /// Randomize each delay between zero and its computed value
+    #[serde(default)]
+    pub jitter: bool,
+
+    /// Stop retrying once this much time has passed since the first attempt
+    #[serde(default)]
+    pub max_elapsed_ms: Option<u64>,
+
+    /// Error kinds worth retrying; empty means every failure is retried
+    #[serde(default)]
+    pub retry_on: Vec<ErrorKind>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Backoff {
+    #[default]
+    Fixed,
+    Exponential,
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+impl RetryPolicy {
+    /// Fixed delay between attempts.
+    pub fn fixed(max_attempts: u32, delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            backoff: Backoff::Fixed,
+            delay_ms: delay.as_millis() as u64,
+            multiplier: default_multiplier(),
+            max_delay_ms: None,
+            jitter: false,
+            max_elapsed_ms: None,
+            retry_on: vec![],
+        }
+    }
+
+    /// Delay starts at `initial` and doubles after every attempt.
+    pub fn exponential(max_attempts: u32, initial: Duration) -> Self {
+        Self {
+            backoff: Backoff::Exponential,
+            ..Self::fixed(max_attempts, initial)
+        }
+    }
+
+    pub fn with_jitter(mut self) -> Self {
+        self.jitter = true;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max: Duration) -> Self {
+        self.max_delay_ms = Some(max.as_millis() as u64);
+        self
+    }
+
+    pub fn with_max_elapsed(mut self, max: Duration) -> Self {
+        self.max_elapsed_ms = Some(max.as_millis() as u64);
+        self
+    }
+
+    pub fn retry_on(mut self, kind: ErrorKind) -> Self {
+        self.retry_on.push(kind);
+        self
+    }
+
+    /// Whether a failure of the given kind is eligible for retry.
+    pub fn retries(&self, kind: ErrorKind) -> bool {
+        self.retry_on.is_empty() || self.retry_on.contains(&kind)
+    }
+
+    /// Delay before the attempt following `attempt` (1-based), or `None` when
+    /// the policy gives up: attempts exhausted, error kind not retryable, or
+    /// the delay would exceed the elapsed-time cap.
+    pub fn next_delay(&self, attempt: u32, kind: ErrorKind, elapsed: Duration) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.retries(kind) {
+            return None;
+        }
+
+        let mut delay_ms = match self.backoff {
+            Backoff::Fixed => self.delay_ms as f64,
+            Backoff::Exponential => {
+                self.delay_ms as f64 * self.multiplier.powi(attempt.saturating_sub(1) as i32)
+            }
+        };
+        if let Some(max) = self.max_delay_ms {
+            delay_ms = delay_ms.min(max as f64);
+        }
+        if self.jitter && delay_ms > 0.0 {
+            delay_ms = rand::rng().random_range(0.0..=delay_ms);
+        }
+
+        let delay = Duration::from_millis(delay_ms as u64);
+        if let Some(max) = self.max_elapsed_ms
+            && elapsed + delay > Duration::from_millis(max)
+        {
+            return None;
+        }
+
+        Some(delay)
+    }
+}
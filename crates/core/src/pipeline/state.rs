@@ -7,6 +7,10 @@ pub enum PipelineState {
     Completed,
     Failed,
     Canceled,
+    /// Failed, then rolled back by the compensating actions of its steps
+    Compensated,
+    /// Waiting for an external signal or a timer
+    Suspended,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -0,0 +1,384 @@
+//! `${...}` interpolation for step params and config.
+//!
+//! ```text
+//! https://${env.API_HOST}/users/${$.payload.id | urlencode}?name=${$.payload.name | default('anon') | upper}
+//! ```
+//!
+//! A placeholder holds a subject and optional filters separated by `|`. Subjects
+//! are resolved in phases: flow resolves `env.NAME` and `secret.NAME` before the
+//! run, and the engine resolves JSONPaths (`$.payload.id`) when the step starts.
+//! A phase leaves placeholders it does not own untouched. A string that is a
+//! single placeholder keeps the JSON type of its value; otherwise values are
+//! rendered as text. `$${` produces a literal `${`; phases before the last render
+//! with [`Template::render_for_later`] so literals and values stay literal.
+//!
+//! Filters: `default(value)`, `upper`, `lower`, `json`, `urlencode`, `base64`.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    /// What to look up, e.g. `$.payload.id` or `env.HOST`
+    pub subject: String,
+    pub filters: Vec<Filter>,
+    /// The placeholder as written, kept for phases that defer it
+    source: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Replaces `null` and missing values
+    Default(Value),
+    Upper,
+    Lower,
+    /// Renders the value as JSON text
+    Json,
+    /// Percent-encodes everything but RFC 3986 unreserved characters
+    UrlEncode,
+    /// Standard base64 with padding
+    Base64,
+}
+
+/// Outcome of resolving a placeholder subject.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lookup {
+    Found(Value),
+    /// The subject belongs to this phase but has no value; filters see `null`
+    Missing,
+    /// Another phase resolves this subject; the placeholder is kept as written
+    Defer,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid template '{template}': {message} at column {column}")]
+pub struct TemplateError {
+    pub template: String,
+    /// 1-based character column of the offending placeholder
+    pub column: usize,
+    pub message: String,
+}
+
+impl Template {
+    /// Cheap check for strings that need parsing at all.
+    pub fn is_template(text: &str) -> bool {
+        text.contains("${")
+    }
+
+    pub fn parse(text: &str) -> Result<Self, TemplateError> {
+        let chars: Vec<char> = text.chars().collect();
+        let error = |column: usize, message: &str| TemplateError {
+            template: text.to_string(),
+            column: column + 1,
+            message: message.to_string(),
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i..].starts_with(&['$', '$', '{']) {
+                literal.push_str("${");
+                i += 3;
+                continue;
+            }
+            if !chars[i..].starts_with(&['$', '{']) {
+                literal.push(chars[i]);
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            let end =
+                closing_brace(&chars, i + 2).ok_or_else(|| error(start, "unterminated '${'"))?;
+            let inner: String = chars[i + 2..end].iter().collect();
+            let placeholder = parse_placeholder(&inner, chars[start..=end].iter().collect())
+                .map_err(|message| error(start, &message))?;
+
+            if !literal.is_empty() {
+                parts.push(Part::Text(std::mem::take(&mut literal)));
+            }
+            parts.push(Part::Placeholder(placeholder));
+            i = end + 1;
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Text(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    pub fn placeholders(&self) -> impl Iterator<Item = &Placeholder> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Placeholder(p) => Some(p),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Renders the template, resolving subjects through `lookup`. Placeholders
+    /// `lookup` defers are kept as written, so another phase can render them.
+    pub fn render(&self, lookup: &mut dyn FnMut(&str) -> Lookup) -> Value {
+        self.render_with(lookup, false)
+    }
+
+    /// Like [`Template::render`], for a phase whose output a later phase parses
+    /// again. Literal `${` in the text and in resolved values comes out as `$${`,
+    /// and a string that would read as a JSONPath as `$$.`, so only the
+    /// deferred placeholders are left for the later phase.
+    pub fn render_for_later(&self, lookup: &mut dyn FnMut(&str) -> Lookup) -> Value {
+        self.render_with(lookup, true)
+    }
+
+    fn render_with(&self, lookup: &mut dyn FnMut(&str) -> Lookup, escape: bool) -> Value {
+        let escaped = |text: &str| match escape {
+            true => text.replace("${", "$${"),
+            false => text.to_string(),
+        };
+        let rendered: Vec<Result<Value, String>> = self
+            .parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => Err(escaped(text)),
+                Part::Placeholder(p) => match lookup(&p.subject) {
+                    Lookup::Found(value) => Ok(p.apply(value)),
+                    Lookup::Missing => Ok(p.apply(Value::Null)),
+                    Lookup::Defer => Err(p.source.clone()),
+                },
+            })
+            .collect();
+
+        // A lone resolved placeholder keeps its JSON type
+        if let [Ok(value)] = rendered.as_slice() {
+            return match value {
+                Value::String(text) if escape => Value::String(escape_path(escaped(text))),
+                _ => value.clone(),
+            };
+        }
+
+        let text = rendered
+            .into_iter()
+            .map(|part| match part {
+                Ok(value) => escaped(&to_text(&value)),
+                Err(text) => text,
+            })
+            .collect();
+        Value::String(if escape { escape_path(text) } else { text })
+    }
+}
+
+impl Placeholder {
+    /// Applies the filters to a resolved value.
+    pub fn apply(&self, value: Value) -> Value {
+        self.filters
+            .iter()
+            .fold(value, |value, filter| filter.apply(value))
+    }
+}
+
+impl Filter {
+    fn parse(name: &str, args: Vec<Value>) -> Result<Self, String> {
+        let filter = match name {
+            "default" => {
+                let [value] = <[Value; 1]>::try_from(args)
+                    .map_err(|_| "default() takes exactly one value".to_string())?;
+                return Ok(Filter::Default(value));
+            }
+            "upper" => Filter::Upper,
+            "lower" => Filter::Lower,
+            "json" => Filter::Json,
+            "urlencode" => Filter::UrlEncode,
+            "base64" => Filter::Base64,
+            other => return Err(format!("unknown filter '{}'", other)),
+        };
+        if !args.is_empty() {
+            return Err(format!("{}() takes no arguments", name));
+        }
+        Ok(filter)
+    }
+
+    fn apply(&self, value: Value) -> Value {
+        match self {
+            Filter::Default(fallback) if value.is_null() => fallback.clone(),
+            Filter::Default(_) => value,
+            Filter::Upper => Value::String(to_text(&value).to_uppercase()),
+            Filter::Lower => Value::String(to_text(&value).to_lowercase()),
+            Filter::Json => Value::String(value.to_string()),
+            Filter::UrlEncode => Value::String(url_encode(&to_text(&value))),
+            Filter::Base64 => Value::String(STANDARD.encode(to_text(&value))),
+        }
+    }
+}
+
+/// Text form used when a value is interpolated: strings as-is, `null` as empty, the rest as JSON.
+/// Escapes a finished string the engine would otherwise look up as a JSONPath.
+fn escape_path(text: String) -> String {
+    if text.starts_with("$.") && !Template::is_template(&text) {
+        format!("${text}")
+    } else {
+        text
+    }
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Index of the `}` closing a placeholder whose body starts at `i`.
+/// Braces inside quotes, brackets or parentheses do not count.
+fn closing_brace(chars: &[char], mut i: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    while i < chars.len() {
+        match (quote, chars[i]) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), '\\') => i += 1,
+            (Some(_), _) => {}
+            (None, c @ ('\'' | '"')) => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth = depth.saturating_sub(1),
+            (None, '}') if depth == 0 => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Splits `inner` on `|` outside quotes, brackets and parentheses.
+fn split_pipes(inner: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), '\\') => {
+                segments.last_mut().unwrap().push(c);
+                if let Some(escaped) = chars.next() {
+                    segments.last_mut().unwrap().push(escaped);
+                }
+                continue;
+            }
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth = depth.saturating_sub(1),
+            (None, '|') if depth == 0 => {
+                segments.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        segments.last_mut().unwrap().push(c);
+    }
+    segments
+}
+
+fn parse_placeholder(inner: &str, source: String) -> Result<Placeholder, String> {
+    let mut segments = split_pipes(inner).into_iter();
+    let subject = segments.next().unwrap_or_default().trim().to_string();
+    if subject.is_empty() {
+        return Err("empty placeholder".into());
+    }
+
+    let filters = segments
+        .map(|segment| {
+            let segment = segment.trim();
+            let (name, args) = match segment.split_once('(') {
+                Some((name, rest)) => {
+                    let args = rest
+                        .strip_suffix(')')
+                        .ok_or_else(|| format!("missing ')' in filter '{}'", segment))?;
+                    (name.trim(), parse_args(args)?)
+                }
+                None => (segment, Vec::new()),
+            };
+            if name.is_empty() {
+                return Err("empty filter".to_string());
+            }
+            Filter::parse(name, args)
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Placeholder {
+        subject,
+        filters,
+        source,
+    })
+}
+
+/// Filter arguments: a comma-separated list of quoted strings or JSON scalars.
+fn parse_args(args: &str) -> Result<Vec<Value>, String> {
+    let args = args.trim();
+    if args.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut values = Vec::new();
+    let mut chars = args.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let value = match chars.peek() {
+            Some(&q @ ('\'' | '"')) => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err("unterminated string in filter argument".into()),
+                        Some('\\') => text.extend(chars.next()),
+                        Some(c) if c == q => break,
+                        Some(c) => text.push(c),
+                    }
+                }
+                Value::String(text)
+            }
+            _ => {
+                let mut raw = String::new();
+                while let Some(c) = chars.next_if(|c| *c != ',') {
+                    raw.push(c);
+                }
+                let raw = raw.trim();
+                serde_json::from_str(raw)
+                    .ok()
+                    .filter(|v: &Value| !v.is_object() && !v.is_array())
+                    .ok_or_else(|| format!("invalid filter argument '{}'; quote strings", raw))?
+            }
+        };
+        values.push(value);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            None => return Ok(values),
+            Some(',') => continue,
+            Some(c) => return Err(format!("unexpected '{}' in filter arguments", c)),
+        }
+    }
+}
+
+/// Unreserved characters of RFC 3986 stay as they are.
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+fn url_encode(text: &str) -> String {
+    utf8_percent_encode(text, URL_ENCODE_SET).to_string()
+}
@@ -1,16 +1,25 @@
 // Action layer
 pub use crate::action::action::Action;
-pub use crate::action::result::{ActionResult, ExecutionStatus};
+pub use crate::action::result::{ActionAttempt, ActionResult, ExecutionStatus};
 
 // Context layer
 pub use crate::context::action_context::ActionContext;
 pub use crate::context::execution_context::ExecutionContext;
 
 // Pipeline layer
+pub use crate::pipeline::condition::Condition;
 pub use crate::pipeline::hook::PipelineHook;
 pub use crate::pipeline::metadata::{ActionMetadata, PipelineMetadata};
 pub use crate::pipeline::pipeline;
 pub use crate::pipeline::pipeline::PipelineStep;
+pub use crate::pipeline::retry::RetryPolicy;
 pub use crate::pipeline::state::{ActionState, PipelineState};
+// State layer
+pub use crate::state::checkpoint::RunCheckpoint;
+pub use crate::state::query::RunQuery;
+pub use crate::state::queue::QueuedRun;
+pub use crate::state::state_store::StateStore;
+pub use crate::state::timer::RunTimer;
+
 // Errors
-pub use crate::error::Error;
+pub use crate::error::{Error, ErrorKind};
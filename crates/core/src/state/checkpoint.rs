@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::execution_context::ExecutionContext,
+    pipeline::{pipeline::Pipeline, state::PipelineState},
+};
+
+/// Snapshot of an in-flight run, written after every step so the run can be resumed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCheckpoint {
+    pub run_id: String,
+    pub pipeline: Pipeline,
+    pub context: ExecutionContext,
+
+    /// Step the run continues with; `None` once routing has nowhere left to go
+    pub next_step: Option<String>,
+
+    pub state: PipelineState,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RunCheckpoint {
+    pub fn new(
+        pipeline: &Pipeline,
+        context: &ExecutionContext,
+        next_step: Option<String>,
+        state: PipelineState,
+    ) -> Self {
+        Self {
+            run_id: context.run_id.clone(),
+            pipeline: pipeline.clone(),
+            context: context.clone(),
+            next_step,
+            state,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Whether the run stopped before finishing and can be picked up again.
+    pub fn is_resumable(&self) -> bool {
+        !matches!(
+            self.state,
+            PipelineState::Completed | PipelineState::Compensated
+        )
+    }
+}
@@ -0,0 +1,182 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::{
+    action::result::{ExecutionResult, ExecutionStatus},
+    pipeline::pipeline::Pipeline,
+    state::{
+        checkpoint::RunCheckpoint, query::RunQuery, queue::QueuedRun, state_store::StateStore,
+        timer::RunTimer,
+    },
+};
+
+/// Simple in-memory state store for testing and examples.
+#[derive(Default)]
+pub struct InMemoryStateStore {
+    results: RwLock<HashMap<String, ExecutionResult>>,
+    steps: RwLock<HashMap<String, HashMap<String, Value>>>,
+    checkpoints: RwLock<HashMap<String, RunCheckpoint>>,
+    pipelines: RwLock<HashMap<String, Pipeline>>,
+    timers: RwLock<HashMap<String, RunTimer>>,
+    queue: RwLock<HashMap<String, QueuedRun>>,
+}
+
+#[async_trait]
+impl StateStore for InMemoryStateStore {
+    async fn save_result(&self, result: &ExecutionResult) -> Result<(), String> {
+        let mut guard = self.results.write().map_err(|e| e.to_string())?;
+        guard.insert(result.run_id.clone(), result.clone());
+        Ok(())
+    }
+
+    async fn load_result(&self, run_id: &str) -> Result<Option<ExecutionResult>, String> {
+        let guard = self.results.read().map_err(|e| e.to_string())?;
+        Ok(guard.get(run_id).cloned())
+    }
+
+    async fn update_step(&self, run_id: &str, step_name: &str, data: Value) -> Result<(), String> {
+        let mut guard = self.steps.write().map_err(|e| e.to_string())?;
+        guard
+            .entry(run_id.to_string())
+            .or_default()
+            .insert(step_name.to_string(), data);
+        Ok(())
+    }
+
+    async fn load_steps(&self, run_id: &str) -> Result<HashMap<String, Value>, String> {
+        let guard = self.steps.read().map_err(|e| e.to_string())?;
+        Ok(guard.get(run_id).cloned().unwrap_or_default())
+    }
+
+    async fn save_pipeline(&self, pipeline: &Pipeline) -> Result<(), String> {
+        let mut guard = self.pipelines.write().map_err(|e| e.to_string())?;
+        guard.insert(pipeline.key.clone(), pipeline.clone());
+        Ok(())
+    }
+
+    async fn load_pipeline(&self, key: &str) -> Result<Option<Pipeline>, String> {
+        let guard = self.pipelines.read().map_err(|e| e.to_string())?;
+        Ok(guard.get(key).cloned())
+    }
+
+    async fn list_pipelines(&self) -> Result<Vec<String>, String> {
+        let guard = self.pipelines.read().map_err(|e| e.to_string())?;
+        let mut keys: Vec<String> = guard.keys().cloned().collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &RunCheckpoint) -> Result<(), String> {
+        let mut guard = self.checkpoints.write().map_err(|e| e.to_string())?;
+        guard.insert(checkpoint.run_id.clone(), checkpoint.clone());
+        Ok(())
+    }
+
+    async fn load_checkpoint(&self, run_id: &str) -> Result<Option<RunCheckpoint>, String> {
+        let guard = self.checkpoints.read().map_err(|e| e.to_string())?;
+        Ok(guard.get(run_id).cloned())
+    }
+
+    async fn list_results(&self, query: &RunQuery) -> Result<Vec<ExecutionResult>, String> {
+        let guard = self.results.read().map_err(|e| e.to_string())?;
+        Ok(query.apply(guard.values().cloned()))
+    }
+
+    async fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        let mut results = self.results.write().map_err(|e| e.to_string())?;
+        let expired: Vec<String> = results
+            .values()
+            .filter(|r| r.metrics.finished_at < cutoff && r.status != ExecutionStatus::Suspended)
+            .map(|r| r.run_id.clone())
+            .collect();
+
+        let mut steps = self.steps.write().map_err(|e| e.to_string())?;
+        let mut checkpoints = self.checkpoints.write().map_err(|e| e.to_string())?;
+        for run_id in &expired {
+            results.remove(run_id);
+            steps.remove(run_id);
+            checkpoints.remove(run_id);
+        }
+        Ok(expired.len())
+    }
+
+    async fn save_timer(&self, timer: &RunTimer) -> Result<(), String> {
+        let mut guard = self.timers.write().map_err(|e| e.to_string())?;
+        guard.insert(timer.run_id.clone(), timer.clone());
+        Ok(())
+    }
+
+    async fn due_timers(&self, now: DateTime<Utc>) -> Result<Vec<RunTimer>, String> {
+        let guard = self.timers.read().map_err(|e| e.to_string())?;
+        let mut due: Vec<RunTimer> = guard
+            .values()
+            .filter(|t| t.fire_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|t| t.fire_at);
+        Ok(due)
+    }
+
+    async fn delete_timer(&self, run_id: &str) -> Result<(), String> {
+        let mut guard = self.timers.write().map_err(|e| e.to_string())?;
+        guard.remove(run_id);
+        Ok(())
+    }
+
+    async fn save_queued(&self, run: &QueuedRun) -> Result<(), String> {
+        let mut guard = self.queue.write().map_err(|e| e.to_string())?;
+        guard.insert(run.run_id.clone(), run.clone());
+        Ok(())
+    }
+
+    async fn claim_queued(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        skip: &[String],
+    ) -> Result<Option<QueuedRun>, String> {
+        let mut guard = self.queue.write().map_err(|e| e.to_string())?;
+        let Some(run) = guard
+            .values_mut()
+            .filter(|r| r.visible_at <= now && !skip.contains(&r.pipeline_key))
+            .min_by(|a, b| a.claim_order(b))
+        else {
+            return Ok(None);
+        };
+        run.visible_at = lease_until;
+        run.deliveries += 1;
+        Ok(Some(run.clone()))
+    }
+
+    async fn renew_lease(
+        &self,
+        run_id: &str,
+        deliveries: u32,
+        until: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        let mut guard = self.queue.write().map_err(|e| e.to_string())?;
+        match guard.get_mut(run_id) {
+            Some(run) if run.deliveries == deliveries => {
+                run.visible_at = until;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn list_queued(&self) -> Result<Vec<QueuedRun>, String> {
+        let guard = self.queue.read().map_err(|e| e.to_string())?;
+        let mut runs: Vec<QueuedRun> = guard.values().cloned().collect();
+        runs.sort_by(|a, b| a.claim_order(b));
+        Ok(runs)
+    }
+
+    async fn delete_queued(&self, run_id: &str) -> Result<(), String> {
+        let mut guard = self.queue.write().map_err(|e| e.to_string())?;
+        guard.remove(run_id);
+        Ok(())
+    }
+}
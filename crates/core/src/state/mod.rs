@@ -0,0 +1,13 @@
+pub mod checkpoint;
+pub mod in_memory;
+pub mod query;
+pub mod queue;
+pub mod state_store;
+pub mod timer;
+
+pub use checkpoint::RunCheckpoint;
+pub use in_memory::InMemoryStateStore;
+pub use query::RunQuery;
+pub use queue::QueuedRun;
+pub use state_store::{STATE_STORE_VERSION, StateStore};
+pub use timer::RunTimer;
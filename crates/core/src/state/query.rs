@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::action::result::{ExecutionResult, ExecutionStatus};
+
+/// Filter for listing stored runs. Empty fields match every run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunQuery {
+    pub pipeline_key: Option<String>,
+    pub status: Option<ExecutionStatus>,
+
+    /// Maximum number of runs returned, newest first
+    pub limit: Option<usize>,
+}
+
+impl RunQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pipeline(mut self, key: impl Into<String>) -> Self {
+        self.pipeline_key = Some(key.into());
+        self
+    }
+
+    pub fn status(mut self, status: ExecutionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn matches(&self, result: &ExecutionResult) -> bool {
+        self.pipeline_key
+            .as_ref()
+            .is_none_or(|key| result.pipeline_key.as_ref() == Some(key))
+            && self.status.as_ref().is_none_or(|s| &result.status == s)
+    }
+
+    /// Filters `results`, then orders them newest first and applies the limit.
+    pub fn apply(
+        &self,
+        results: impl IntoIterator<Item = ExecutionResult>,
+    ) -> Vec<ExecutionResult> {
+        let mut matched: Vec<_> = results.into_iter().filter(|r| self.matches(r)).collect();
+        matched.sort_by_key(|r| std::cmp::Reverse(r.metrics.started_at));
+        if let Some(limit) = self.limit {
+            matched.truncate(limit);
+        }
+        matched
+    }
+}
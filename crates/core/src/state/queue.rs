@@ -0,0 +1,53 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A run waiting in the dispatch queue, or leased to a worker until `visible_at`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedRun {
+    pub run_id: String,
+    pub pipeline_key: String,
+    pub input: Value,
+
+    /// Higher priorities are claimed first; equal ones in enqueue order
+    pub priority: i32,
+    pub enqueued_at: DateTime<Utc>,
+
+    /// The run can be claimed from this instant on
+    pub visible_at: DateTime<Utc>,
+
+    /// Times a worker claimed the run
+    pub deliveries: u32,
+}
+
+impl QueuedRun {
+    /// A run that can be claimed right away.
+    pub fn new(run_id: impl Into<String>, pipeline_key: impl Into<String>, input: Value) -> Self {
+        let now = Utc::now();
+        Self {
+            run_id: run_id.into(),
+            pipeline_key: pipeline_key.into(),
+            input,
+            priority: 0,
+            enqueued_at: now,
+            visible_at: now,
+            deliveries: 0,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Order in which stores hand out runs: highest priority, then oldest.
+    pub fn claim_order(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then(self.enqueued_at.cmp(&other.enqueued_at))
+            .then_with(|| self.run_id.cmp(&other.run_id))
+    }
+}
@@ -1,16 +1,130 @@
-use crate::prelude::ExecutionResult;
+use crate::{
+    action::result::ExecutionResult,
+    pipeline::pipeline::Pipeline,
+    state::{checkpoint::RunCheckpoint, query::RunQuery, queue::QueuedRun, timer::RunTimer},
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
+/// Layout version of persisted records. Durable stores record it and refuse
+/// to open data written by a newer version.
+pub const STATE_STORE_VERSION: u32 = 1;
+
+/// Persistence for runs, their steps, checkpoints and timers, queued runs,
+/// and pipeline definitions.
+///
+/// This is the single storage trait shared by the engine and flow; a backend
+/// implements it once to be usable everywhere. Timers and the run queue are
+/// optional: their methods fail by default, so durable sleeps, signal
+/// timeouts and `RunDispatcher` are unavailable on a store that leaves them out.
 #[async_trait]
 pub trait StateStore: Send + Sync {
+    /// Layout version this store reads and writes.
+    fn version(&self) -> u32 {
+        STATE_STORE_VERSION
+    }
+
+    // --- Run records ---
+
+    /// Inserts or replaces the result for `result.run_id`.
     async fn save_result(&self, result: &ExecutionResult) -> Result<(), String>;
 
     async fn load_result(&self, run_id: &str) -> Result<Option<ExecutionResult>, String>;
 
+    /// Stored results matching `query`, newest first.
+    async fn list_results(&self, query: &RunQuery) -> Result<Vec<ExecutionResult>, String>;
+
+    /// Removes runs that finished before `cutoff`, with their steps and checkpoints.
+    /// Suspended runs are kept. Returns the number of runs removed.
+    async fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize, String>;
+
+    // --- Step records ---
+
     async fn update_step(
         &self,
         run_id: &str,
         step_name: &str,
         data: serde_json::Value,
     ) -> Result<(), String>;
+
+    /// Step data recorded through `update_step`, keyed by step name.
+    async fn load_steps(&self, run_id: &str) -> Result<HashMap<String, serde_json::Value>, String>;
+
+    // --- Pipeline definitions ---
+
+    /// Inserts or replaces the definition stored under `pipeline.key`.
+    async fn save_pipeline(&self, pipeline: &Pipeline) -> Result<(), String>;
+
+    async fn load_pipeline(&self, key: &str) -> Result<Option<Pipeline>, String>;
+
+    /// Keys of all stored pipelines, sorted.
+    async fn list_pipelines(&self) -> Result<Vec<String>, String>;
+
+    // --- Checkpoints ---
+
+    /// Overwrites the checkpoint for `checkpoint.run_id`.
+    async fn save_checkpoint(&self, checkpoint: &RunCheckpoint) -> Result<(), String>;
+
+    async fn load_checkpoint(&self, run_id: &str) -> Result<Option<RunCheckpoint>, String>;
+
+    // --- Timers ---
+
+    /// Schedules `timer`, replacing any earlier timer of the same run.
+    async fn save_timer(&self, _timer: &RunTimer) -> Result<(), String> {
+        unsupported("Timers")
+    }
+
+    /// Timers firing at or before `now`, earliest first.
+    async fn due_timers(&self, _now: DateTime<Utc>) -> Result<Vec<RunTimer>, String> {
+        unsupported("Timers")
+    }
+
+    async fn delete_timer(&self, _run_id: &str) -> Result<(), String> {
+        unsupported("Timers")
+    }
+
+    // --- Run queue ---
+
+    /// Queues `run`, replacing any queued run with the same id.
+    async fn save_queued(&self, _run: &QueuedRun) -> Result<(), String> {
+        unsupported("Queued runs")
+    }
+
+    /// Leases the first run visible at `now` in claim order, skipping runs of
+    /// the pipelines in `skip`. The run stays queued but is hidden until
+    /// `lease_until`, and its delivery count goes up.
+    async fn claim_queued(
+        &self,
+        _now: DateTime<Utc>,
+        _lease_until: DateTime<Utc>,
+        _skip: &[String],
+    ) -> Result<Option<QueuedRun>, String> {
+        unsupported("Queued runs")
+    }
+
+    /// Extends the lease of a claimed run to `until` while the run is still
+    /// queued and its latest delivery is `deliveries`. Returns whether it was
+    /// renewed; `false` means the lease ran out and another worker claimed it.
+    async fn renew_lease(
+        &self,
+        _run_id: &str,
+        _deliveries: u32,
+        _until: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        unsupported("Queued runs")
+    }
+
+    /// All queued runs in claim order, leased or not.
+    async fn list_queued(&self) -> Result<Vec<QueuedRun>, String> {
+        unsupported("Queued runs")
+    }
+
+    async fn delete_queued(&self, _run_id: &str) -> Result<(), String> {
+        unsupported("Queued runs")
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, String> {
+    Err(format!("{} are not supported by this state store", what))
 }
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Durable wake-up for a suspended run; a run has at most one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunTimer {
+    pub run_id: String,
+    pub fire_at: DateTime<Utc>,
+}
+
+impl RunTimer {
+    pub fn new(run_id: impl Into<String>, fire_at: DateTime<Utc>) -> Self {
+        Self {
+            run_id: run_id.into(),
+            fire_at,
+        }
+    }
+}
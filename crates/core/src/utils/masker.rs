@@ -0,0 +1,36 @@
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+/// Masks known secret values in text or structured JSON.
+#[derive(Clone, Debug, Default)]
+pub struct SensitiveMasker {
+    secrets: Vec<String>,
+}
+
+impl SensitiveMasker {
+    pub fn new(secrets: Vec<String>) -> Self {
+        Self { secrets }
+    }
+
+    pub fn mask_text(&self, text: &str) -> String {
+        let mut masked = text.to_string();
+        for secret in &self.secrets {
+            if !secret.is_empty() {
+                masked = masked.replace(secret, "****");
+            }
+        }
+        masked
+    }
+
+    pub fn mask_value(&self, value: &Value) -> Value {
+        let text = serde_json::to_string(value).unwrap_or_default();
+        let masked = self.mask_text(&text);
+        serde_json::from_str(&masked).unwrap_or(Value::Null)
+    }
+
+    /// A copy of `value` with the secrets masked in its JSON form.
+    pub fn mask<T: Serialize + DeserializeOwned>(&self, value: &T) -> serde_json::Result<T> {
+        let value = serde_json::to_value(value)?;
+        serde_json::from_value(self.mask_value(&value))
+    }
+}
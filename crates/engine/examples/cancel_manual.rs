@@ -48,6 +48,7 @@ impl Action for RepetitiveAction {
 /// ----------------------------------------------
 /// Manual cancellation source
 /// ----------------------------------------------
+#[derive(Default)]
 pub struct ManualCancellationSource {
     triggered: Arc<AtomicBool>,
 }
@@ -119,10 +120,9 @@ async fn main() {
     );
 
     // Create pipeline with 2 long actions
-    let pipeline = Pipeline {
-        key: "manual_cancel_demo".into(),
-        steps: vec![ps1, ps2],
-    };
+    let pipeline = Pipeline::builder("manual_cancel_demo")
+        .steps(vec![ps1, ps2])
+        .build();
 
     // Trigger cancellation after 1 second
     let cancel_trigger = manual_source.clone();
@@ -98,9 +98,8 @@ async fn main() {
         .with_action_resolver(DummyResolver);
 
     // Create pipeline definition (string-based)
-    let pipeline = Pipeline {
-        key: "cancel_demo".into(),
-        steps: vec![
+    let pipeline = Pipeline::builder("cancel_demo")
+        .steps(vec![
             PipelineStep::builder("sfdo", "first")
                 .params(json!({"delay_ms": 300}))
                 .next("second")
@@ -108,8 +107,8 @@ async fn main() {
             PipelineStep::builder("second", "second")
                 .params(json!({"delay_ms": 300}))
                 .build(),
-        ],
-    };
+        ])
+        .build();
 
     let input = json!({ "test": "foobar" });
 
@@ -98,15 +98,14 @@ async fn main() {
         .with_action_resolver(DummyResolver);
 
     // Define pipeline
-    let pipeline = Pipeline {
-        key: "cancel_source_demo".into(),
-        steps: vec![
+    let pipeline = Pipeline::builder("cancel_source_demo")
+        .steps(vec![
             PipelineStep::builder("prepare_data", "prepare_data")
                 .next("process_data")
                 .build(),
             PipelineStep::builder("process_data", "process_data").build(),
-        ],
-    };
+        ])
+        .build();
 
     // Input
     let input = json!({ "test": "foobar" });
@@ -115,8 +115,8 @@ async fn main() -> Result<(), Error> {
         .step(
             PipelineStep::builder("check_region", "ryvus/log")
                 .params(json!({ "message": "Determining upload target..." }))
-                .when("$.payload.region == 'eu'", "eu_upload")
-                .when("$.payload.region == 'us'", "us_upload")
+                .when("$.payload.region == 'eu'".parse()?, "eu_upload")
+                .when("$.payload.region == 'us'".parse()?, "us_upload")
                 .otherwise("upload_fallback")
                 .on_error("error_handler")
                 .build(),
@@ -50,10 +50,9 @@ impl ActionHook for PrintHook {
 #[tokio::main]
 async fn main() {
     //  Pipeline uses steps with string-based action names
-    let pipeline = Pipeline {
-        key: "cmd_hooks".into(),
-        steps: vec![PipelineStep::builder("foobar", "foobar").build()],
-    };
+    let pipeline = Pipeline::builder("cmd_hooks")
+        .steps(vec![PipelineStep::builder("foobar", "foobar").build()])
+        .build();
 
     let input = json!({ "test": "foobar" });
 
@@ -65,9 +65,8 @@ impl ActionResolver for DummyResolver {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Define pipeline (now string-based)
-    let pipeline = Pipeline {
-        key: "context_demo".into(),
-        steps: vec![
+    let pipeline = Pipeline::builder("context_demo")
+        .steps(vec![
             PipelineStep::builder("first", "first")
                 .next("second")
                 .build(),
@@ -75,8 +74,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .next("third")
                 .build(),
             PipelineStep::builder("third", "third").build(),
-        ],
-    };
+        ])
+        .build();
 
     // Build engine using current architecture
     let engine = Engine::default().with_action_resolver(DummyResolver);
@@ -91,11 +91,9 @@ impl ActionResolver for DummyResolver {
 /// ------------------------------------------------
 #[tokio::main]
 async fn main() {
-    let pipeline = Pipeline {
-        key: "with_hooks".into(),
-
-        steps: vec![PipelineStep::builder("dummy", "dummy").build()],
-    };
+    let pipeline = Pipeline::builder("with_hooks")
+        .steps(vec![PipelineStep::builder("dummy", "dummy").build()])
+        .build();
 
     let input = json!({ "test": "foobar" });
 
@@ -26,6 +26,12 @@ where
     }
 }
 
+impl Default for DefaultActionResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DefaultActionResolver {
     pub fn new() -> Self {
         Self {
@@ -48,6 +54,11 @@ impl DefaultActionResolver {
         self.registry.len()
     }
 
+    /// Returns `true` when no actions are registered.
+    pub fn is_empty(&self) -> bool {
+        self.registry.is_empty()
+    }
+
     /// Returns all registered Actions as *new owned clones*.
     pub fn all(&self) -> Vec<Box<dyn Action + Send + Sync>> {
         let mut entries: Vec<_> = self.registry.iter().collect();
@@ -68,6 +79,10 @@ pub trait ActionResolver: Send + Sync {
     fn len(&self) -> usize {
         0
     }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,5 @@
+#[cfg(feature = "script")]
+pub mod script;
+
+#[cfg(feature = "script")]
+pub use script::{ScriptAction, ScriptConfig};
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rhai::{module_resolvers::DummyModuleResolver, Dynamic, Engine as ScriptEngine, Scope, AST};
+use ryvus_core::{
+    error::Error,
+    prelude::{Action, ActionContext, ActionResult},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, info};
+
+/// Step config for [`ScriptAction`].
+///
+/// ```json
+/// { "script": "#{ total: input.items.reduce(|sum, i| sum + i.price, 0) }", "max_operations": 50000 }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptConfig {
+    /// Rhai source; the value of the last expression becomes the step output
+    pub script: String,
+
+    /// CPU budget: evaluation fails after this many operations
+    #[serde(default = "default_max_operations")]
+    pub max_operations: u64,
+
+    /// Longest string the script may build, in bytes
+    #[serde(default = "default_max_string_size")]
+    pub max_string_size: usize,
+
+    /// Most elements in one array
+    #[serde(default = "default_max_collection_size")]
+    pub max_array_size: usize,
+
+    /// Most properties in one object map
+    #[serde(default = "default_max_collection_size")]
+    pub max_map_size: usize,
+
+    /// Deepest function call nesting
+    #[serde(default = "default_max_call_levels")]
+    pub max_call_levels: usize,
+}
+
+fn default_max_operations() -> u64 {
+    100_000
+}
+
+fn default_max_string_size() -> usize {
+    1024 * 1024
+}
+
+fn default_max_collection_size() -> usize {
+    10_000
+}
+
+fn default_max_call_levels() -> usize {
+    32
+}
+
+/// Built-in `ryvus/script` action: transforms JSON with a sandboxed [Rhai](https://rhai.rs) script.
+///
+/// The script sees the step input as `input` and the run as `context`
+/// (`context.payload`, `context.<step>.output`, `context.output`).
+/// It has no file, network or module access, and it runs under operation,
+/// size and call-depth limits. A script that exceeds them fails the step.
+#[derive(Clone, Default)]
+pub struct ScriptAction {
+    compiled: Option<Arc<Compiled>>,
+}
+
+struct Compiled {
+    engine: ScriptEngine,
+    ast: AST,
+}
+
+impl ScriptAction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Action for ScriptAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let compiled = self
+            .compiled
+            .clone()
+            .ok_or_else(|| Error::Config("ryvus/script requires a 'script' config".into()))?;
+
+        let input = to_dynamic(ctx.input.clone().unwrap_or(Value::Null))?;
+        let context = to_dynamic(ctx.context.clone().unwrap_or(Value::Null))?;
+
+        // Evaluation is CPU-bound; keep it off the async workers
+        let output = tokio::task::spawn_blocking(move || {
+            let mut scope = Scope::new();
+            scope.push_constant_dynamic("input", input);
+            scope.push_constant_dynamic("context", context);
+            compiled
+                .engine
+                .eval_ast_with_scope::<Dynamic>(&mut scope, &compiled.ast)
+                .map_err(|e| Error::Action(format!("Script failed: {}", e)))
+        })
+        .await
+        .map_err(|e| Error::System(e.to_string()))??;
+
+        let output: Value = rhai::serde::from_dynamic(&output)
+            .map_err(|e| Error::Action(format!("Script output is not JSON: {}", e)))?;
+        Ok(ActionResult::success(output))
+    }
+
+    fn key(&self) -> &str {
+        "ryvus/script"
+    }
+
+    async fn configure(&mut self, config: Value) -> Result<(), String> {
+        let config: ScriptConfig =
+            serde_json::from_value(config).map_err(|e| format!("ryvus/script config: {}", e))?;
+
+        let engine = sandboxed_engine(&config);
+        let ast = engine
+            .compile(&config.script)
+            .map_err(|e| format!("ryvus/script does not compile: {}", e))?;
+
+        self.compiled = Some(Arc::new(Compiled { engine, ast }));
+        Ok(())
+    }
+}
+
+fn sandboxed_engine(config: &ScriptConfig) -> ScriptEngine {
+    let mut engine = ScriptEngine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(config.max_operations)
+        .set_max_string_size(config.max_string_size)
+        .set_max_array_size(config.max_array_size)
+        .set_max_map_size(config.max_map_size)
+        .set_max_call_levels(config.max_call_levels)
+        .set_max_expr_depths(64, 32)
+        .on_print(|text| info!("ryvus/script: {}", text))
+        .on_debug(|text, _, pos| debug!("ryvus/script {}: {}", pos, text));
+    engine
+}
+
+fn to_dynamic(value: Value) -> Result<Dynamic, Error> {
+    rhai::serde::to_dynamic(value).map_err(|e| Error::Action(e.to_string()))
+}
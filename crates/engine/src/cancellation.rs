@@ -19,6 +19,12 @@ pub struct CancellationListener {
     handle: Option<JoinHandle<()>>,
 }
 
+impl Default for CancellationListener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CancellationListener {
     /// Create a new cancellation listener with its own cancellation token.
     pub fn new() -> Self {
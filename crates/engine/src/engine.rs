@@ -6,19 +6,30 @@ use crate::hook_resolver::{
     PipelineHookResolver,
 };
 use crate::mapper::mapper::{DefaultMapper, Mapper};
-use crate::pipeline::pipeline_executor::PipelineExecutor;
+use crate::pipeline::pipeline_executor::{PipelineExecutor, SubPipelines};
+use crate::pipeline_resolver::{DefaultPipelineResolver, PipelineResolver};
 
 use chrono::Utc;
 use ryvus_core::action::result::{ExecutionMetrics, ExecutionResult};
 use ryvus_core::pipeline::hook::ActionHook;
 use ryvus_core::prelude::pipeline::Pipeline;
-use ryvus_core::prelude::{Action, ActionContext, ExecutionStatus, PipelineHook};
+use ryvus_core::prelude::{
+    Action, ActionContext, ExecutionContext, ExecutionStatus, PipelineHook, PipelineState,
+    RunCheckpoint,
+};
+use ryvus_core::state::state_store::StateStore;
+use ryvus_core::utils::id::generate_id;
+use ryvus_core::utils::masker::SensitiveMasker;
 
 use async_trait::async_trait;
 use serde_json::Value;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_util::sync::CancellationToken;
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Shortest delay that suspends a run on a durable timer instead of sleeping.
+pub const DEFAULT_DURABLE_TIMER_THRESHOLD: Duration = Duration::from_secs(60);
 
 /// ------------------------------------------------------
 /// Engine definition with ActionResolver support
@@ -41,6 +52,11 @@ pub struct Engine<
     pub pipeline_hook_resolver: Arc<PHR>,
     pub action_resolver: Box<AR>,
     pub cancel_listener: Option<CancellationListener>,
+    pub pipeline_timeout: Option<Duration>,
+    pub state_store: Option<Arc<dyn StateStore>>,
+    /// Delays at least this long suspend the run on a durable timer when a state store is set
+    pub durable_timer_threshold: Duration,
+    pub pipelines: Arc<DefaultPipelineResolver>,
 }
 
 impl<M, HR, PHR, AR> Engine<M, HR, PHR, AR>
@@ -64,6 +80,10 @@ where
             pipeline_hook_resolver: Arc::new(pipeline_hook_resolver),
             action_resolver: Box::new(action_resolver),
             cancel_listener: None,
+            pipeline_timeout: None,
+            state_store: None,
+            durable_timer_threshold: DEFAULT_DURABLE_TIMER_THRESHOLD,
+            pipelines: Arc::new(DefaultPipelineResolver::new()),
         }
     }
 
@@ -79,6 +99,10 @@ where
             pipeline_hook_resolver: self.pipeline_hook_resolver,
             action_resolver: self.action_resolver,
             cancel_listener: self.cancel_listener,
+            pipeline_timeout: self.pipeline_timeout,
+            state_store: self.state_store,
+            durable_timer_threshold: self.durable_timer_threshold,
+            pipelines: self.pipelines,
         }
     }
 
@@ -107,6 +131,10 @@ where
             pipeline_hook_resolver: self.pipeline_hook_resolver,
             action_resolver: self.action_resolver,
             cancel_listener: self.cancel_listener,
+            pipeline_timeout: self.pipeline_timeout,
+            state_store: self.state_store,
+            durable_timer_threshold: self.durable_timer_threshold,
+            pipelines: self.pipelines,
         }
     }
 
@@ -125,6 +153,10 @@ where
             pipeline_hook_resolver: Arc::new(pipeline_hook_resolver),
             action_resolver: self.action_resolver,
             cancel_listener: self.cancel_listener,
+            pipeline_timeout: self.pipeline_timeout,
+            state_store: self.state_store,
+            durable_timer_threshold: self.durable_timer_threshold,
+            pipelines: self.pipelines,
         }
     }
 
@@ -146,6 +178,10 @@ where
             pipeline_hook_resolver: self.pipeline_hook_resolver,
             action_resolver: Box::new(action_resolver),
             cancel_listener: self.cancel_listener,
+            pipeline_timeout: self.pipeline_timeout,
+            state_store: self.state_store,
+            durable_timer_threshold: self.durable_timer_threshold,
+            pipelines: self.pipelines,
         }
     }
 
@@ -154,19 +190,246 @@ where
         self
     }
 
+    /// Default deadline for every run; a pipeline's own `timeout_ms` wins when shorter.
+    pub fn with_pipeline_timeout(mut self, timeout: Duration) -> Self {
+        self.pipeline_timeout = Some(timeout);
+        self
+    }
+
+    /// Checkpoints every run to `store` and saves its final result there, enabling `resume`.
+    pub fn with_state_store(mut self, store: Arc<dyn StateStore>) -> Self {
+        self.state_store = Some(store);
+        self
+    }
+
+    /// Delays of at least `threshold` suspend the run on a timer in the state
+    /// store; shorter ones sleep inline.
+    pub fn with_durable_timer_threshold(mut self, threshold: Duration) -> Self {
+        self.durable_timer_threshold = threshold;
+        self
+    }
+
+    /// Registers `pipeline` for sub-pipeline steps, replacing any pipeline with the same key.
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        Arc::make_mut(&mut self.pipelines).register(pipeline);
+        self
+    }
+
     pub fn cancel_token(&self) -> Option<CancellationToken> {
         self.cancel_listener.as_ref().map(|c| c.token())
     }
 
     /// Executes a pipeline with mapper, cancellation, hooks, and resolver support.
     pub async fn execute(&self, pipeline: Pipeline, input: Value) -> Result<ExecutionResult> {
+        self.execute_run(generate_id("run"), pipeline, input).await
+    }
+
+    /// Executes a pipeline under a caller-chosen run id, which `resume` accepts later.
+    pub async fn execute_run(
+        &self,
+        run_id: impl Into<String>,
+        pipeline: Pipeline,
+        input: Value,
+    ) -> Result<ExecutionResult> {
+        self.execute_run_with(run_id.into(), pipeline, input, None)
+            .await
+    }
+
+    async fn execute_run_with(
+        &self,
+        run_id: String,
+        pipeline: Pipeline,
+        input: Value,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult> {
         debug!("Exectuting pipeline {}", pipeline.key);
+        let executor = self
+            .pipeline_executor(&pipeline, pipelines)
+            .with_run_id(run_id.clone());
+
+        debug!("Executing pipeline");
+        let outcome = executor.execute(input).await;
+        self.finish_run(&run_id, &pipeline.key, outcome, &executor.masker())
+            .await
+    }
+
+    /// Continues a run from its last checkpoint in the configured state store.
+    ///
+    /// Steps that already succeeded keep their results and are not executed again.
+    /// Resuming a completed run returns its stored result.
+    pub async fn resume(&self, run_id: &str) -> Result<ExecutionResult> {
+        let store = self.store()?;
+        let checkpoint = self.load_checkpoint(run_id).await?;
+
+        if !checkpoint.is_resumable() {
+            debug!("Run {} already completed", run_id);
+            let stored = store
+                .load_result(run_id)
+                .await
+                .map_err(EngineError::State)?;
+            return Ok(stored
+                .unwrap_or_else(|| assemble_result(checkpoint.context, &checkpoint.pipeline.key)));
+        }
+
+        self.continue_run(checkpoint, None).await
+    }
+
+    /// Delivers signal `name` with `payload` to a run suspended at a
+    /// `wait_for` step and continues the run from there.
+    pub async fn signal(
+        &self,
+        run_id: &str,
+        name: &str,
+        payload: Value,
+    ) -> Result<ExecutionResult> {
+        let checkpoint = self.signaled(run_id, name, payload).await?;
+        self.continue_run(checkpoint, None).await
+    }
+
+    /// Continues the suspended runs whose timer fired: finished sleeps, and
+    /// signal waits that timed out and take their `otherwise` route. Meant to
+    /// be called periodically; returns the results of the runs it continued,
+    /// with a failed result for runs that stopped on an engine error.
+    /// Runs that cannot be loaded are logged and skipped.
+    pub async fn wake_due(&self) -> Result<Vec<ExecutionResult>> {
+        self.wake_due_with(None).await
+    }
+
+    async fn wake_due_with(
+        &self,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<Vec<ExecutionResult>> {
+        let timers = self
+            .store()?
+            .due_timers(Utc::now())
+            .await
+            .map_err(EngineError::State)?;
+
+        let mut woken = Vec::new();
+        for timer in timers {
+            let checkpoint = match self.load_checkpoint(&timer.run_id).await {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => {
+                    warn!("Could not wake run {}: {}", timer.run_id, e);
+                    continue;
+                }
+            };
+            // Runs that went on without their timer, e.g. on a signal, leave it stale
+            if checkpoint.state != PipelineState::Suspended {
+                self.delete_timer(&timer.run_id).await;
+                continue;
+            }
+
+            debug!("Waking run {}", timer.run_id);
+            let pipeline_key = checkpoint.pipeline.key.clone();
+            match self.continue_run(checkpoint, pipelines.clone()).await {
+                Ok(result) => woken.push(result),
+                Err(e) => {
+                    warn!("Run {} failed after waking: {}", timer.run_id, e);
+                    woken.push(self.failed_result(&timer.run_id, &pipeline_key, &e).await);
+                }
+            }
+        }
+        Ok(woken)
+    }
+
+    /// Calls `wake_due` every `poll` until the engine is canceled.
+    pub async fn run_scheduler(&self, poll: Duration) {
+        let cancel_token = self.cancel_token().unwrap_or_default();
+        let mut ticks = tokio::time::interval(poll);
+        loop {
+            tokio::select! {
+                _ = cancel_token.cancelled() => break,
+                _ = ticks.tick() => {}
+            }
+            if let Err(e) = self.wake_due().await {
+                warn!("Could not wake due runs: {}", e);
+            }
+        }
+    }
+
+    fn store(&self) -> Result<&Arc<dyn StateStore>> {
+        self.state_store
+            .as_ref()
+            .ok_or_else(|| EngineError::State("No state store configured".into()))
+    }
+
+    async fn load_checkpoint(&self, run_id: &str) -> Result<RunCheckpoint> {
+        self.store()?
+            .load_checkpoint(run_id)
+            .await
+            .map_err(EngineError::State)?
+            .ok_or_else(|| EngineError::State(format!("No checkpoint for run '{}'", run_id)))
+    }
+
+    /// Checkpoint of `run_id` with `payload` delivered to its `wait_for` step.
+    async fn signaled(&self, run_id: &str, name: &str, payload: Value) -> Result<RunCheckpoint> {
+        let mut checkpoint = self.load_checkpoint(run_id).await?;
+        let waiting = checkpoint
+            .context
+            .waiting
+            .as_mut()
+            .filter(|w| checkpoint.state == PipelineState::Suspended && w.signal == name)
+            .ok_or_else(|| {
+                EngineError::State(format!(
+                    "Run '{}' is not waiting for signal '{}'",
+                    run_id, name
+                ))
+            })?;
+        waiting.payload = Some(payload);
+        Ok(checkpoint)
+    }
+
+    /// Runs a checkpointed run on from its next step, with sub-pipeline
+    /// steps looking in `pipelines` first like they did when it started.
+    async fn continue_run(
+        &self,
+        checkpoint: RunCheckpoint,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult> {
+        self.delete_timer(&checkpoint.run_id).await;
+        let executor = self
+            .pipeline_executor(&checkpoint.pipeline, pipelines)
+            .with_run_id(&checkpoint.run_id);
+        let outcome = executor
+            .resume(checkpoint.context, checkpoint.next_step)
+            .await;
+        self.finish_run(
+            &checkpoint.run_id,
+            &checkpoint.pipeline.key,
+            outcome,
+            &executor.masker(),
+        )
+        .await
+    }
+
+    async fn delete_timer(&self, run_id: &str) {
+        if let Some(store) = &self.state_store {
+            if let Err(e) = store.delete_timer(run_id).await {
+                warn!("Could not delete the timer of run {}: {}", run_id, e);
+            }
+        }
+    }
+
+    /// Builds the executor for a top-level run of `definition`, prepared by
+    /// `pipelines` when given. Sub-pipeline steps look in `pipelines` first,
+    /// then in the pipelines registered on the engine.
+    fn pipeline_executor(
+        &self,
+        definition: &Pipeline,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> PipelineExecutor<'_, M, HR, AR> {
+        let pipeline = match &pipelines {
+            Some(pipelines) => pipelines.prepare(definition),
+            None => definition.clone(),
+        };
+
         // --- Setup cancellation token ---
         let cancel_token = self
             .cancel_listener
             .as_ref()
             .map(|c| c.token())
-            .unwrap_or_else(CancellationToken::new);
+            .unwrap_or_default();
 
         debug!("Cancel token {:?}", cancel_token);
 
@@ -177,73 +440,133 @@ where
         let all_pipeline_hooks = [self.global_pipeline_hooks.clone(), pipeline_hooks].concat();
         debug!("Total hooks: # {:?}", all_pipeline_hooks.len());
 
+        let timeout = [
+            self.pipeline_timeout,
+            pipeline.timeout_ms.map(Duration::from_millis),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let deadline = timeout.map(|t| tokio::time::Instant::now() + t);
+
         debug!("Create PipelineExecutor");
-        let executor = PipelineExecutor::new(
-            pipeline.clone(),
+        PipelineExecutor::new(
+            pipeline,
             self.mapper.clone(),
             self.global_action_hooks.clone(),
             all_pipeline_hooks,
             &*self.action_hook_resolver,
             &*self.action_resolver,
             cancel_token,
-        );
-        debug!("Executing pipeline");
-        let mut ex_context = executor.execute(input).await?;
-        debug!("Marking context as finsihed");
-        ex_context.finish(); // Mark as finished if not already done
-
-        debug!("Building metrics");
-        let finished_at = ex_context.finished_at.unwrap_or_else(Utc::now);
-        let duration_ms = (finished_at - ex_context.started_at)
-            .num_milliseconds()
-            .max(0) as u64;
-
-        let steps_total = ex_context.steps.len();
-        let steps_succeeded = ex_context
-            .steps
-            .iter()
-            .filter(|s| s.status == ExecutionStatus::Success)
-            .count();
-
-        let steps_failed = ex_context
-            .steps
-            .iter()
-            .filter(|s| s.status == ExecutionStatus::Failed)
-            .count();
-
-        debug!("Composing metrics");
-
-        let metrics = ExecutionMetrics {
-            started_at: ex_context.started_at,
-            finished_at,
-            duration_ms,
-            steps_total,
-            steps_succeeded,
-            steps_failed,
+        )
+        .with_definition(definition.clone())
+        .with_deadline(deadline)
+        .with_state_store(self.state_store.clone())
+        .with_durable_timers(
+            self.state_store
+                .as_ref()
+                .map(|_| self.durable_timer_threshold),
+        )
+        .with_sub_pipelines(Some(SubPipelines {
+            resolvers: pipelines
+                .into_iter()
+                .chain([self.pipelines.clone() as Arc<dyn PipelineResolver>])
+                .collect(),
+            global_hooks: self.global_pipeline_hooks.clone(),
+            hook_resolver: &*self.pipeline_hook_resolver,
+        }))
+    }
+
+    /// Turns the executor outcome into the run result and saves it to the
+    /// state store with the run's secrets masked.
+    async fn finish_run(
+        &self,
+        run_id: &str,
+        pipeline_key: &str,
+        outcome: Result<ExecutionContext>,
+        masker: &SensitiveMasker,
+    ) -> Result<ExecutionResult> {
+        match outcome {
+            Ok(ex_context) => {
+                let result = assemble_result(ex_context, pipeline_key);
+                self.save_result(&result, masker).await;
+                debug!("Pipeline execution finsihed");
+                Ok(result)
+            }
+            Err(err) => {
+                if self.state_store.is_some() {
+                    let result = self.failed_result(run_id, pipeline_key, &err).await;
+                    self.save_result(&result, masker).await;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn save_result(&self, result: &ExecutionResult, masker: &SensitiveMasker) {
+        let Some(store) = &self.state_store else {
+            return;
+        };
+        let stored = match masker.mask(result) {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("Could not mask the result of run {}: {}", result.run_id, e);
+                return;
+            }
         };
+        if let Err(e) = store.save_result(&stored).await {
+            warn!("Could not save result of run {}: {}", result.run_id, e);
+        }
+    }
 
-        debug!("Assemble final result");
-        let result = ExecutionResult {
-            run_id: ex_context.run_id.clone(),
-            pipeline_key: Some(pipeline.key.clone()),
-            environment: Some("local".to_string()),
-            status: if ex_context.error.is_none() {
-                ExecutionStatus::Success
-            } else {
-                ExecutionStatus::Failed
-            },
-            result: match ex_context.steps.last() {
-                Some(val) => val.output.clone(),
-                None => None,
-            },
-            error: ex_context.error,
-            steps: ex_context.steps,
-            metrics,
+    /// Result for a run that ended with an engine error, including the steps
+    /// of its last checkpoint when a state store is configured.
+    async fn failed_result(
+        &self,
+        run_id: &str,
+        pipeline_key: &str,
+        err: &EngineError,
+    ) -> ExecutionResult {
+        let checkpoint = match &self.state_store {
+            Some(store) => store.load_checkpoint(run_id).await.ok().flatten(),
+            None => None,
         };
 
-        debug!("Pipeline execution finsihed");
+        let mut result = match checkpoint {
+            Some(checkpoint) => assemble_result(checkpoint.context, pipeline_key),
+            None => {
+                // Create a lightweight failed ExecutionResult
+                let now = Utc::now();
+                ExecutionResult {
+                    run_id: run_id.to_string(),
+                    parent_run_id: None,
+                    pipeline_key: Some(pipeline_key.to_owned()),
+                    environment: Some("local".into()),
+                    status: ExecutionStatus::Failed,
+                    error: None,
+                    steps: vec![],
+                    compensations: vec![],
+                    result: None,
+                    metrics: ExecutionMetrics {
+                        started_at: now,
+                        finished_at: now,
+                        duration_ms: 0,
+                        steps_total: 0,
+                        steps_succeeded: 0,
+                        steps_failed: 0,
+                    },
+                }
+            }
+        };
 
-        Ok(result)
+        result.status = match err {
+            EngineError::Canceled => ExecutionStatus::Canceled,
+            _ if result.status == ExecutionStatus::Compensated => ExecutionStatus::Compensated,
+            EngineError::Timeout(_) => ExecutionStatus::Timeout,
+            _ => ExecutionStatus::Failed,
+        };
+        result.error = Some(err.to_string());
+        result
     }
 
     /// ------------------------------------------------------
@@ -255,7 +578,7 @@ where
             .cancel_listener
             .as_ref()
             .map(|c| c.token())
-            .unwrap_or_else(CancellationToken::new);
+            .unwrap_or_default();
 
         let mut ctx = ActionContext::new("", input);
 
@@ -296,7 +619,6 @@ where
 /// ------------------------------------------------------
 /// Builder extensions for Default Resolvers only
 /// ------------------------------------------------------
-
 impl<M> Engine<M, DefaultActionHookResolver, DefaultPipelineHookResolver, DefaultActionResolver>
 where
     M: Mapper + Send + Sync + 'static,
@@ -311,7 +633,7 @@ where
         self
     }
 
-    pub fn with_action_hook_for<H: ActionHook>(self, action_id: &str, hook: H) -> Self
+    pub fn with_action_hook_for<H>(self, action_id: &str, hook: H) -> Self
     where
         H: ActionHook + 'static,
     {
@@ -346,6 +668,7 @@ impl
         DefaultActionResolver,
     >
 {
+    #[allow(clippy::should_implement_trait)]
     pub fn default() -> Self {
         Self {
             mapper: Arc::new(DefaultMapper),
@@ -355,6 +678,10 @@ impl
             pipeline_hook_resolver: Arc::new(DefaultPipelineHookResolver::new()),
             action_resolver: Box::new(DefaultActionResolver::new()),
             cancel_listener: None,
+            pipeline_timeout: None,
+            state_store: None,
+            durable_timer_threshold: DEFAULT_DURABLE_TIMER_THRESHOLD,
+            pipelines: Arc::new(DefaultPipelineResolver::new()),
         }
     }
 }
@@ -362,10 +689,49 @@ impl
 /// ------------------------------------------------------
 /// Public Engine API for Flow integration
 /// ------------------------------------------------------
-
 #[async_trait]
 pub trait EngineApi: Send + Sync {
-    async fn execute_pipeline(&self, pipeline: Pipeline, input: Value) -> Result<ExecutionResult>;
+    async fn execute_pipeline(&self, pipeline: Pipeline, input: Value) -> Result<ExecutionResult> {
+        self.execute_pipeline_as(None, pipeline, input, None).await
+    }
+
+    /// Runs `pipeline` under `run_id`, or a generated id when `None`, so
+    /// callers can hand out the id before the run finishes. Sub-pipeline
+    /// steps look in `pipelines` before the pipelines registered on the engine.
+    async fn execute_pipeline_as(
+        &self,
+        run_id: Option<String>,
+        pipeline: Pipeline,
+        input: Value,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult>;
+
+    /// Continues `run_id` from its checkpoint when it was cut off mid-run,
+    /// or returns its result when it had already stopped. `None` when there
+    /// is no checkpoint for the run. Sub-pipeline steps look in `pipelines`
+    /// first, as with `execute_pipeline_as`.
+    async fn resume_pipeline(
+        &self,
+        run_id: &str,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<Option<ExecutionResult>>;
+
+    /// Delivers signal `name` with `payload` to run `run_id`, suspended at a
+    /// `wait_for` step, and continues it. Fails when the run is not waiting
+    /// for that signal.
+    async fn signal_pipeline(
+        &self,
+        run_id: &str,
+        name: &str,
+        payload: Value,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult>;
+
+    /// Continues the suspended runs whose timer fired and returns their results.
+    async fn wake_due_pipelines(
+        &self,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<Vec<ExecutionResult>>;
 }
 
 #[async_trait]
@@ -376,36 +742,155 @@ where
     PHR: PipelineHookResolver + Send + Sync + 'static,
     AR: ActionResolver + Send + Sync + 'static,
 {
-    async fn execute_pipeline(&self, pipeline: Pipeline, input: Value) -> Result<ExecutionResult> {
+    async fn execute_pipeline_as(
+        &self,
+        run_id: Option<String>,
+        pipeline: Pipeline,
+        input: Value,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult> {
+        let run_id = run_id.unwrap_or_else(|| generate_id("run"));
+        self.run_for_flow(run_id, pipeline, input, pipelines).await
+    }
+
+    async fn resume_pipeline(
+        &self,
+        run_id: &str,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<Option<ExecutionResult>> {
+        let Some(store) = &self.state_store else {
+            return Ok(None);
+        };
+        let checkpoint = store
+            .load_checkpoint(run_id)
+            .await
+            .map_err(EngineError::State)?;
+        let Some(checkpoint) = checkpoint else {
+            return Ok(None);
+        };
+
+        // Failed, canceled and suspended runs stopped on their own and stay that way
+        if checkpoint.state != PipelineState::Running {
+            let stored = store
+                .load_result(run_id)
+                .await
+                .map_err(EngineError::State)?;
+            return Ok(Some(stored.unwrap_or_else(|| {
+                assemble_result(checkpoint.context, &checkpoint.pipeline.key)
+            })));
+        }
+
+        let pipeline_key = checkpoint.pipeline.key.clone();
+        match self.continue_run(checkpoint, pipelines).await {
+            Ok(result) => Ok(Some(result)),
+            Err(err) => Ok(Some(self.failed_result(run_id, &pipeline_key, &err).await)),
+        }
+    }
+
+    async fn signal_pipeline(
+        &self,
+        run_id: &str,
+        name: &str,
+        payload: Value,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult> {
+        let checkpoint = self.signaled(run_id, name, payload).await?;
+        let pipeline_key = checkpoint.pipeline.key.clone();
+        match self.continue_run(checkpoint, pipelines).await {
+            Ok(result) => Ok(result),
+            Err(err) => Ok(self.failed_result(run_id, &pipeline_key, &err).await),
+        }
+    }
+
+    async fn wake_due_pipelines(
+        &self,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<Vec<ExecutionResult>> {
+        self.wake_due_with(pipelines).await
+    }
+}
+
+impl<M, HR, PHR, AR> Engine<M, HR, PHR, AR>
+where
+    M: Mapper + Send + Sync + 'static,
+    HR: ActionHookResolver + Send + Sync + 'static,
+    PHR: PipelineHookResolver + Send + Sync + 'static,
+    AR: ActionResolver + Send + Sync + 'static,
+{
+    /// Runs `pipeline` for `EngineApi`, reporting engine errors as a failed result.
+    async fn run_for_flow(
+        &self,
+        run_id: String,
+        pipeline: Pipeline,
+        input: Value,
+        pipelines: Option<Arc<dyn PipelineResolver>>,
+    ) -> Result<ExecutionResult> {
         let _ = tracing_subscriber::fmt::try_init();
 
         // The engine itself already tracks start, finish, and metrics internally.
         // Just call it and propagate the result.
 
-        let pipeline_key = &pipeline.key.clone();
-        match self.execute(pipeline, input).await {
+        let pipeline_key = pipeline.key.clone();
+        match self
+            .execute_run_with(run_id.clone(), pipeline, input, pipelines)
+            .await
+        {
             Ok(result) => Ok(result),
-            Err(err) => {
-                // Create a lightweight failed ExecutionResult
-                let now = chrono::Utc::now();
-                Ok(ExecutionResult {
-                    run_id: ryvus_core::utils::id::generate_id("run"),
-                    pipeline_key: Some(pipeline_key.to_owned()),
-                    environment: Some("local".into()),
-                    status: ExecutionStatus::Failed,
-                    error: Some(err.to_string()),
-                    steps: vec![],
-                    result: None,
-                    metrics: ryvus_core::action::result::ExecutionMetrics {
-                        started_at: now,
-                        finished_at: now,
-                        duration_ms: 0,
-                        steps_total: 0,
-                        steps_succeeded: 0,
-                        steps_failed: 0,
-                    },
-                })
-            }
+            Err(err) => Ok(self.failed_result(&run_id, &pipeline_key, &err).await),
         }
     }
 }
+
+/// Builds the final `ExecutionResult` with metrics from a finished context.
+fn assemble_result(mut ex_context: ExecutionContext, pipeline_key: &str) -> ExecutionResult {
+    debug!("Marking context as finsihed");
+    ex_context.finish(); // Mark as finished if not already done
+
+    debug!("Building metrics");
+    let finished_at = ex_context.finished_at.unwrap_or_else(Utc::now);
+    let duration_ms = (finished_at - ex_context.started_at)
+        .num_milliseconds()
+        .max(0) as u64;
+
+    let steps_total = ex_context.steps.len();
+    let steps_succeeded = ex_context
+        .steps
+        .iter()
+        .filter(|s| s.status == ExecutionStatus::Success)
+        .count();
+
+    let steps_failed = ex_context
+        .steps
+        .iter()
+        .filter(|s| s.status == ExecutionStatus::Failed)
+        .count();
+
+    debug!("Composing metrics");
+
+    let metrics = ExecutionMetrics {
+        started_at: ex_context.started_at,
+        finished_at,
+        duration_ms,
+        steps_total,
+        steps_succeeded,
+        steps_failed,
+    };
+
+    debug!("Assemble final result");
+    let status = ex_context.status();
+    ExecutionResult {
+        run_id: ex_context.run_id.clone(),
+        parent_run_id: ex_context.parent_run_id.clone(),
+        pipeline_key: Some(pipeline_key.to_string()),
+        environment: Some("local".to_string()),
+        status,
+        result: match ex_context.steps.last() {
+            Some(val) => val.output.clone(),
+            None => None,
+        },
+        error: ex_context.error,
+        steps: ex_context.steps,
+        compensations: ex_context.compensations,
+        metrics,
+    }
+}
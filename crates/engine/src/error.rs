@@ -6,8 +6,14 @@ pub enum EngineError {
     Action(String),
     #[error("Pipeline canceled")]
     Canceled,
+    #[error("Timed out: {0}")]
+    Timeout(String),
     #[error("There was an issue configuring the Action {0}")]
     Config(String),
+    #[error("Routing budget exceeded: {0}")]
+    Budget(String),
+    #[error("State store error: {0}")]
+    State(String),
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -1,4 +1,4 @@
-use ryvus_core::prelude::Action;
+use ryvus_core::prelude::{Action, RetryPolicy};
 
 use crate::internal::retryable_action::RetryableAction;
 
@@ -7,6 +7,11 @@ pub trait RetryExt: Action + Sized {
     fn retryable(self, max_retries: usize) -> RetryableAction<Self> {
         RetryableAction::new(self, max_retries)
     }
+
+    /// Retries with the backoff and error classification of `policy`.
+    fn retry_with(self, policy: RetryPolicy) -> RetryableAction<Self> {
+        RetryableAction::with_policy(self, policy)
+    }
 }
 
 impl<A: Action> RetryExt for A {}
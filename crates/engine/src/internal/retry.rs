@@ -0,0 +1,122 @@
+use chrono::Utc;
+use ryvus_core::error::{Error, ErrorKind};
+use ryvus_core::prelude::{
+    Action, ActionAttempt, ActionContext, ActionResult, ExecutionStatus, RetryPolicy,
+};
+use std::time::Duration;
+use tokio::select;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// How a retry loop ended.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Outcome {
+    /// The last attempt's result; a failed one when the policy gave up
+    Finished(Result<ActionResult, Error>),
+    Canceled,
+    /// The pipeline deadline passed during an attempt or a backoff
+    DeadlineExpired,
+}
+
+/// The one retry loop behind both step execution and `RetryableAction`.
+///
+/// Failed results count as failures just like errors, backoff sleeps end
+/// early on cancellation or at the deadline, and every attempt is recorded.
+pub(crate) struct Retry<'a> {
+    pub key: &'a str,
+    pub policy: Option<&'a RetryPolicy>,
+    pub timeout: Option<Duration>,
+    pub deadline: Option<Instant>,
+    pub cancel_token: Option<&'a CancellationToken>,
+}
+
+impl Retry<'_> {
+    pub async fn run(
+        &self,
+        action: &dyn Action,
+        ctx: &mut ActionContext,
+    ) -> (Outcome, Vec<ActionAttempt>) {
+        let started_at = Utc::now();
+        let mut attempts = Vec::new();
+
+        loop {
+            let attempt_started = Utc::now();
+            let step_deadline = self.timeout.map(|t| Instant::now() + t);
+            let outcome = select! {
+                _ = cancelled(self.cancel_token) => return (Outcome::Canceled, attempts),
+                _ = sleep_until(self.deadline) => return (Outcome::DeadlineExpired, attempts),
+                _ = sleep_until(step_deadline) => Err(Error::Timeout(format!(
+                    "Step '{}' exceeded {}ms",
+                    self.key,
+                    self.timeout.unwrap_or_default().as_millis()
+                ))),
+                res = action.execute(ctx) => res,
+            };
+
+            // Failed results without an error are classified as action errors
+            let failure = match &outcome {
+                Err(e) => Some((e.kind(), e.to_string())),
+                Ok(res) if res.status == ExecutionStatus::Failed => {
+                    Some((ErrorKind::Action, res.message.clone().unwrap_or_default()))
+                }
+                Ok(res) if res.status == ExecutionStatus::Timeout => {
+                    Some((ErrorKind::Timeout, res.message.clone().unwrap_or_default()))
+                }
+                Ok(_) => None,
+            };
+
+            let attempt = attempts.len() as u32 + 1;
+            let mut record = ActionAttempt::finished(
+                attempt,
+                attempt_started,
+                failure.as_ref().map(|(_, message)| message.clone()),
+            );
+            if matches!(failure, Some((ErrorKind::Timeout, _))) {
+                record.status = ExecutionStatus::Timeout;
+            }
+            attempts.push(record);
+
+            let delay = match (&failure, self.policy) {
+                (Some((kind, _)), Some(policy)) => {
+                    let elapsed = (Utc::now() - started_at).to_std().unwrap_or_default();
+                    policy.next_delay(attempt, *kind, elapsed)
+                }
+                _ => None,
+            };
+            let Some(delay) = delay else {
+                return (Outcome::Finished(outcome), attempts);
+            };
+
+            warn!(
+                "Step '{}' failed (attempt {}/{}), retrying in {:?}: {}",
+                self.key,
+                attempt,
+                self.policy.map_or(1, |p| p.max_attempts),
+                delay,
+                failure.map(|(_, message)| message).unwrap_or_default()
+            );
+
+            select! {
+                _ = cancelled(self.cancel_token) => return (Outcome::Canceled, attempts),
+                _ = sleep_until(self.deadline) => return (Outcome::DeadlineExpired, attempts),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+}
+
+async fn cancelled(token: Option<&CancellationToken>) {
+    match token {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Resolves at `deadline`, or never when there is none.
+pub(crate) async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
@@ -1,20 +1,31 @@
 use async_trait::async_trait;
 use ryvus_core::error::Error;
-use ryvus_core::prelude::{Action, ActionContext, ActionResult};
-use tracing::debug;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, ExecutionStatus, RetryPolicy};
+use std::time::Duration;
 
-use crate::error::EngineError;
+use crate::internal::retry::{Outcome, Retry};
 
-/// Retries an Action up to `max_retries` times when it fails.
+/// Retries an Action according to a [`RetryPolicy`] when it fails.
+///
+/// Runs the same retry loop as pipeline steps. An error that exhausts the
+/// policy comes back as a failed result so the attempts are not lost.
 #[derive(Clone)]
 pub struct RetryableAction<A: Action> {
     inner: A,
-    max_retries: usize,
+    policy: RetryPolicy,
 }
 
 impl<A: Action> RetryableAction<A> {
+    /// Retries up to `max_retries` times without delay.
     pub fn new(inner: A, max_retries: usize) -> Self {
-        Self { inner, max_retries }
+        Self::with_policy(
+            inner,
+            RetryPolicy::fixed(max_retries as u32 + 1, Duration::ZERO),
+        )
+    }
+
+    pub fn with_policy(inner: A, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
     }
 }
 
@@ -24,25 +35,34 @@ where
     A: Action + Send + Sync,
 {
     async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
-        let mut attempts = 0;
-        debug!("RetryableAction exec {attempts}");
-        loop {
-            match self.inner.execute(ctx).await {
-                Ok(res) => return Ok(res),
-                Err(e) if attempts < self.max_retries => {
-                    attempts += 1;
-                    println!(
-                        "Action {} failed (attempt {}/{}): {}",
-                        self.inner.key(),
-                        attempts,
-                        self.max_retries,
-                        e
-                    );
-                    continue;
+        let retry = Retry {
+            key: self.inner.key(),
+            policy: Some(&self.policy),
+            timeout: None,
+            deadline: None,
+            cancel_token: None,
+        };
+        // Cancellation drops this future, backoff sleep included
+        let (outcome, attempts) = retry.run(&self.inner, ctx).await;
+
+        let mut res = match outcome {
+            Outcome::Finished(Ok(res)) => res,
+            Outcome::Finished(Err(e)) if attempts.len() <= 1 => return Err(e),
+            Outcome::Finished(Err(e)) => {
+                let mut res = ActionResult::failed(e.to_string());
+                if matches!(e, Error::Timeout(_)) {
+                    res.status = ExecutionStatus::Timeout;
                 }
-                Err(e) => return Err(e),
+                res
+            }
+            Outcome::Canceled | Outcome::DeadlineExpired => {
+                unreachable!("retrying actions run without a token or deadline")
             }
+        };
+        if attempts.len() > 1 {
+            res.attempts = attempts;
         }
+        Ok(res)
     }
 
     fn key(&self) -> &str {
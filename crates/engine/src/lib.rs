@@ -1,4 +1,5 @@
 pub mod action_resolver;
+pub mod actions;
 pub mod cancellation;
 pub mod config_resolver;
 pub mod engine;
@@ -6,10 +7,12 @@ pub mod error;
 pub mod hook_resolver;
 pub mod mapper;
 pub mod pipeline;
+pub mod pipeline_resolver;
 pub use engine::Engine;
 pub mod utils;
 mod internal {
     pub mod hooked_action;
+    pub(crate) mod retry;
     pub(crate) mod retryable_action;
 }
 
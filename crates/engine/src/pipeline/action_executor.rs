@@ -1,13 +1,17 @@
 use crate::error::{EngineError, Result};
 use crate::hook_resolver::ActionHookResolver;
+use crate::internal::retry::{Outcome, Retry};
 use crate::mapper::mapper::Mapper;
 use chrono::Utc;
 use ryvus_core::error::Error;
 use ryvus_core::pipeline::hook::ActionHook;
-use ryvus_core::prelude::{Action, ActionContext, ActionResult, ExecutionContext, ExecutionStatus};
+use ryvus_core::prelude::{
+    Action, ActionContext, ActionResult, ExecutionContext, ExecutionStatus, RetryPolicy,
+};
 use serde_json::Value;
 use std::sync::Arc;
-use tokio::select;
+use std::time::Duration;
+use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 
 /// Executes an Action, applying hooks, using a Mapper for input, and respecting cancellation.
@@ -22,6 +26,11 @@ pub struct ActionExecutor<'a, M: Mapper, HR: ActionHookResolver> {
     pub mapper: Arc<M>,
     pub cancel_token: CancellationToken,
     pub params: Value,
+    pub retry: Option<RetryPolicy>,
+    pub timeout: Option<Duration>,
+    pub deadline: Option<Instant>,
+    pub context: Option<Value>,
+    pub keep_input: bool,
 }
 
 impl<'a, M: Mapper, HR: ActionHookResolver> ActionExecutor<'a, M, HR> {
@@ -42,14 +51,49 @@ impl<'a, M: Mapper, HR: ActionHookResolver> ActionExecutor<'a, M, HR> {
             mapper,
             cancel_token,
             params,
+            retry: None,
+            timeout: None,
+            deadline: None,
+            context: None,
+            keep_input: false,
         }
     }
 
+    /// Limits every attempt to `timeout`; a timed-out attempt fails with `Error::Timeout`.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Pipeline-wide deadline; reaching it aborts the step without further retries.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Run view handed to the action as `ActionContext::context`.
+    pub fn with_context(mut self, context: Value) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Keeps the resolved params on a successful result as its `input`.
+    pub fn with_input_kept(mut self, keep: bool) -> Self {
+        self.keep_input = keep;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Option<RetryPolicy>) -> Self {
+        self.retry = retry;
+        self
+    }
+
     /// Executes the Action within the given ExecutionContext.
     ///
     /// - Resolves hooks (global + dynamic)
     /// - Uses the mapper to populate ActionContext input
-    /// - Executes the action or cancels on request
+    /// - Executes the action, cancelling or timing out on request
+    /// - Retries failed attempts according to the step's retry policy
     /// - Runs before/after/error hooks appropriately
     pub async fn execute(&self, exec_ctx: &mut ExecutionContext) -> Result<ActionResult> {
         // Resolve hooks: global + action-specific
@@ -58,17 +102,39 @@ impl<'a, M: Mapper, HR: ActionHookResolver> ActionExecutor<'a, M, HR> {
         // Prepare input
 
         let mut ctx = ActionContext::new(&self.key, self.params.clone());
+        ctx.context = self.context.clone();
 
         for hook in &hooks {
             hook.before(&mut ctx).await;
         }
 
         let started_at = Utc::now();
-        let result = select! {
-            _ = self.cancel_token.cancelled() => Err(EngineError::Canceled),
-            res = self.action.execute(&mut ctx) => res.map_err(|e| EngineError::Action(e.to_string())),
+        let retry = Retry {
+            key: &self.key,
+            policy: self.retry.as_ref(),
+            timeout: self.timeout,
+            deadline: self.deadline,
+            cancel_token: Some(&self.cancel_token),
+        };
+        let (outcome, mut attempts) = retry.run(self.action.as_ref(), &mut ctx).await;
+        let deadline_expired = matches!(outcome, Outcome::DeadlineExpired);
+        let result = match outcome {
+            Outcome::Finished(res) => res.map_err(|e| match e {
+                Error::Timeout(message) => EngineError::Timeout(message),
+                e => EngineError::Action(e.to_string()),
+            }),
+            Outcome::Canceled => Err(EngineError::Canceled),
+            Outcome::DeadlineExpired => {
+                Err(EngineError::Timeout("Pipeline deadline exceeded".into()))
+            }
         };
 
+        // Attempts are only reported for steps running under a retry policy;
+        // a retrying action reports its own
+        if self.retry.is_none() {
+            attempts.clear();
+        }
+
         let finished_at = Utc::now();
         let duration_ms = (finished_at - started_at).num_milliseconds().max(0) as u64;
 
@@ -85,6 +151,12 @@ impl<'a, M: Mapper, HR: ActionHookResolver> ActionExecutor<'a, M, HR> {
 
                 ctx.set_result(value_json.clone());
                 value.key = self.key.to_string();
+                if self.retry.is_some() {
+                    value.attempts = attempts;
+                }
+                if self.keep_input {
+                    value.input = Some(self.params.clone());
+                }
                 for hook in &hooks {
                     hook.after(&mut ctx).await;
                 }
@@ -92,24 +164,33 @@ impl<'a, M: Mapper, HR: ActionHookResolver> ActionExecutor<'a, M, HR> {
                 value
             }
             Err(e) => {
-                for hook in &hooks {
-                    hook.error(&mut ctx, &Error::Action(e.to_string())).await;
+                let hook_error = match &e {
+                    EngineError::Timeout(message) => Error::Timeout(message.clone()),
+                    e => Error::Action(e.to_string()),
+                };
+                // Reaching the pipeline deadline ends the run, not just this step
+                if !deadline_expired {
+                    for hook in &hooks {
+                        hook.error(&mut ctx, &hook_error).await;
+                    }
                 }
 
                 ActionResult {
                     id: action_id,
                     action: Some(action_key.clone()),
-                    status: if matches!(e, EngineError::Canceled) {
-                        ExecutionStatus::Canceled
-                    } else {
-                        ExecutionStatus::Failed
+                    status: match e {
+                        EngineError::Canceled => ExecutionStatus::Canceled,
+                        EngineError::Timeout(_) => ExecutionStatus::Timeout,
+                        _ => ExecutionStatus::Failed,
                     },
+                    input: None,
                     output: None,
                     message: Some(e.to_string()),
                     started_at: Some(started_at),
                     finished_at: Some(Utc::now()),
                     duration_ms: Some(duration_ms),
                     key: action_key.clone(),
+                    attempts,
                 }
             }
         };
@@ -1,4 +1,3 @@
 pub mod action_executor;
 pub mod pipeline_executor;
 pub mod resolved;
-
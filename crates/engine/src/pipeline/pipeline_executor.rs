@@ -2,38 +2,83 @@ use crate::{
     action_resolver::ActionResolver,
     config_resolver::{ConfigResolver, JsonPathConfigResolver},
     error::{EngineError, Result},
-    hook_resolver::ActionHookResolver,
+    hook_resolver::{ActionHookResolver, PipelineHookResolver},
+    internal::retry::sleep_until,
     mapper::mapper::Mapper,
     pipeline::action_executor::ActionExecutor,
+    pipeline_resolver::PipelineResolver,
     utils::{
         json::deep_merge,
-        jsonpath_resolver::{build_jsonpath_context, resolve_jsonpaths},
+        jsonpath_resolver::{build_jsonpath_context, query_first, resolve_jsonpaths},
     },
 };
 
+use chrono::{DateTime, Utc};
+use futures::stream::{FuturesUnordered, StreamExt};
 use ryvus_core::{
+    action::result::ExecutionResult,
+    context::execution_context::SignalWait,
     environment::Environment,
-    pipeline::hook::ActionHook,
+    pipeline::{
+        hook::ActionHook,
+        pipeline::{
+            ExecutionMode, ForEach, ParallelBranches, SubPipeline, WaitFor, WhileLoop,
+            DEFAULT_MAX_STEPS, DEFAULT_MAX_VISITS_PER_STEP,
+        },
+        state::PipelineState,
+    },
     prelude::{
         pipeline::Pipeline, ActionResult, ExecutionContext, ExecutionStatus, PipelineHook,
         PipelineStep,
     },
+    state::{checkpoint::RunCheckpoint, state_store::StateStore, timer::RunTimer},
+    utils::{id::generate_id, masker::SensitiveMasker},
 };
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Deepest chain of sub-pipeline steps a run may start.
+pub const MAX_PIPELINE_DEPTH: usize = 16;
+
+/// Where sub-pipeline steps find their pipelines, and the pipeline hooks child runs get.
+#[derive(Clone)]
+pub struct SubPipelines<'a> {
+    /// Tried in order; the first match wins
+    pub resolvers: Vec<Arc<dyn PipelineResolver>>,
+    pub global_hooks: Vec<Arc<dyn PipelineHook>>,
+    pub hook_resolver: &'a dyn PipelineHookResolver,
+}
 
 /// Executes a Pipeline of Actions with flow control.
-/// Supports next_when, else, on_error, and cancel handling.
+/// Supports next_when, else, on_error, parallel branches, sub-pipelines, and cancel handling.
 pub struct PipelineExecutor<'a, M: Mapper, HR: ActionHookResolver, AR: ActionResolver> {
     pub pipeline: Pipeline,
+    /// Pipeline as given, before `PipelineResolver::prepare`; checkpoints
+    /// record it instead of `pipeline` when set
+    pub definition: Option<Pipeline>,
     pub mapper: Arc<M>,
     pub global_action_hooks: Vec<Arc<dyn ActionHook>>,
     pub global_pipeline_hooks: Vec<Arc<dyn PipelineHook>>,
     pub hook_resolver: &'a HR,
     pub action_resolver: &'a AR,
     pub cancel_token: CancellationToken,
+    pub deadline: Option<Instant>,
+    pub state_store: Option<Arc<dyn StateStore>>,
+    pub run_id: Option<String>,
+    pub sub_pipelines: Option<SubPipelines<'a>>,
+    /// Run that started this one from a sub-pipeline step
+    pub parent_run_id: Option<String>,
+    /// Number of sub-pipeline steps between this run and the top-level run
+    pub depth: usize,
+    /// Delays at least this long suspend the run on a durable timer
+    pub durable_timers: Option<Duration>,
 }
 
 impl<'a, M, HR, AR> PipelineExecutor<'a, M, HR, AR>
@@ -53,16 +98,65 @@ where
     ) -> Self {
         Self {
             pipeline,
+            definition: None,
             mapper,
             global_action_hooks,
             global_pipeline_hooks,
             hook_resolver,
             action_resolver,
             cancel_token,
+            deadline: None,
+            state_store: None,
+            run_id: None,
+            sub_pipelines: None,
+            parent_run_id: None,
+            depth: 0,
+            durable_timers: None,
         }
     }
 
-    /// Executes the pipeline based on dynamic routing.
+    /// Fails the run with `EngineError::Timeout` once `deadline` passes.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Checkpoints the run to `store` before every step so it can be resumed.
+    pub fn with_state_store(mut self, store: Option<Arc<dyn StateStore>>) -> Self {
+        self.state_store = store;
+        self
+    }
+
+    /// Checkpoints `definition` instead of the prepared pipeline being run.
+    pub fn with_definition(mut self, definition: Pipeline) -> Self {
+        self.definition = Some(definition);
+        self
+    }
+
+    /// Runs under `run_id` instead of a generated one.
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// Lets sub-pipeline steps run pipelines found through `sub_pipelines`.
+    pub fn with_sub_pipelines(mut self, sub_pipelines: Option<SubPipelines<'a>>) -> Self {
+        self.sub_pipelines = sub_pipelines;
+        self
+    }
+
+    /// Suspends the run on a timer in the state store for delays of at least
+    /// `threshold` instead of sleeping through them.
+    pub fn with_durable_timers(mut self, threshold: Option<Duration>) -> Self {
+        self.durable_timers = threshold;
+        self
+    }
+
+    fn deadline_passed(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Executes the pipeline based on dynamic routing, or declared dependencies in DAG mode.
     pub async fn execute(&self, input: Value) -> Result<ExecutionContext> {
         let _ = tracing_subscriber::fmt::try_init();
 
@@ -71,21 +165,84 @@ where
             &self.pipeline.key,
             Environment::new("local", ryvus_core::environment::EnvironmentKind::Local),
         );
-        exec_ctx.data.insert("payload".to_string(), input);
-
-        debug!("Triggering start hooks");
-        for hook in &self.global_pipeline_hooks {
-            hook.start(&mut exec_ctx).await;
+        if let Some(run_id) = &self.run_id {
+            exec_ctx.run_id = run_id.clone();
         }
+        exec_ctx.parent_run_id = self.parent_run_id.clone();
+        exec_ctx.data.insert("payload".to_string(), input);
 
         // Start at the first step in the pipeline
-        let mut current_key = self
+        let start = self
             .pipeline
             .steps
             .first()
             .ok_or_else(|| EngineError::Other("Pipeline has no steps".into()))?
             .key
             .clone();
+
+        Box::pin(self.run(exec_ctx, Some(start))).await
+    }
+
+    /// Continues a checkpointed run at `next_step`. Steps already recorded in
+    /// the context keep their results; in DAG mode only steps without a
+    /// successful result are run again.
+    pub async fn resume(
+        &self,
+        mut exec_ctx: ExecutionContext,
+        next_step: Option<String>,
+    ) -> Result<ExecutionContext> {
+        debug!("Resuming run {} at {:?}", exec_ctx.run_id, next_step);
+        exec_ctx.error = None;
+        exec_ctx.finished_at = None;
+
+        Box::pin(self.run(exec_ctx, next_step)).await
+    }
+
+    async fn run(
+        &self,
+        mut exec_ctx: ExecutionContext,
+        start: Option<String>,
+    ) -> Result<ExecutionContext> {
+        debug!("Triggering start hooks");
+        for hook in &self.global_pipeline_hooks {
+            hook.start(&mut exec_ctx).await;
+        }
+
+        if self.pipeline.mode == ExecutionMode::Dag {
+            match Box::pin(self.execute_dag(&mut exec_ctx)).await {
+                Ok(()) => {}
+                Err(EngineError::Canceled) => {
+                    self.checkpoint(&exec_ctx, None, PipelineState::Canceled)
+                        .await;
+                    for hook in &self.global_pipeline_hooks {
+                        hook.canceled(&mut exec_ctx).await;
+                    }
+                    return Err(EngineError::Canceled);
+                }
+                Err(e) => {
+                    exec_ctx.error = Some(e.to_string());
+                    self.fail(&mut exec_ctx, None).await;
+                    return Err(e);
+                }
+            }
+
+            self.checkpoint(&exec_ctx, None, PipelineState::Completed)
+                .await;
+            for hook in &self.global_pipeline_hooks {
+                hook.completed(&mut exec_ctx).await;
+            }
+            return Ok(exec_ctx);
+        }
+
+        let Some(mut current_key) = start else {
+            self.checkpoint(&exec_ctx, None, PipelineState::Completed)
+                .await;
+            for hook in &self.global_pipeline_hooks {
+                hook.completed(&mut exec_ctx).await;
+            }
+            return Ok(exec_ctx);
+        };
+        let mut budget = RouteBudget::new(&self.pipeline);
         debug!("Current step key: {}", current_key);
 
         loop {
@@ -95,31 +252,105 @@ where
             if self.cancel_token.is_cancelled() {
                 debug!("Cancelling:  {}", current_key);
 
+                self.checkpoint(&exec_ctx, Some(&current_key), PipelineState::Canceled)
+                    .await;
                 for hook in &self.global_pipeline_hooks {
                     hook.canceled(&mut exec_ctx).await;
                 }
                 return Err(EngineError::Canceled);
             }
 
+            if self.deadline_passed() {
+                debug!("Deadline exceeded before:  {}", current_key);
+
+                let error = EngineError::Timeout("Pipeline deadline exceeded".into());
+                exec_ctx.error = Some(error.to_string());
+                self.fail(&mut exec_ctx, Some(&current_key)).await;
+                return Err(error);
+            }
+
+            if let Err(error) = budget.visit(&current_key) {
+                warn!("Run {} stopped: {}", exec_ctx.run_id, error);
+
+                exec_ctx.error = Some(error.to_string());
+                self.fail(&mut exec_ctx, Some(&current_key)).await;
+                return Err(error);
+            }
+
+            self.checkpoint(&exec_ctx, Some(&current_key), PipelineState::Running)
+                .await;
+
             // Find current step
-            let step = self
-                .pipeline
-                .steps
-                .iter()
-                .find(|s| s.key == current_key)
-                .ok_or_else(|| EngineError::Other(format!("Step '{}' not found", current_key)))?;
+            let step = self.find_step(&current_key)?;
 
             debug!("Resolved step:  {}", step.key);
             // Execute current step
-            let result = self.execute_action_step(step, &mut exec_ctx).await;
+            let result = match (&step.parallel, step.action.is_empty(), &step.wait_for) {
+                // Pure fan-out steps have no action of their own
+                (Some(_), true, _) => Ok(ActionResult::skipped()),
+                // Sub-pipelines cannot be suspended on their own
+                (_, _, Some(wait)) if self.depth == 0 => {
+                    let Some(result) = self.receive_signal(step, wait, &mut exec_ctx).await else {
+                        debug!("Run {} waits for '{}'", exec_ctx.run_id, wait.signal);
+                        self.checkpoint(&exec_ctx, Some(&step.key), PipelineState::Suspended)
+                            .await;
+                        if let Some(timeout_at) =
+                            exec_ctx.waiting.as_ref().and_then(|w| w.timeout_at)
+                        {
+                            self.schedule(&exec_ctx.run_id, timeout_at).await;
+                        }
+                        return Ok(exec_ctx);
+                    };
+                    if let (ExecutionStatus::Timeout, Some(otherwise)) =
+                        (&result.status, &step.otherwise)
+                    {
+                        current_key = otherwise.clone();
+                        continue;
+                    }
+                    Ok(result)
+                }
+                _ if self.depth == 0 && is_delay(step) => {
+                    if let Some(until) = self.durable_sleep(step, &exec_ctx) {
+                        debug!("Run {} sleeps until {}", exec_ctx.run_id, until);
+                        exec_ctx.sleeping_until = Some(until);
+                        self.checkpoint(&exec_ctx, Some(&step.key), PipelineState::Suspended)
+                            .await;
+                        self.schedule(&exec_ctx.run_id, until).await;
+                        return Ok(exec_ctx);
+                    }
+                    self.execute_action_step(step, &mut exec_ctx).await
+                }
+                _ => self.execute_action_step(step, &mut exec_ctx).await,
+            };
 
             match result {
                 Ok(action_result) => {
-                    if action_result.status == ExecutionStatus::Failed {
-                        exec_ctx.error = action_result
-                            .message
-                            .clone()
-                            .or(Some("Step failed without message".into()));
+                    let timed_out = action_result.status == ExecutionStatus::Timeout;
+                    let failure = if action_result.status == ExecutionStatus::Failed || timed_out {
+                        Some(
+                            action_result
+                                .message
+                                .clone()
+                                .unwrap_or_else(|| "Step failed without message".into()),
+                        )
+                    } else if let Some(parallel) = &step.parallel {
+                        Box::pin(self.execute_parallel(step, parallel, &mut exec_ctx))
+                            .await
+                            .err()
+                    } else {
+                        None
+                    };
+
+                    if let Some(message) = failure {
+                        exec_ctx.error = Some(message);
+
+                        // The run is out of time, so there is nothing to route to
+                        if self.deadline_passed() {
+                            let error = EngineError::Timeout("Pipeline deadline exceeded".into());
+                            exec_ctx.error = Some(error.to_string());
+                            self.fail(&mut exec_ctx, Some(&step.key)).await;
+                            return Err(error);
+                        }
 
                         // Handle on_error routing
                         if let Some(on_error) = &step.on_error {
@@ -127,19 +358,25 @@ where
                             continue;
                         }
 
-                        // Trigger global hooks
-                        for hook in &self.global_pipeline_hooks {
-                            hook.failed(&mut exec_ctx).await;
-                        }
+                        self.fail(&mut exec_ctx, Some(&step.key)).await;
 
                         // Stop pipeline here
-                        return Err(EngineError::Action(
-                            exec_ctx.error.clone().unwrap_or_default(),
-                        ));
+                        let message = exec_ctx.error.clone().unwrap_or_default();
+                        return Err(if timed_out {
+                            EngineError::Timeout(message)
+                        } else {
+                            EngineError::Action(message)
+                        });
+                    }
+
+                    // Branches hand control to their join step
+                    if let Some(parallel) = &step.parallel {
+                        current_key = parallel.join.clone();
+                        continue;
                     }
 
                     // Existing success flow
-                    if let Some(next_key) = self.resolve_next_step(step, &exec_ctx)? {
+                    if let Some(next_key) = self.resolve_next_step(step, &exec_ctx) {
                         current_key = next_key;
                     } else {
                         break;
@@ -147,13 +384,17 @@ where
                 }
 
                 Err(e) => {
-                    // existing error handling remains
+                    // Engine errors fail the run like a failed step, resumable at this step
                     exec_ctx.error = Some(e.to_string());
-                    break;
+                    self.fail(&mut exec_ctx, Some(&current_key)).await;
+                    return Ok(exec_ctx);
                 }
             }
         }
 
+        self.checkpoint(&exec_ctx, None, PipelineState::Completed)
+            .await;
+
         for hook in &self.global_pipeline_hooks {
             hook.completed(&mut exec_ctx).await;
         }
@@ -161,11 +402,591 @@ where
         Ok(exec_ctx)
     }
 
-    async fn execute_action_step(
+    /// Consumes the signal a `wait_for` step waits on. Returns `None` while the
+    /// run has to stay suspended, otherwise the step result: the signal
+    /// payload, or a timeout once `timeout_ms` passed without it.
+    async fn receive_signal(
+        &self,
+        step: &PipelineStep,
+        wait: &WaitFor,
+        ctx: &mut ExecutionContext,
+    ) -> Option<ActionResult> {
+        let now = Utc::now();
+        let waiting = ctx
+            .waiting
+            .get_or_insert_with(|| SignalWait {
+                step: step.key.clone(),
+                signal: wait.signal.clone(),
+                since: now,
+                timeout_at: wait
+                    .timeout_ms
+                    .map(|t| now + chrono::Duration::milliseconds(t as i64)),
+                payload: None,
+            })
+            .clone();
+
+        let mut result = match waiting.payload {
+            Some(payload) => ActionResult::success(payload),
+            None if waiting.expired(now) => ActionResult {
+                status: ExecutionStatus::Timeout,
+                ..ActionResult::failed(format!(
+                    "Signal '{}' did not arrive within {}ms",
+                    wait.signal,
+                    wait.timeout_ms.unwrap_or_default()
+                ))
+            },
+            None => return None,
+        };
+        ctx.waiting = None;
+
+        result.key = step.key.clone();
+        result.started_at = Some(waiting.since);
+        result.duration_ms = Some((now - waiting.since).num_milliseconds().max(0) as u64);
+        ctx.insert_result(step.key.clone(), result.clone());
+        self.record_step(&ctx.run_id, &result).await;
+        Some(result)
+    }
+
+    /// End of the delay when it is long enough to suspend the run on a
+    /// durable timer; `None` when the step should sleep inline.
+    fn durable_sleep(&self, step: &PipelineStep, ctx: &ExecutionContext) -> Option<DateTime<Utc>> {
+        let threshold = self.durable_timers?;
+        // A run woken before its timer fired goes back to sleep
+        let until = match ctx.sleeping_until {
+            Some(until) => until,
+            None => delay_until(step, ctx).ok()?,
+        };
+        let remaining = (until - Utc::now()).to_std().ok()?;
+        (remaining >= threshold).then_some(until)
+    }
+
+    /// Runs a `sleep_ms` or `wait_until` step inline, finishing a durable
+    /// sleep the run was woken from.
+    async fn execute_delay(&self, step: &PipelineStep, ctx: &mut ExecutionContext) -> ActionResult {
+        let started_at = Utc::now();
+        let until = match ctx.sleeping_until.take() {
+            Some(until) => Ok(until),
+            None => delay_until(step, ctx),
+        };
+
+        let mut result = match until {
+            Ok(until) => {
+                let remaining = (until - Utc::now()).to_std().unwrap_or_default();
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => ActionResult {
+                        status: ExecutionStatus::Canceled,
+                        ..ActionResult::failed("Canceled")
+                    },
+                    _ = sleep_until(self.deadline) => ActionResult {
+                        status: ExecutionStatus::Timeout,
+                        ..ActionResult::failed("Pipeline deadline exceeded")
+                    },
+                    _ = tokio::time::sleep(remaining) => {
+                        ActionResult::success(json!({ "until": until.to_rfc3339() }))
+                    }
+                }
+            }
+            Err(message) => ActionResult::failed(message),
+        };
+
+        let finished_at = Utc::now();
+        result.key = step.key.clone();
+        result.started_at = Some(started_at);
+        result.finished_at = Some(finished_at);
+        result.duration_ms = Some((finished_at - started_at).num_milliseconds().max(0) as u64);
+        ctx.insert_result(step.key.clone(), result.clone());
+        self.record_step(&ctx.run_id, &result).await;
+        result
+    }
+
+    /// Ends a failed run: compensates its completed steps, checkpoints it
+    /// and triggers the failure hooks.
+    ///
+    /// Boxed so the run loop, which fails in several places, stays small
+    /// enough for deeply nested sub-pipelines.
+    fn fail<'b>(
+        &'b self,
+        ctx: &'b mut ExecutionContext,
+        next_step: Option<&'b str>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'b>> {
+        Box::pin(async move {
+            let state = self.compensate(ctx).await;
+            self.checkpoint(ctx, next_step, state).await;
+            for hook in &self.global_pipeline_hooks {
+                hook.failed(ctx).await;
+            }
+        })
+    }
+
+    /// Runs the `compensate` actions of the steps that succeeded, most recent
+    /// first, and reports the outcome to the pipeline hooks.
+    ///
+    /// Compensation ignores the run's cancellation and deadline so a rollback
+    /// is not cut short by whatever stopped the run.
+    async fn compensate(&self, ctx: &mut ExecutionContext) -> PipelineState {
+        let completed: Vec<_> = ctx
+            .steps
+            .iter()
+            .rev()
+            .filter(|r| r.status == ExecutionStatus::Success)
+            .filter_map(|r| {
+                let step = self.find_step(&r.key).ok()?;
+                let action = step.compensate.as_ref()?;
+                Some((step, action, r.clone()))
+            })
+            .collect();
+        if completed.is_empty() {
+            return PipelineState::Failed;
+        }
+
+        for (step, action, result) in completed {
+            debug!("Compensating step '{}' with '{}'", step.key, action);
+            let compensation = self.compensate_step(step, action, &result, ctx).await;
+            if compensation.status != ExecutionStatus::Success {
+                warn!(
+                    "Could not compensate step '{}' of run {}: {}",
+                    step.key,
+                    ctx.run_id,
+                    compensation.message.clone().unwrap_or_default()
+                );
+            }
+            ctx.compensations.push(compensation);
+        }
+
+        if ctx.status() == ExecutionStatus::Compensated {
+            for hook in &self.global_pipeline_hooks {
+                hook.compensated(ctx).await;
+            }
+            PipelineState::Compensated
+        } else {
+            for hook in &self.global_pipeline_hooks {
+                hook.compensation_failed(ctx).await;
+            }
+            PipelineState::Failed
+        }
+    }
+
+    /// Runs `action` with the step's resolved config and `{ input, output }`
+    /// of the step result it undoes.
+    async fn compensate_step(
+        &self,
+        step: &PipelineStep,
+        action: &str,
+        result: &ActionResult,
+        ctx: &ExecutionContext,
+    ) -> ActionResult {
+        let failed = |message: String| ActionResult {
+            key: step.key.clone(),
+            action: Some(action.to_string()),
+            ..ActionResult::failed(message)
+        };
+
+        let Some(mut compensation) = self.action_resolver.resolve(action).await else {
+            return failed(format!("Action '{}' not found", action));
+        };
+        let mut config = step.config.clone();
+        JsonPathConfigResolver.resolve(&mut config, ctx);
+        if let Err(e) = compensation.configure(config).await {
+            return failed(EngineError::Config(e.to_string()).to_string());
+        }
+
+        let executor = ActionExecutor::new(
+            step.key.clone(),
+            compensation,
+            self.global_action_hooks.clone(),
+            self.hook_resolver,
+            self.mapper.clone(),
+            CancellationToken::new(),
+            json!({ "input": result.input, "output": result.output }),
+        )
+        .with_context(build_jsonpath_context(ctx));
+
+        // Compensations are kept apart from the step history
+        let mut scratch = ctx.clone();
+        match executor.execute(&mut scratch).await {
+            // Failed attempts are keyed by action; compensations by step
+            Ok(result) => ActionResult {
+                key: step.key.clone(),
+                ..result
+            },
+            Err(e) => failed(e.to_string()),
+        }
+    }
+
+    /// Persists the run state; storage failures are logged and never fail the run.
+    async fn checkpoint(
+        &self,
+        ctx: &ExecutionContext,
+        next_step: Option<&str>,
+        state: PipelineState,
+    ) {
+        let Some(store) = &self.state_store else {
+            return;
+        };
+        // Built in a plain fn: every nested run carries this future on its stack
+        let Some(checkpoint) = self.masked_checkpoint(ctx, next_step, state) else {
+            return;
+        };
+        if let Err(e) = store.save_checkpoint(&checkpoint).await {
+            warn!("Could not checkpoint run {}: {}", ctx.run_id, e);
+        }
+    }
+
+    /// Checkpoints the unprepared definition with its secrets masked.
+    fn masked_checkpoint(
+        &self,
+        ctx: &ExecutionContext,
+        next_step: Option<&str>,
+        state: PipelineState,
+    ) -> Option<Box<RunCheckpoint>> {
+        let pipeline = self.definition.as_ref().unwrap_or(&self.pipeline);
+        let checkpoint = RunCheckpoint::new(pipeline, ctx, next_step.map(str::to_string), state);
+        match self.masker().mask(&checkpoint) {
+            Ok(checkpoint) => Some(Box::new(checkpoint)),
+            Err(e) => {
+                warn!("Could not mask the checkpoint of run {}: {}", ctx.run_id, e);
+                None
+            }
+        }
+    }
+
+    /// Masks the secrets of the pipelines this run was prepared from.
+    pub fn masker(&self) -> SensitiveMasker {
+        let secrets = self
+            .sub_pipelines
+            .iter()
+            .flat_map(|sub| &sub.resolvers)
+            .flat_map(|resolver| resolver.secrets())
+            .collect();
+        SensitiveMasker::new(secrets)
+    }
+
+    /// Saves a timer that wakes the suspended run at `fire_at`.
+    async fn schedule(&self, run_id: &str, fire_at: DateTime<Utc>) {
+        let Some(store) = &self.state_store else {
+            return;
+        };
+        if let Err(e) = store.save_timer(&RunTimer::new(run_id, fire_at)).await {
+            warn!("Could not schedule wake-up of run {}: {}", run_id, e);
+        }
+    }
+
+    async fn record_step(&self, run_id: &str, result: &ActionResult) {
+        let Some(store) = &self.state_store else {
+            return;
+        };
+        let data = match serde_json::to_value(result) {
+            Ok(data) => self.masker().mask_value(&data),
+            Err(e) => {
+                warn!("Could not serialize step '{}': {}", result.key, e);
+                return;
+            }
+        };
+        if let Err(e) = store.update_step(run_id, &result.key, data).await {
+            warn!(
+                "Could not record step '{}' of run {}: {}",
+                result.key, run_id, e
+            );
+        }
+    }
+
+    fn find_step(&self, key: &str) -> Result<&PipelineStep> {
+        self.pipeline
+            .steps
+            .iter()
+            .find(|s| s.key == key)
+            .ok_or_else(|| EngineError::Other(format!("Step '{}' not found", key)))
+    }
+
+    /// Runs all branches of a fan-out concurrently and merges their outputs into
+    /// the context under the fan-out step key once the join condition is met.
+    /// Branches still running at that point are dropped.
+    async fn execute_parallel(
+        &self,
+        step: &PipelineStep,
+        parallel: &ParallelBranches,
+        ctx: &mut ExecutionContext,
+    ) -> std::result::Result<(), String> {
+        let total = parallel.branches.len();
+        let required = parallel.wait.required(total);
+        debug!(
+            "Fan-out '{}': {} branches, waiting for {}",
+            step.key, total, required
+        );
+
+        let mut pending: FuturesUnordered<_> = parallel
+            .branches
+            .iter()
+            .map(|branch| self.execute_branch(branch, &parallel.join, ctx.clone()))
+            .collect();
+
+        let mut merged = Map::new();
+        let mut failures = Vec::new();
+        while let Some(outcome) = pending.next().await {
+            for result in outcome.steps {
+                ctx.insert_result(result.action.clone().unwrap_or_default(), result);
+            }
+
+            match outcome.error {
+                None => {
+                    merged.insert(outcome.branch, outcome.output.unwrap_or(Value::Null));
+                }
+                Some(err) => failures.push(format!("{}: {}", outcome.branch, err)),
+            }
+
+            if merged.len() >= required || total - failures.len() < required {
+                break;
+            }
+        }
+        drop(pending);
+
+        // The main loop reports cancellation once control returns to it
+        if self.cancel_token.is_cancelled() {
+            return Ok(());
+        }
+
+        let succeeded = merged.len();
+        ctx.insert(step.key.clone(), Value::Object(merged));
+
+        if succeeded >= required {
+            Ok(())
+        } else {
+            Err(format!(
+                "Join '{}' required {} of {} branches but {} succeeded ({})",
+                parallel.join,
+                required,
+                total,
+                succeeded,
+                failures.join("; ")
+            ))
+        }
+    }
+
+    /// Schedules steps by their `depends_on` edges, running every ready step
+    /// concurrently up to `max_concurrency`. After a failure no new steps start;
+    /// steps already running are allowed to finish.
+    async fn execute_dag(&self, ctx: &mut ExecutionContext) -> Result<()> {
+        self.pipeline
+            .dependency_order()
+            .map_err(EngineError::Other)?;
+
+        let limit = self.pipeline.max_concurrency.unwrap_or(usize::MAX).max(1);
+        let mut started: HashSet<&str> = HashSet::new();
+        let mut succeeded: HashSet<String> = HashSet::new();
+        let mut running = FuturesUnordered::new();
+        let mut failure: Option<String> = None;
+
+        // Steps that succeeded before a resume are not run again
+        for step in &self.pipeline.steps {
+            let done = ctx
+                .steps
+                .iter()
+                .any(|r| r.key == step.key && r.status == ExecutionStatus::Success);
+            if done {
+                started.insert(step.key.as_str());
+                succeeded.insert(step.key.clone());
+            }
+        }
+
+        loop {
+            if failure.is_none() && !self.cancel_token.is_cancelled() {
+                for step in &self.pipeline.steps {
+                    if running.len() >= limit {
+                        break;
+                    }
+                    if started.contains(step.key.as_str())
+                        || !step.depends_on.iter().all(|d| succeeded.contains(d))
+                    {
+                        continue;
+                    }
+
+                    debug!("DAG step ready: {}", step.key);
+                    started.insert(step.key.as_str());
+                    running.push(self.execute_node(step, ctx.clone()));
+                }
+            }
+
+            let Some(outcome) = running.next().await else {
+                break;
+            };
+
+            for result in outcome.steps {
+                ctx.insert_result(result.action.clone().unwrap_or_default(), result);
+            }
+
+            match outcome.error {
+                None => {
+                    succeeded.insert(outcome.branch);
+                }
+                Some(err) => {
+                    failure.get_or_insert(format!("Step '{}' failed: {}", outcome.branch, err));
+                }
+            }
+
+            self.checkpoint(ctx, None, PipelineState::Running).await;
+        }
+
+        if self.cancel_token.is_cancelled() {
+            return Err(EngineError::Canceled);
+        }
+
+        if self.deadline_passed() {
+            return Err(EngineError::Timeout("Pipeline deadline exceeded".into()));
+        }
+
+        match failure {
+            Some(message) => Err(EngineError::Action(message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs one step on its own context copy, ignoring routing.
+    async fn execute_node(&self, step: &PipelineStep, mut ctx: ExecutionContext) -> BranchOutcome {
+        let recorded = ctx.steps.len();
+
+        let error = match self.execute_action_step(step, &mut ctx).await {
+            Ok(result)
+                if matches!(
+                    result.status,
+                    ExecutionStatus::Failed | ExecutionStatus::Timeout
+                ) =>
+            {
+                Some(
+                    result
+                        .message
+                        .unwrap_or_else(|| "Step failed without message".into()),
+                )
+            }
+            Ok(result) if result.status == ExecutionStatus::Canceled => {
+                Some(EngineError::Canceled.to_string())
+            }
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        };
+
+        let steps = ctx.steps.split_off(recorded);
+        BranchOutcome {
+            branch: step.key.clone(),
+            output: steps.last().and_then(|s| s.output.clone()),
+            steps,
+            error,
+        }
+    }
+
+    /// Walks a single branch from `start` until it routes into `join` or ends.
+    async fn execute_branch(
+        &self,
+        start: &str,
+        join: &str,
+        mut ctx: ExecutionContext,
+    ) -> BranchOutcome {
+        let recorded = ctx.steps.len();
+        let mut current_key = start.to_string();
+        let mut budget = RouteBudget::new(&self.pipeline);
+
+        let error = loop {
+            if current_key == join {
+                break None;
+            }
+
+            if self.cancel_token.is_cancelled() {
+                break Some(EngineError::Canceled.to_string());
+            }
+
+            if self.deadline_passed() {
+                break Some("Pipeline deadline exceeded".to_string());
+            }
+
+            if let Err(e) = budget.visit(&current_key) {
+                break Some(e.to_string());
+            }
+
+            let step = match self.find_step(&current_key) {
+                Ok(step) => step,
+                Err(e) => break Some(e.to_string()),
+            };
+
+            if step.parallel.is_some() {
+                break Some(format!(
+                    "Step '{}' starts a nested parallel block, which is not supported inside a branch",
+                    step.key
+                ));
+            }
+
+            match self.execute_action_step(step, &mut ctx).await {
+                Ok(result)
+                    if matches!(
+                        result.status,
+                        ExecutionStatus::Failed | ExecutionStatus::Timeout
+                    ) =>
+                {
+                    if let Some(on_error) = &step.on_error {
+                        current_key = on_error.clone();
+                        continue;
+                    }
+                    break Some(
+                        result
+                            .message
+                            .unwrap_or_else(|| "Step failed without message".into()),
+                    );
+                }
+                Ok(result) if result.status == ExecutionStatus::Canceled => {
+                    break Some(EngineError::Canceled.to_string());
+                }
+                Ok(_) => match self.resolve_next_step(step, &ctx) {
+                    Some(next_key) => current_key = next_key,
+                    None => break None,
+                },
+                Err(e) => break Some(e.to_string()),
+            }
+        };
+
+        let steps = ctx.steps.split_off(recorded);
+        BranchOutcome {
+            branch: start.to_string(),
+            output: steps.last().and_then(|s| s.output.clone()),
+            steps,
+            error,
+        }
+    }
+
+    /// Boxed like `execute_sub_pipeline`, which it leads to: every nested run
+    /// adds its callers' frames to the stack.
+    fn execute_action_step<'b>(
+        &'b self,
+        step: &'b PipelineStep,
+        ctx: &'b mut ExecutionContext,
+    ) -> Pin<Box<dyn Future<Output = Result<ActionResult>> + Send + 'b>> {
+        Box::pin(async move {
+            if let Some(for_each) = &step.for_each {
+                return self.execute_for_each(step, for_each, ctx).await;
+            }
+            if let Some(repeat) = &step.repeat {
+                return self.execute_while(step, repeat, ctx).await;
+            }
+            self.execute_once(step, ctx).await
+        })
+    }
+
+    /// Runs the step's sub-pipeline or action a single time.
+    async fn execute_once(
         &self,
         step: &PipelineStep,
         ctx: &mut ExecutionContext,
     ) -> Result<ActionResult> {
+        if let Some(wait) = &step.wait_for {
+            return Err(EngineError::Config(format!(
+                "Step '{}' waits for signal '{}', which only top-level routed steps can do",
+                step.key, wait.signal
+            )));
+        }
+        if let Some(sub) = &step.pipeline {
+            return self.execute_sub_pipeline(step, sub, ctx).await;
+        }
+        if is_delay(step) {
+            return Ok(self.execute_delay(step, ctx).await);
+        }
+
         debug!("Executing step");
         match self.action_resolver.resolve(&step.action).await {
             Some(mut action) => {
@@ -200,9 +1021,16 @@ where
                     self.mapper.clone(),
                     self.cancel_token.clone(),
                     merged_params,
-                );
+                )
+                .with_retry(step.retry.clone())
+                .with_timeout(step.timeout_ms.map(Duration::from_millis))
+                .with_deadline(self.deadline)
+                .with_context(ctx_json)
+                .with_input_kept(step.compensate.is_some());
 
-                executor.execute(ctx).await
+                let result = executor.execute(ctx).await?;
+                self.record_step(&ctx.run_id, &result).await;
+                Ok(result)
             }
             None => Err(EngineError::Action(format!(
                 "Action '{}' not found",
@@ -211,63 +1039,447 @@ where
         }
     }
 
-    fn resolve_next_step(
+    /// Runs the step once per element selected by `for_each.items`, up to
+    /// `concurrency` elements at a time. Stops at the first failed element;
+    /// elements still running at that point are dropped.
+    async fn execute_for_each(
         &self,
         step: &PipelineStep,
-        ctx: &ExecutionContext,
-    ) -> Result<Option<String>> {
+        for_each: &ForEach,
+        ctx: &mut ExecutionContext,
+    ) -> Result<ActionResult> {
+        let started_at = Utc::now();
+        let items = match query_first(&build_jsonpath_context(ctx), &for_each.items) {
+            Some(Value::Array(items)) => items,
+            _ => {
+                let failure = format!(
+                    "for_each items '{}' did not select an array",
+                    for_each.items
+                );
+                return Ok(self
+                    .finish_loop(
+                        step,
+                        ctx,
+                        started_at,
+                        vec![],
+                        Some((ExecutionStatus::Failed, failure)),
+                    )
+                    .await);
+            }
+        };
+        let limit = for_each.concurrency.unwrap_or(1).max(1);
+        debug!(
+            "Step '{}' maps over {} items, {} at a time",
+            step.key,
+            items.len(),
+            limit
+        );
+
+        let base = &*ctx;
+        let mut iterations = futures::stream::iter(items.into_iter().enumerate())
+            .map(|(index, item)| {
+                let mut iteration = base.clone();
+                iteration.insert("item", item);
+                iteration.insert("index", json!(index));
+                async move { (index, self.execute_once(step, &mut iteration).await) }
+            })
+            .buffered(limit);
+
+        let mut outputs = Vec::new();
+        let mut failure = None;
+        while let Some((index, result)) = iterations.next().await {
+            let result = result?;
+            if let Some(status) = failed_status(&result) {
+                let message = result.message.unwrap_or_default();
+                failure = Some((status, format!("Item {} failed: {}", index, message)));
+                break;
+            }
+            outputs.push(result.output.unwrap_or(Value::Null));
+        }
+        drop(iterations);
+
+        Ok(self
+            .finish_loop(step, ctx, started_at, outputs, failure)
+            .await)
+    }
+
+    /// Repeats the step while its condition holds. Iterations run on a copy of
+    /// the context, so the condition sees the latest iteration's output.
+    async fn execute_while(
+        &self,
+        step: &PipelineStep,
+        repeat: &WhileLoop,
+        ctx: &mut ExecutionContext,
+    ) -> Result<ActionResult> {
+        let started_at = Utc::now();
+        let mut iteration = ctx.clone();
+        let mut outputs = Vec::new();
+
+        let failure = loop {
+            let index = outputs.len();
+            iteration.insert("index", json!(index));
+            let ctx_json = build_jsonpath_context(&iteration);
+            let resolve = |path: &str| query_first(&ctx_json, path);
+            if !repeat.when.evaluate(&resolve) {
+                break None;
+            }
+            if index >= repeat.max_iterations as usize {
+                break Some((
+                    ExecutionStatus::Failed,
+                    format!(
+                        "Step '{}' is still looping after max_iterations ({})",
+                        step.key, repeat.max_iterations
+                    ),
+                ));
+            }
+
+            let result = self.execute_once(step, &mut iteration).await?;
+            if let Some(status) = failed_status(&result) {
+                let message = result.message.unwrap_or_default();
+                break Some((status, format!("Iteration {} failed: {}", index, message)));
+            }
+            outputs.push(result.output.unwrap_or(Value::Null));
+        };
+
+        Ok(self
+            .finish_loop(step, ctx, started_at, outputs, failure)
+            .await)
+    }
+
+    /// Records a loop step with the ordered iteration outputs, failing it
+    /// with `failure` when an iteration did not succeed.
+    async fn finish_loop(
+        &self,
+        step: &PipelineStep,
+        ctx: &mut ExecutionContext,
+        started_at: chrono::DateTime<Utc>,
+        outputs: Vec<Value>,
+        failure: Option<(ExecutionStatus, String)>,
+    ) -> ActionResult {
+        let finished_at = Utc::now();
+        let (status, message) = match failure {
+            Some((status, message)) => (status, Some(message)),
+            None => (ExecutionStatus::Success, None),
+        };
+        let result = ActionResult {
+            id: generate_id("action_result"),
+            key: step.key.clone(),
+            action: (!step.action.is_empty()).then(|| step.action.clone()),
+            status,
+            input: None,
+            output: Some(Value::Array(outputs)),
+            message,
+            started_at: Some(started_at),
+            finished_at: Some(finished_at),
+            duration_ms: Some((finished_at - started_at).num_milliseconds().max(0) as u64),
+            attempts: vec![],
+        };
+
+        ctx.insert_result(step.key.clone(), result.clone());
+        self.record_step(&ctx.run_id, &result).await;
+        result
+    }
+
+    /// Runs the child pipeline of a sub-pipeline step as its own run.
+    ///
+    /// The child is canceled with the parent and shares its deadline. Its
+    /// `ExecutionResult` becomes the step output, and a failed child fails the step.
+    ///
+    /// Boxed as a trait object because the child run's future contains this one.
+    fn execute_sub_pipeline<'b>(
+        &'b self,
+        step: &'b PipelineStep,
+        sub: &'b SubPipeline,
+        ctx: &'b mut ExecutionContext,
+    ) -> Pin<Box<dyn Future<Output = Result<ActionResult>> + Send + 'b>> {
+        Box::pin(async move {
+            ctx.current_step = Some(step.clone());
+            let sub_pipelines = self.sub_pipelines.as_ref().ok_or_else(|| {
+                EngineError::Config(format!(
+                    "Step '{}' runs pipeline '{}' but no pipelines are configured",
+                    step.key, sub.key
+                ))
+            })?;
+            if self.depth >= MAX_PIPELINE_DEPTH {
+                return Err(EngineError::Config(format!(
+                    "Step '{}' exceeds {} nested sub-pipelines",
+                    step.key, MAX_PIPELINE_DEPTH
+                )));
+            }
+
+            let mut found = None;
+            for resolver in &sub_pipelines.resolvers {
+                if let Some(definition) = resolver.resolve(&sub.key).await {
+                    found = Some((resolver.prepare(&definition), definition));
+                    break;
+                }
+            }
+            let (pipeline, definition) = found
+                .ok_or_else(|| EngineError::Action(format!("Pipeline '{}' not found", sub.key)))?;
+
+            let input = match &sub.input {
+                Some(input) => {
+                    let mut input = input.clone();
+                    resolve_jsonpaths(&mut input, &build_jsonpath_context(ctx));
+                    input
+                }
+                None => ctx
+                    .data
+                    .get("payload")
+                    .cloned()
+                    .unwrap_or_else(|| json!({})),
+            };
+
+            let deadline = [
+                self.deadline,
+                step.timeout_ms
+                    .map(|t| Instant::now() + Duration::from_millis(t)),
+                pipeline
+                    .timeout_ms
+                    .map(|t| Instant::now() + Duration::from_millis(t)),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+            let hooks = [
+                sub_pipelines.global_hooks.clone(),
+                sub_pipelines.hook_resolver.resolve(&pipeline.key),
+            ]
+            .concat();
+            let run_id = generate_id("run");
+
+            let mut child = PipelineExecutor::new(
+                pipeline,
+                self.mapper.clone(),
+                self.global_action_hooks.clone(),
+                hooks,
+                self.hook_resolver,
+                self.action_resolver,
+                self.cancel_token.child_token(),
+            )
+            .with_deadline(deadline)
+            .with_state_store(self.state_store.clone())
+            .with_run_id(run_id.clone())
+            .with_sub_pipelines(Some(sub_pipelines.clone()));
+            child.definition = Some(definition);
+            child.parent_run_id = Some(ctx.run_id.clone());
+            child.depth = self.depth + 1;
+
+            debug!(
+                "Step '{}' starts pipeline '{}' as run {}",
+                step.key, sub.key, run_id
+            );
+            let started_at = Utc::now();
+            let kept_input = step.compensate.is_some().then(|| input.clone());
+            let child_result = match child.execute(input).await {
+                Ok(child_ctx) => child_ctx.into_result(),
+                Err(e) => self.failed_child(&child, &run_id, &e).await,
+            };
+
+            if let Some(store) = &self.state_store {
+                match self.masker().mask(&child_result) {
+                    Ok(stored) => {
+                        if let Err(e) = store.save_result(&stored).await {
+                            warn!("Could not save result of run {}: {}", run_id, e);
+                        }
+                    }
+                    Err(e) => warn!("Could not mask the result of run {}: {}", run_id, e),
+                }
+            }
+
+            let finished_at = Utc::now();
+            let result = ActionResult {
+                id: generate_id("action_result"),
+                key: step.key.clone(),
+                action: None,
+                // A rolled-back child still failed the step
+                status: match child_result.status {
+                    ExecutionStatus::Compensated => ExecutionStatus::Failed,
+                    ref status => status.clone(),
+                },
+                input: kept_input,
+                output: Some(serde_json::to_value(&child_result).map_err(|e| {
+                    EngineError::Other(format!("Could not serialize run {}: {}", run_id, e))
+                })?),
+                message: child_result
+                    .error
+                    .as_ref()
+                    .map(|e| format!("Pipeline '{}' (run {}) failed: {}", sub.key, run_id, e)),
+                started_at: Some(started_at),
+                finished_at: Some(finished_at),
+                duration_ms: Some((finished_at - started_at).num_milliseconds().max(0) as u64),
+                attempts: vec![],
+            };
+
+            ctx.insert_result(step.key.clone(), result.clone());
+            self.record_step(&ctx.run_id, &result).await;
+            Ok(result)
+        })
+    }
+
+    /// Result of a child run that stopped with an engine error, including the
+    /// steps of its last checkpoint when a state store is configured.
+    async fn failed_child(
+        &self,
+        child: &PipelineExecutor<'_, M, HR, AR>,
+        run_id: &str,
+        err: &EngineError,
+    ) -> ExecutionResult {
+        let checkpoint = match &self.state_store {
+            Some(store) => store.load_checkpoint(run_id).await.ok().flatten(),
+            None => None,
+        };
+        let context = checkpoint.map(|c| c.context).unwrap_or_else(|| {
+            let mut context = ExecutionContext::new(
+                &child.pipeline.key,
+                Environment::new("local", ryvus_core::environment::EnvironmentKind::Local),
+            );
+            context.run_id = run_id.to_string();
+            context.parent_run_id = child.parent_run_id.clone();
+            context
+        });
+
+        let mut result = context.into_result();
+        result.status = match err {
+            EngineError::Canceled => ExecutionStatus::Canceled,
+            _ if result.status == ExecutionStatus::Compensated => ExecutionStatus::Compensated,
+            EngineError::Timeout(_) => ExecutionStatus::Timeout,
+            _ => ExecutionStatus::Failed,
+        };
+        result.error = Some(err.to_string());
+        result
+    }
+
+    fn resolve_next_step(&self, step: &PipelineStep, ctx: &ExecutionContext) -> Option<String> {
         // Evaluate all conditional branches first
-        for cond in &step.next_when {
-            if self.evaluate_condition(&cond.when, ctx)? {
-                return Ok(Some(cond.next.clone()));
+        if !step.next_when.is_empty() {
+            let ctx_json = build_jsonpath_context(ctx);
+            let resolve = |path: &str| query_first(&ctx_json, path);
+            for cond in &step.next_when {
+                if cond.when.evaluate(&resolve) {
+                    return Some(cond.next.clone());
+                }
             }
         }
 
-        // If no condition matched, use the else path
-        if let Some(else_key) = &step.otherwise {
-            return Ok(Some(else_key.clone()));
+        // If no condition matched, use the else path; signal steps only take
+        // it when their signal times out
+        if let Some(else_key) = step.otherwise.as_ref().filter(|_| step.wait_for.is_none()) {
+            return Some(else_key.clone());
         }
 
         // Default linear next
-        Ok(step.next.clone())
+        step.next.clone()
     }
+}
 
-    fn evaluate_condition(&self, expr: &str, ctx: &ExecutionContext) -> Result<bool> {
-        use serde_json::Value;
+/// Steps shown at the end of a budget error's path trace.
+const TRACE_LEN: usize = 20;
 
-        let operators = ["==", "!=", ">=", "<=", ">", "<"];
-        let (op, (left_raw, right_raw)) = operators
-            .iter()
-            .find_map(|&op| expr.split_once(op).map(|(l, r)| (op, (l.trim(), r.trim()))))
-            .ok_or_else(|| EngineError::Other(format!("Invalid condition syntax: {expr}")))?;
-
-        // Parse right-hand side as JSON if possible
-        let right_val: Value = serde_json::from_str(right_raw)
-            .unwrap_or_else(|_| json!(right_raw.trim_matches('"').trim_matches('\'')));
-
-        // Build JSONPath context
-        let ctx_json = build_jsonpath_context(ctx);
-
-        // Resolve left-hand side
-        let mut left_val = json!(left_raw);
-        resolve_jsonpaths(&mut left_val, &ctx_json);
-
-        // Compare
-        Ok(match op {
-            "==" => left_val == right_val,
-            "!=" => left_val != right_val,
-            ">" => compare_num(&left_val, &right_val, |a, b| a > b),
-            ">=" => compare_num(&left_val, &right_val, |a, b| a >= b),
-            "<" => compare_num(&left_val, &right_val, |a, b| a < b),
-            "<=" => compare_num(&left_val, &right_val, |a, b| a <= b),
-            _ => false,
-        })
+/// Counts the steps routing enters against the pipeline's
+/// `max_visits_per_step` and `max_steps` budgets.
+struct RouteBudget {
+    max_visits: u32,
+    max_steps: u32,
+    visits: HashMap<String, u32>,
+    path: Vec<String>,
+}
+
+impl RouteBudget {
+    fn new(pipeline: &Pipeline) -> Self {
+        Self {
+            max_visits: pipeline
+                .max_visits_per_step
+                .unwrap_or(DEFAULT_MAX_VISITS_PER_STEP),
+            max_steps: pipeline.max_steps.unwrap_or(DEFAULT_MAX_STEPS),
+            visits: HashMap::new(),
+            path: Vec::new(),
+        }
+    }
+
+    /// Records a visit to `key`, failing once either budget is exceeded.
+    fn visit(&mut self, key: &str) -> Result<()> {
+        self.path.push(key.to_string());
+        let visits = self.visits.entry(key.to_string()).or_default();
+        *visits += 1;
+
+        if *visits > self.max_visits {
+            return Err(EngineError::Budget(format!(
+                "step '{}' entered more than {} times (max_visits_per_step); path: {}",
+                key,
+                self.max_visits,
+                self.trace()
+            )));
+        }
+        if self.path.len() > self.max_steps as usize {
+            return Err(EngineError::Budget(format!(
+                "run took more than {} steps (max_steps); path: {}",
+                self.max_steps,
+                self.trace()
+            )));
+        }
+        Ok(())
+    }
+
+    /// The steps taken so far, shortened to the most recent ones.
+    fn trace(&self) -> String {
+        let skipped = self.path.len().saturating_sub(TRACE_LEN);
+        let recent = self.path[skipped..].join(" -> ");
+        if skipped > 0 {
+            format!("({} earlier steps) -> {}", skipped, recent)
+        } else {
+            recent
+        }
+    }
+}
+
+/// Whether `step` is a `sleep_ms` or `wait_until` step.
+fn is_delay(step: &PipelineStep) -> bool {
+    step.sleep_ms.is_some() || step.wait_until.is_some()
+}
+
+/// When a delay step ends: `sleep_ms` from now, or the RFC 3339 timestamp or
+/// epoch milliseconds `wait_until` resolves to.
+fn delay_until(
+    step: &PipelineStep,
+    ctx: &ExecutionContext,
+) -> std::result::Result<DateTime<Utc>, String> {
+    if let Some(ms) = step.sleep_ms {
+        return Ok(Utc::now() + chrono::Duration::milliseconds(ms as i64));
+    }
+    let expr = step.wait_until.as_deref().unwrap_or_default();
+    let mut value = Value::String(expr.to_string());
+    resolve_jsonpaths(&mut value, &build_jsonpath_context(ctx));
+
+    let until = match &value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|t| t.with_timezone(&Utc)),
+        Value::Number(n) => n.as_i64().and_then(DateTime::from_timestamp_millis),
+        _ => None,
+    };
+    until.ok_or_else(|| {
+        format!(
+            "wait_until '{}' of step '{}' is not a timestamp: {}",
+            expr, step.key, value
+        )
+    })
+}
+
+/// Status of an iteration that stops its loop, or `None` when it succeeded.
+fn failed_status(result: &ActionResult) -> Option<ExecutionStatus> {
+    match result.status {
+        ExecutionStatus::Failed | ExecutionStatus::Timeout | ExecutionStatus::Canceled => {
+            Some(result.status.clone())
+        }
+        _ => None,
     }
 }
 
-// Helper for numeric comparisons
-fn compare_num<F: Fn(f64, f64) -> bool>(a: &Value, b: &Value, cmp: F) -> bool {
-    let Some(na) = a.as_f64() else { return false };
-    let Some(nb) = b.as_f64() else { return false };
-    cmp(na, nb)
+/// Result of running a parallel branch or DAG step on its own context copy.
+struct BranchOutcome {
+    branch: String,
+    output: Option<Value>,
+    steps: Vec<ActionResult>,
+    error: Option<String>,
 }
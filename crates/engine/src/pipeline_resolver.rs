@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use ryvus_core::prelude::pipeline::Pipeline;
+use std::collections::HashMap;
+
+/// Looks up the pipelines that sub-pipeline steps run.
+///
+/// A resolver handed to `EngineApi` can also fill in what its pipelines
+/// leave to the caller, such as variables and secrets. The engine runs the
+/// prepared pipeline but checkpoints the one it was given, and masks the
+/// resolver's secrets in everything it writes to the state store.
+#[async_trait]
+pub trait PipelineResolver: Send + Sync {
+    async fn resolve(&self, key: &str) -> Option<Pipeline>;
+
+    /// `pipeline` ready to run; called for every run, resumed ones included.
+    fn prepare(&self, pipeline: &Pipeline) -> Pipeline {
+        pipeline.clone()
+    }
+
+    /// Secret values `prepare` filled in so far.
+    fn secrets(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Pipelines registered on the engine with `Engine::with_pipeline`.
+#[derive(Default, Clone)]
+pub struct DefaultPipelineResolver {
+    registry: HashMap<String, Pipeline>,
+}
+
+impl DefaultPipelineResolver {
+    pub fn new() -> Self {
+        Self {
+            registry: HashMap::new(),
+        }
+    }
+
+    /// Registers `pipeline`, replacing any pipeline with the same key.
+    pub fn register(&mut self, pipeline: Pipeline) {
+        self.registry.insert(pipeline.key.clone(), pipeline);
+    }
+}
+
+#[async_trait]
+impl PipelineResolver for DefaultPipelineResolver {
+    async fn resolve(&self, key: &str) -> Option<Pipeline> {
+        self.registry.get(key).cloned()
+    }
+}
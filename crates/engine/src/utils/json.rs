@@ -5,7 +5,7 @@ use tracing::debug;
 /// - Objects are merged recursively
 /// - Arrays are replaced
 /// - Other values are overwritten by `b`
-pub fn deep_merge(mut a: Value, b: Value) -> Value {
+pub fn deep_merge(a: Value, b: Value) -> Value {
     debug!("{:?}, {:?}", a, b);
 
     match (a, b) {
@@ -1,9 +1,14 @@
 use jsonpath_rust::JsonPath;
-use ryvus_core::prelude::ExecutionContext;
+use ryvus_core::{
+    pipeline::template::{Lookup, Template},
+    prelude::ExecutionContext,
+};
 use serde_json::{json, Value};
+use tracing::warn;
 
 /// Builds a JSON structure for JSONPath resolution with:
 /// - $.payload
+/// - $.item and $.index inside loop iterations
 /// - $.<step>.output.<field>
 pub fn build_jsonpath_context(ctx: &ExecutionContext) -> Value {
     let mut map = serde_json::Map::new();
@@ -12,6 +17,11 @@ pub fn build_jsonpath_context(ctx: &ExecutionContext) -> Value {
     if let Some(payload) = ctx.data.get("payload") {
         map.insert("payload".into(), payload.clone());
     }
+    for key in ["item", "index"] {
+        if let Some(value) = ctx.data.get(key) {
+            map.insert(key.into(), value.clone());
+        }
+    }
 
     // 2️⃣ Include all step outputs keyed by step.key
     for step in &ctx.steps {
@@ -29,7 +39,8 @@ pub fn build_jsonpath_context(ctx: &ExecutionContext) -> Value {
 
     Value::Object(map)
 }
-/// Recursively resolve any JSONPath or secret:$. references
+/// Recursively resolve any JSONPath or secret:$. references,
+/// and `${$.path}` placeholders inside larger strings
 pub fn resolve_jsonpaths(value: &mut Value, ctx_json: &Value) {
     match value {
         Value::Object(map) => {
@@ -51,34 +62,51 @@ pub fn resolve_jsonpaths(value: &mut Value, ctx_json: &Value) {
                 return;
             }
 
-            // 2. Handle secret:$. prefix
-            let (expr, is_secret) = if let Some(stripped) = s.strip_prefix("secret:") {
+            // 2. Interpolate `${...}` placeholders; other phases own non-JSONPath subjects
+            if Template::is_template(s) {
+                match Template::parse(s) {
+                    Ok(template) => {
+                        *value = template.render(&mut |subject| {
+                            if !subject.starts_with('$') {
+                                return Lookup::Defer;
+                            }
+                            query_first(ctx_json, subject).map_or(Lookup::Missing, Lookup::Found)
+                        });
+                    }
+                    Err(e) => warn!("Leaving string unresolved: {}", e),
+                }
+                return;
+            }
+
+            // 3. Handle secret:$. prefix
+            let (expr, _is_secret) = if let Some(stripped) = s.strip_prefix("secret:") {
                 (stripped.to_string(), true)
             } else {
                 (s.clone(), false)
             };
 
-            // 3. Only resolve if it's actually a JSONPath
+            // 4. Only resolve if it's actually a JSONPath
             if !expr.starts_with("$.") {
                 return;
             }
 
-            // Run JSONPath query
-            match ctx_json.query_with_path(expr.as_str()) {
-                Ok(results) if !results.is_empty() => {
-                    let first = results.first().unwrap().clone();
-                    let inner_val = first.val();
-                    *value = inner_val.clone();
-                }
-                Ok(_) => {
-                    // nothing found, leave as-is
-                }
-                Err(err) => {
-                    eprintln!("JsonPath resolution error for '{}': {}", expr, err);
-                }
+            // Nothing found leaves the string as-is
+            if let Some(found) = query_first(ctx_json, &expr) {
+                *value = found;
             }
         }
 
         _ => {}
     }
 }
+
+/// First value matched by the JSONPath `expr`, or `None` when nothing matches.
+pub fn query_first(ctx_json: &Value, expr: &str) -> Option<Value> {
+    match ctx_json.query_with_path(expr) {
+        Ok(results) => results.first().map(|found| found.clone().val().clone()),
+        Err(err) => {
+            warn!("JsonPath resolution error for '{}': {}", expr, err);
+            None
+        }
+    }
+}
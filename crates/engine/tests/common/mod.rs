@@ -0,0 +1,23 @@
+//! Actions shared by the integration tests.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    prelude::{Action, ActionContext, ActionResult},
+};
+
+/// Succeeds with its input as the output.
+#[derive(Clone)]
+pub struct Echo;
+
+#[async_trait]
+impl Action for Echo {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Ok(ActionResult::success(ctx.input.clone().unwrap_or_default()))
+    }
+
+    fn key(&self) -> &str {
+        "test/echo"
+    }
+}
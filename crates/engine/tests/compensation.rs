@@ -0,0 +1,221 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    pipeline::hook::PipelineHook,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionContext, ExecutionStatus,
+        PipelineStep, StateStore,
+    },
+    state::in_memory::InMemoryStateStore,
+};
+use ryvus_engine::Engine;
+use serde_json::{json, Value};
+
+/// Creates a resource named after its input.
+#[derive(Clone)]
+struct Create;
+
+#[async_trait]
+impl Action for Create {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let name = ctx.input.as_ref().unwrap()["name"].clone();
+        Ok(ActionResult::success(
+            json!({ "id": format!("id-{}", name.as_str().unwrap()) }),
+        ))
+    }
+
+    fn key(&self) -> &str {
+        "cloud/create"
+    }
+}
+
+/// Records what it is asked to delete; fails for resources named `stuck`.
+#[derive(Clone, Default)]
+struct Delete {
+    deleted: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait]
+impl Action for Delete {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let input = ctx.input.clone().unwrap();
+        if input["input"]["name"] == "stuck" {
+            return Err(Error::Action("resource is locked".into()));
+        }
+        self.deleted.lock().unwrap().push(input);
+        Ok(ActionResult::success(json!({ "deleted": true })))
+    }
+
+    fn key(&self) -> &str {
+        "cloud/delete"
+    }
+}
+
+#[derive(Clone)]
+struct Fail;
+
+#[async_trait]
+impl Action for Fail {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Err(Error::Action("quota exceeded".into()))
+    }
+
+    fn key(&self) -> &str {
+        "cloud/fail"
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<&'static str>>,
+}
+
+#[async_trait]
+impl PipelineHook for Recorder {
+    async fn completed(&self, _context: &mut ExecutionContext) {
+        self.events.lock().unwrap().push("completed");
+    }
+
+    async fn failed(&self, _context: &mut ExecutionContext) {
+        self.events.lock().unwrap().push("failed");
+    }
+
+    async fn canceled(&self, _context: &mut ExecutionContext) {}
+
+    async fn start(&self, _context: &mut ExecutionContext) {}
+
+    async fn compensated(&self, _context: &mut ExecutionContext) {
+        self.events.lock().unwrap().push("compensated");
+    }
+
+    async fn compensation_failed(&self, _context: &mut ExecutionContext) {
+        self.events.lock().unwrap().push("compensation_failed");
+    }
+}
+
+fn create(key: &str, name: &str) -> PipelineStep {
+    PipelineStep::builder(key, "cloud/create")
+        .params(json!({ "name": name }))
+        .compensate("cloud/delete")
+        .build()
+}
+
+fn provision(network: &str) -> Pipeline {
+    let mut network = create("network", network);
+    network.next = Some("vm".into());
+    let mut vm = create("vm", "vm");
+    vm.next = Some("dns".into());
+
+    Pipeline::builder("provision")
+        .step(network)
+        .step(vm)
+        .step(PipelineStep::builder("dns", "cloud/fail").build())
+        .build()
+}
+
+fn engine(delete: Delete, hooks: Arc<Recorder>, store: Arc<InMemoryStateStore>) -> Engine {
+    Engine::default()
+        .with_action(Create)
+        .with_action(delete)
+        .with_action(Fail)
+        .with_pipeline_hook(hooks)
+        .with_state_store(store)
+}
+
+#[tokio::test]
+async fn undoes_completed_steps_in_reverse_order() {
+    let delete = Delete::default();
+    let deleted = delete.deleted.clone();
+    let hooks = Arc::new(Recorder::default());
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = engine(delete, hooks.clone(), store.clone());
+
+    let err = engine
+        .execute_run("provision-1", provision("net"), json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("quota exceeded"), "{err}");
+
+    // Each compensation sees the input and output of the step it undoes
+    assert_eq!(
+        *deleted.lock().unwrap(),
+        [
+            json!({ "input": { "name": "vm" }, "output": { "id": "id-vm" } }),
+            json!({ "input": { "name": "net" }, "output": { "id": "id-net" } }),
+        ]
+    );
+    assert_eq!(*hooks.events.lock().unwrap(), ["compensated", "failed"]);
+
+    let result = store.load_result("provision-1").await.unwrap().unwrap();
+    assert_eq!(result.status, ExecutionStatus::Compensated);
+    let undone: Vec<_> = result
+        .compensations
+        .iter()
+        .map(|c| (c.key.as_str(), c.action.as_deref()))
+        .collect();
+    assert_eq!(
+        undone,
+        [
+            ("vm", Some("cloud/delete")),
+            ("network", Some("cloud/delete"))
+        ]
+    );
+
+    // A compensated run is finished and resuming it returns the stored result
+    let resumed = engine.resume("provision-1").await.unwrap();
+    assert_eq!(resumed.status, ExecutionStatus::Compensated);
+}
+
+#[tokio::test]
+async fn failed_compensations_leave_the_run_failed() {
+    let delete = Delete::default();
+    let deleted = delete.deleted.clone();
+    let hooks = Arc::new(Recorder::default());
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = engine(delete, hooks.clone(), store.clone());
+
+    engine
+        .execute_run("provision-2", provision("stuck"), json!({}))
+        .await
+        .unwrap_err();
+
+    // The remaining steps are still compensated
+    assert_eq!(deleted.lock().unwrap().len(), 1);
+    assert_eq!(
+        *hooks.events.lock().unwrap(),
+        ["compensation_failed", "failed"]
+    );
+
+    let result = store.load_result("provision-2").await.unwrap().unwrap();
+    assert_eq!(result.status, ExecutionStatus::Failed);
+    let statuses: Vec<_> = result.compensations.iter().map(|c| &c.status).collect();
+    assert_eq!(
+        statuses,
+        [&ExecutionStatus::Success, &ExecutionStatus::Failed]
+    );
+    assert_eq!(result.compensations[1].key, "network");
+    assert!(result.compensations[1]
+        .message
+        .as_ref()
+        .unwrap()
+        .contains("resource is locked"));
+}
+
+#[tokio::test]
+async fn handled_failures_are_not_compensated() {
+    let delete = Delete::default();
+    let deleted = delete.deleted.clone();
+    let hooks = Arc::new(Recorder::default());
+    let engine = engine(delete, hooks.clone(), Arc::default());
+
+    let mut pipeline = provision("net");
+    pipeline.steps[2].on_error = Some("fallback".into());
+    pipeline.steps.push(create("fallback", "backup"));
+
+    let result = engine.execute(pipeline, json!({})).await.unwrap();
+    assert!(result.compensations.is_empty());
+    assert!(deleted.lock().unwrap().is_empty());
+    assert_eq!(*hooks.events.lock().unwrap(), ["completed"]);
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::Echo;
+use ryvus_core::{
+    pipeline::pipeline::ConditionalNext,
+    prelude::{pipeline::Pipeline, Condition, ExecutionStatus, PipelineStep},
+};
+use ryvus_engine::Engine;
+use serde_json::{json, Value};
+
+/// Evaluates `expr` against `doc`, resolving `$.a.b` style paths by hand.
+fn eval(expr: &str, doc: Value) -> bool {
+    let condition = Condition::parse(expr).unwrap();
+    condition.evaluate(&|path: &str| {
+        path.trim_start_matches("$.")
+            .split('.')
+            .try_fold(&doc, |value, key| value.get(key))
+            .cloned()
+    })
+}
+
+#[test]
+fn compares_typed_values() {
+    let doc = json!({
+        "total": 120, "ratio": 1.0, "name": "bob", "vip": true,
+        "tags": ["a", "b"], "note": null
+    });
+
+    assert!(eval("$.total >= 120", doc.clone()));
+    assert!(!eval("$.total > 120", doc.clone()));
+    assert!(eval("$.ratio == 1", doc.clone()));
+    assert!(eval("$.name < 'carol'", doc.clone()));
+    assert!(eval("$.vip == true", doc.clone()));
+    assert!(eval("$.tags == ['a', 'b']", doc.clone()));
+    // Mismatched types are neither equal nor ordered
+    assert!(!eval("$.total == '120'", doc.clone()));
+    assert!(!eval("$.name > 1", doc.clone()));
+    assert!(eval("$.total != '120'", doc));
+}
+
+#[test]
+fn supports_membership_regex_and_null_checks() {
+    let doc = json!({ "country": "DE", "tags": ["vip"], "email": "a@example.com", "note": null });
+
+    assert!(eval("$.country in ['DE', 'FR']", doc.clone()));
+    assert!(eval("$.tags contains 'vip'", doc.clone()));
+    assert!(eval("'example' in $.email", doc.clone()));
+    assert!(eval("$.email matches '@example\\.com$'", doc.clone()));
+    assert!(eval("$.email =~ '^a@'", doc.clone()));
+    assert!(eval("exists($.note) && $.note == null", doc.clone()));
+    assert!(!eval("exists($.missing)", doc.clone()));
+    assert!(eval("$.missing == null", doc));
+}
+
+#[test]
+fn combines_predicates_with_precedence() {
+    let doc = json!({ "a": 1, "b": 2, "flag": false });
+
+    // && binds tighter than ||
+    assert!(eval("$.a == 1 || $.b == 0 && $.flag", doc.clone()));
+    assert!(!eval("($.a == 1 || $.b == 0) && $.flag", doc.clone()));
+    assert!(eval("!$.flag && !($.a > $.b)", doc.clone()));
+    assert!(eval("$.b", doc));
+}
+
+#[test]
+fn reports_parse_errors_with_columns() {
+    let cases = [
+        ("$.a = 1", 5, "unexpected character '='"),
+        ("$.a == ", 8, "expected a value"),
+        ("($.a == 1", 10, "expected ')'"),
+        ("$.a matches '['", 13, "invalid regex"),
+        ("$.a == 1 $.b", 10, "unexpected input"),
+        ("$.a == 'open", 8, "unterminated string"),
+    ];
+    for (expr, column, message) in cases {
+        let err = Condition::parse(expr).unwrap_err();
+        assert_eq!(err.column, column, "{expr}: {err}");
+        assert!(err.message.contains(message), "{expr}: {err}");
+    }
+}
+
+#[test]
+fn reads_bare_words_as_strings() {
+    let condition = Condition::parse("$.status == approved || $.tier in [gold, 'silver']").unwrap();
+    assert_eq!(condition.bare_words(), ["approved", "gold"]);
+    assert!(eval(
+        "$.status == approved",
+        json!({ "status": "approved" })
+    ));
+    assert!(eval(
+        "$.tier in [gold, 'silver']",
+        json!({ "tier": "gold" })
+    ));
+}
+
+#[test]
+fn keeps_bracketed_paths_whole() {
+    let condition =
+        Condition::parse("$.items[?(@.price > 10)].name contains 'x' || exists($['a b'])").unwrap();
+    assert_eq!(
+        condition.paths(),
+        ["$.items[?(@.price > 10)].name", "$['a b']"]
+    );
+}
+
+#[test]
+fn parses_when_deserialized() {
+    let next: ConditionalNext =
+        serde_json::from_value(json!({ "when": "$.a >= 1 && $.b", "next": "x" })).unwrap();
+    assert_eq!(next.when.as_str(), "$.a >= 1 && $.b");
+    assert_eq!(
+        serde_json::to_value(&next).unwrap(),
+        json!({ "when": "$.a >= 1 && $.b", "next": "x" })
+    );
+
+    let err = serde_json::from_value::<ConditionalNext>(json!({ "when": "$.a >", "next": "x" }))
+        .unwrap_err();
+    assert!(err.to_string().contains("expected a value"));
+}
+
+#[tokio::test]
+async fn routes_on_compound_conditions() {
+    let engine = Engine::default().with_action(Echo);
+    let pipeline = |when: &str| {
+        Pipeline::builder("route")
+            .step(
+                PipelineStep::builder("check", "test/echo")
+                    .when(when.parse().unwrap(), "matched")
+                    .otherwise("fallback")
+                    .build(),
+            )
+            .step(PipelineStep::builder("matched", "test/echo").build())
+            .step(PipelineStep::builder("fallback", "test/echo").build())
+            .build()
+    };
+    let last_step = |result: &ryvus_core::action::result::ExecutionResult| {
+        result.steps.last().map(|s| s.key.clone()).unwrap()
+    };
+    let input = json!({ "total": 250, "country": "FR", "tags": ["b2b"] });
+
+    let result = engine
+        .execute(
+            pipeline("$.payload.total >= 200 && ($.payload.country in ['DE', 'FR'] || $.payload.tags contains 'vip')"),
+            input.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(last_step(&result), "matched");
+
+    let result = engine
+        .execute(
+            pipeline("$.check.output.total < 100 || !exists($.payload.country)"),
+            input,
+        )
+        .await
+        .unwrap();
+    assert_eq!(last_step(&result), "fallback");
+}
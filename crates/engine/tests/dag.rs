@@ -0,0 +1,101 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    pipeline::pipeline::ExecutionMode,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+    },
+};
+use ryvus_engine::Engine;
+use serde_json::json;
+
+/// Tracks how many instances run at the same time.
+#[derive(Clone, Default)]
+struct TrackedAction {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Action for TrackedAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+
+        Ok(ActionResult::success(json!({ "step": ctx.id })))
+    }
+
+    fn key(&self) -> &str {
+        "test/tracked"
+    }
+}
+
+fn step(key: &str, deps: &[&str]) -> PipelineStep {
+    deps.iter()
+        .fold(PipelineStep::builder(key, "test/tracked"), |b, d| {
+            b.depends_on(*d)
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn runs_steps_in_dependency_order() {
+    let engine = Engine::default().with_action(TrackedAction::default());
+    let pipeline = Pipeline::builder("dag")
+        .mode(ExecutionMode::Dag)
+        .steps(vec![
+            step("report", &["left", "right"]),
+            step("left", &["fetch"]),
+            step("right", &["fetch"]),
+            step("fetch", &[]),
+        ])
+        .build();
+
+    let result = engine.execute(pipeline, json!({})).await.unwrap();
+    let order: Vec<&str> = result.steps.iter().map(|s| s.key.as_str()).collect();
+
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(order.first(), Some(&"fetch"));
+    assert_eq!(order.last(), Some(&"report"));
+    assert_eq!(order.len(), 4);
+}
+
+#[tokio::test]
+async fn respects_max_concurrency() {
+    let action = TrackedAction::default();
+    let peak = action.peak.clone();
+    let engine = Engine::default().with_action(action);
+
+    let pipeline = Pipeline::builder("dag")
+        .mode(ExecutionMode::Dag)
+        .max_concurrency(2)
+        .steps((0..6).map(|i| step(&format!("s{i}"), &[])).collect())
+        .build();
+
+    engine.execute(pipeline, json!({})).await.unwrap();
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn rejects_cycles_and_missing_dependencies() {
+    let cyclic = Pipeline::builder("cyclic")
+        .steps(vec![step("a", &["b"]), step("b", &["a"]), step("c", &[])])
+        .build();
+    let err = cyclic.dependency_order().unwrap_err();
+    assert!(err.contains("a, b"), "{err}");
+
+    let missing = Pipeline::builder("missing")
+        .steps(vec![step("a", &["ghost"])])
+        .build();
+    assert!(missing.dependency_order().unwrap_err().contains("ghost"));
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::Echo;
+use ryvus_core::{
+    error::Error,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+    },
+};
+use ryvus_engine::Engine;
+use serde_json::{json, Value};
+
+/// Doubles `n`, finishing later for smaller values, and tracks how many calls overlap.
+#[derive(Clone, Default)]
+struct Double {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Action for Double {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+
+        let n = ctx
+            .input
+            .as_ref()
+            .and_then(|i| i["n"].as_u64())
+            .unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(40u64.saturating_sub(5 * n))).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+
+        if n == 13 {
+            return Err(Error::Action("unlucky record".into()));
+        }
+        Ok(ActionResult::success(json!(n * 2)))
+    }
+
+    fn key(&self) -> &str {
+        "test/double"
+    }
+}
+
+/// Reports `done` from its third call on.
+#[derive(Clone, Default)]
+struct Poll {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Action for Poll {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let status = if call >= 3 { "done" } else { "pending" };
+        Ok(ActionResult::success(
+            json!({ "call": call, "status": status }),
+        ))
+    }
+
+    fn key(&self) -> &str {
+        "test/poll"
+    }
+}
+
+fn map_pipeline(concurrency: Option<usize>) -> Pipeline {
+    Pipeline::builder("batch")
+        .step(
+            PipelineStep::builder("double", "test/double")
+                .params(json!({ "n": "$.item.n", "position": "$.index" }))
+                .for_each("$.payload.records", concurrency)
+                .build(),
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn maps_in_element_order_within_the_concurrency_limit() {
+    let records =
+        json!({ "records": [{ "n": 1 }, { "n": 2 }, { "n": 3 }, { "n": 4 }, { "n": 5 }] });
+
+    for (concurrency, expected_peak) in [(None, 1), (Some(3), 3)] {
+        let double = Double::default();
+        let peak = double.peak.clone();
+        let engine = Engine::default().with_action(double);
+
+        let result = engine
+            .execute(map_pipeline(concurrency), records.clone())
+            .await
+            .unwrap();
+        assert_eq!(result.status, ExecutionStatus::Success);
+        assert_eq!(result.result, Some(json!([2, 4, 6, 8, 10])));
+        assert_eq!(result.steps.len(), 1);
+        assert_eq!(peak.load(Ordering::SeqCst), expected_peak);
+    }
+}
+
+#[tokio::test]
+async fn maps_a_sub_pipeline_over_items() {
+    let engine = Engine::default().with_action(Echo).with_pipeline(
+        Pipeline::builder("greet")
+            .step(
+                PipelineStep::builder("hello", "test/echo")
+                    .params(json!({ "greeting": "hello ${$.payload.name}" }))
+                    .build(),
+            )
+            .build(),
+    );
+    let pipeline = Pipeline::builder("greet-all")
+        .step(
+            PipelineStep::builder("each", "")
+                .pipeline("greet", Some(json!("$.item")))
+                .for_each("$.payload.people", Some(2))
+                .build(),
+        )
+        .build();
+
+    let result = engine
+        .execute(
+            pipeline,
+            json!({ "people": [{ "name": "ada" }, { "name": "alan" }] }),
+        )
+        .await
+        .unwrap();
+    let greetings: Vec<Value> = result.steps[0]
+        .output
+        .as_ref()
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|child| child["result"]["greeting"].clone())
+        .collect();
+    assert_eq!(greetings, [json!("hello ada"), json!("hello alan")]);
+}
+
+#[tokio::test]
+async fn failed_items_fail_the_step() {
+    let engine = Engine::default()
+        .with_action(Double::default())
+        .with_action(Echo);
+    let pipeline = Pipeline::builder("batch")
+        .step(
+            PipelineStep::builder("double", "test/double")
+                .params(json!({ "n": "$.item" }))
+                .for_each("$.payload.records", None)
+                .on_error("report")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("report", "test/echo")
+                .params(json!({ "done": "$.double.output" }))
+                .build(),
+        )
+        .build();
+
+    let result = engine
+        .execute(pipeline.clone(), json!({ "records": [1, 13, 2] }))
+        .await
+        .unwrap();
+    let step = &result.steps[0];
+    assert_eq!(step.status, ExecutionStatus::Failed);
+    assert!(
+        step.message
+            .as_ref()
+            .unwrap()
+            .contains("Item 1 failed: Action failed: Action error: unlucky record"),
+        "{:?}",
+        step.message
+    );
+    // Outputs of the items before the failure are kept
+    assert_eq!(result.result.unwrap()["done"], json!([2]));
+
+    let result = engine
+        .execute(pipeline, json!({ "records": "not a list" }))
+        .await
+        .unwrap();
+    assert!(result.steps[0]
+        .message
+        .as_ref()
+        .unwrap()
+        .contains("did not select an array"));
+}
+
+#[tokio::test]
+async fn repeats_while_the_condition_holds() {
+    let poll = Poll::default();
+    let calls = poll.calls.clone();
+    let engine = Engine::default().with_action(poll);
+    let pipeline = Pipeline::builder("wait")
+        .step(
+            PipelineStep::builder("poll", "test/poll")
+                .repeat_while("$.poll.output.status != 'done'".parse().unwrap(), 10)
+                .build(),
+        )
+        .build();
+
+    let result = engine.execute(pipeline, json!({})).await.unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    let statuses: Vec<Value> = result
+        .result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["status"].clone())
+        .collect();
+    assert_eq!(
+        statuses,
+        [json!("pending"), json!("pending"), json!("done")]
+    );
+}
+
+#[tokio::test]
+async fn while_loops_fail_past_max_iterations() {
+    let poll = Poll::default();
+    let calls = poll.calls.clone();
+    let engine = Engine::default().with_action(poll);
+    let pipeline = Pipeline::builder("spin")
+        .step(
+            PipelineStep::builder("poll", "test/poll")
+                .repeat_while("$.index < 1000".parse().unwrap(), 5)
+                .build(),
+        )
+        .build();
+
+    let err = engine.execute(pipeline, json!({})).await.unwrap_err();
+    assert!(err.to_string().contains("max_iterations (5)"), "{err}");
+    assert_eq!(calls.load(Ordering::SeqCst), 5);
+}
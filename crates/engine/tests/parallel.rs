@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    pipeline::pipeline::JoinMode,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+    },
+};
+use ryvus_engine::Engine;
+use serde_json::json;
+
+/// Sleeps for `delay_ms` (from params) and echoes the step it ran for.
+#[derive(Clone)]
+struct SlowAction;
+
+#[async_trait]
+impl Action for SlowAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let input = ctx.input.clone().unwrap_or_default();
+        let delay = input.get("delay_ms").and_then(|v| v.as_u64()).unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+
+        if input.get("fail").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Err(Error::action(format!("{} failed", ctx.id)));
+        }
+        Ok(ActionResult::success(json!({ "step": ctx.id })))
+    }
+
+    fn key(&self) -> &str {
+        "test/slow"
+    }
+}
+
+fn branch(key: &str, delay_ms: u64, fail: bool) -> PipelineStep {
+    PipelineStep::builder(key, "test/slow")
+        .params(json!({ "delay_ms": delay_ms, "fail": fail }))
+        .next("join")
+        .build()
+}
+
+fn fan_out(wait: JoinMode, branches: Vec<PipelineStep>) -> Pipeline {
+    let keys: Vec<String> = branches.iter().map(|b| b.key.clone()).collect();
+
+    let mut steps = vec![PipelineStep::builder("fork", "")
+        .parallel(keys, "join", wait)
+        .build()];
+    steps.extend(branches);
+    steps.push(PipelineStep::builder("join", "test/slow").build());
+
+    Pipeline::builder("parallel").steps(steps).build()
+}
+
+#[tokio::test]
+async fn branches_run_concurrently_and_join_waits_for_all() {
+    let engine = Engine::default().with_action(SlowAction);
+    let pipeline = fan_out(
+        JoinMode::All,
+        vec![
+            branch("a", 200, false),
+            branch("b", 200, false),
+            branch("c", 200, false),
+        ],
+    );
+
+    let started = Instant::now();
+    let result = engine.execute(pipeline, json!({})).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_millis(500));
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.steps.len(), 4);
+    assert_eq!(result.steps.last().unwrap().key, "join");
+}
+
+#[tokio::test]
+async fn any_join_continues_after_first_success() {
+    let engine = Engine::default().with_action(SlowAction);
+    let pipeline = fan_out(
+        JoinMode::Any,
+        vec![branch("fast", 10, false), branch("slow", 5_000, false)],
+    );
+
+    let started = Instant::now();
+    let result = engine.execute(pipeline, json!({})).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert!(result.steps.iter().all(|s| s.key != "slow"));
+}
+
+#[tokio::test]
+async fn count_join_fails_when_too_few_branches_succeed() {
+    let engine = Engine::default().with_action(SlowAction);
+    let pipeline = fan_out(
+        JoinMode::Count(2),
+        vec![
+            branch("a", 10, false),
+            branch("b", 10, true),
+            branch("c", 10, true),
+        ],
+    );
+
+    let result = engine.execute(pipeline, json!({})).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,144 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    pipeline::{pipeline::ExecutionMode, state::PipelineState},
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+        StateStore,
+    },
+    state::in_memory::InMemoryStateStore,
+};
+use ryvus_engine::Engine;
+use serde_json::json;
+
+/// Counts its calls and fails the first `fail_first` of them.
+#[derive(Clone)]
+struct CountingAction {
+    key: &'static str,
+    calls: Arc<AtomicU32>,
+    fail_first: u32,
+}
+
+impl CountingAction {
+    fn new(key: &'static str, fail_first: u32) -> Self {
+        Self {
+            key,
+            calls: Arc::new(AtomicU32::new(0)),
+            fail_first,
+        }
+    }
+}
+
+#[async_trait]
+impl Action for CountingAction {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call <= self.fail_first {
+            return Err(Error::Action(format!("{} call {call} failed", self.key)));
+        }
+        Ok(ActionResult::success(json!({ "from": self.key })))
+    }
+
+    fn key(&self) -> &str {
+        self.key
+    }
+}
+
+#[tokio::test]
+async fn resume_continues_after_last_successful_step() {
+    let first = CountingAction::new("test/first", 0);
+    let flaky = CountingAction::new("test/flaky", 1);
+    let (first_calls, flaky_calls) = (first.calls.clone(), flaky.calls.clone());
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = Engine::default()
+        .with_action(first)
+        .with_action(flaky)
+        .with_state_store(store.clone());
+
+    let pipeline = Pipeline::builder("resume")
+        .step(
+            PipelineStep::builder("first", "test/first")
+                .next("flaky")
+                .build(),
+        )
+        .step(PipelineStep::builder("flaky", "test/flaky").build())
+        .build();
+
+    let err = engine
+        .execute_run("run-1", pipeline, json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("test/flaky call 1 failed"));
+
+    let checkpoint = store.load_checkpoint("run-1").await.unwrap().unwrap();
+    assert_eq!(checkpoint.state, PipelineState::Failed);
+    assert_eq!(checkpoint.next_step.as_deref(), Some("flaky"));
+    assert!(store
+        .load_steps("run-1")
+        .await
+        .unwrap()
+        .contains_key("first"));
+
+    let result = engine.resume("run-1").await.unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.run_id, "run-1");
+    assert_eq!(result.result, Some(json!({ "from": "test/flaky" })));
+    assert_eq!(first_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(flaky_calls.load(Ordering::SeqCst), 2);
+
+    let stored = store.load_result("run-1").await.unwrap().unwrap();
+    assert_eq!(stored.status, ExecutionStatus::Success);
+
+    // A completed run is not executed again
+    engine.resume("run-1").await.unwrap();
+    assert_eq!(flaky_calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn resume_skips_succeeded_dag_steps() {
+    let first = CountingAction::new("test/first", 0);
+    let flaky = CountingAction::new("test/flaky", 1);
+    let (first_calls, flaky_calls) = (first.calls.clone(), flaky.calls.clone());
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = Engine::default()
+        .with_action(first)
+        .with_action(flaky)
+        .with_state_store(store.clone());
+
+    let pipeline = Pipeline::builder("resume-dag")
+        .mode(ExecutionMode::Dag)
+        .step(PipelineStep::builder("first", "test/first").build())
+        .step(
+            PipelineStep::builder("flaky", "test/flaky")
+                .depends_on("first")
+                .build(),
+        )
+        .build();
+
+    assert!(engine
+        .execute_run("dag-1", pipeline, json!({}))
+        .await
+        .is_err());
+    let failed = store.load_result("dag-1").await.unwrap().unwrap();
+    assert_eq!(failed.status, ExecutionStatus::Failed);
+
+    let result = engine.resume("dag-1").await.unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(first_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(flaky_calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn resume_requires_a_checkpoint() {
+    let engine = Engine::default();
+    assert!(engine.resume("missing").await.is_err());
+
+    let engine = engine.with_state_store(Arc::new(InMemoryStateStore::default()));
+    let err = engine.resume("missing").await.unwrap_err();
+    assert!(err.to_string().contains("No checkpoint for run 'missing'"));
+}
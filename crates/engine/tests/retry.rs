@@ -0,0 +1,154 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::{Error, ErrorKind},
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+        RetryPolicy,
+    },
+};
+use ryvus_engine::{prelude::RetryExt, Engine};
+use serde_json::json;
+
+/// Fails with the configured error until `succeed_after` calls have been made.
+#[derive(Clone)]
+struct FlakyAction {
+    calls: Arc<AtomicU32>,
+    succeed_after: u32,
+    error: fn(String) -> Error,
+}
+
+impl FlakyAction {
+    fn new(succeed_after: u32, error: fn(String) -> Error) -> Self {
+        Self {
+            calls: Arc::new(AtomicU32::new(0)),
+            succeed_after,
+            error,
+        }
+    }
+}
+
+#[async_trait]
+impl Action for FlakyAction {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call <= self.succeed_after {
+            return Err((self.error)(format!("call {call} failed")));
+        }
+        Ok(ActionResult::success(json!({ "calls": call })))
+    }
+
+    fn key(&self) -> &str {
+        "test/flaky"
+    }
+}
+
+fn pipeline(policy: RetryPolicy) -> Pipeline {
+    Pipeline::builder("retry")
+        .step(
+            PipelineStep::builder("flaky", "test/flaky")
+                .retry(policy)
+                .build(),
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn retries_until_success_and_records_attempts() {
+    let action = FlakyAction::new(2, Error::Action);
+    let calls = action.calls.clone();
+    let engine = Engine::default().with_action(action);
+
+    let policy = RetryPolicy::exponential(5, Duration::from_millis(5)).with_jitter();
+    let result = engine.execute(pipeline(policy), json!({})).await.unwrap();
+
+    let step = &result.steps[0];
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(step.attempts.len(), 3);
+    assert_eq!(step.attempts[0].status, ExecutionStatus::Failed);
+    assert_eq!(step.attempts[2].status, ExecutionStatus::Success);
+}
+
+#[tokio::test]
+async fn only_retries_selected_error_kinds() {
+    let action = FlakyAction::new(2, Error::NotFound);
+    let calls = action.calls.clone();
+    let engine = Engine::default().with_action(action);
+
+    let policy = RetryPolicy::fixed(5, Duration::ZERO).retry_on(ErrorKind::Action);
+    let result = engine.execute(pipeline(policy), json!({})).await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+/// Reports a failed result on every call.
+#[derive(Clone, Default)]
+struct FailingAction {
+    calls: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Action for FailingAction {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(ActionResult::failed("still failing"))
+    }
+
+    fn key(&self) -> &str {
+        "test/flaky"
+    }
+}
+
+#[tokio::test]
+async fn retryable_actions_retry_failed_results_and_keep_attempts() {
+    let action = FailingAction::default();
+    let calls = action.calls.clone();
+
+    let mut ctx = ActionContext::new("flaky", json!({}));
+    let result = action.retryable(2).execute(&mut ctx).await.unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Failed);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(result.attempts.len(), 3);
+}
+
+#[tokio::test]
+async fn retryable_actions_keep_attempts_when_retries_run_out() {
+    let action = FlakyAction::new(5, Error::Action);
+    let calls = action.calls.clone();
+
+    let mut ctx = ActionContext::new("flaky", json!({}));
+    let result = action.retryable(1).execute(&mut ctx).await.unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Failed);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(result.attempts.len(), 2);
+    assert_eq!(
+        result.attempts[1].message.as_deref(),
+        Some("Action error: call 2 failed")
+    );
+}
+
+#[test]
+fn exponential_backoff_is_capped() {
+    let policy = RetryPolicy::exponential(10, Duration::from_millis(100))
+        .with_max_delay(Duration::from_millis(500))
+        .with_max_elapsed(Duration::from_secs(1));
+
+    let delay = |attempt, elapsed| policy.next_delay(attempt, ErrorKind::Action, elapsed);
+
+    assert_eq!(delay(1, Duration::ZERO), Some(Duration::from_millis(100)));
+    assert_eq!(delay(3, Duration::ZERO), Some(Duration::from_millis(400)));
+    assert_eq!(delay(5, Duration::ZERO), Some(Duration::from_millis(500)));
+    assert_eq!(delay(2, Duration::from_millis(900)), None);
+    assert_eq!(delay(10, Duration::ZERO), None);
+}
@@ -0,0 +1,100 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+        StateStore,
+    },
+    state::in_memory::InMemoryStateStore,
+};
+use ryvus_engine::{error::EngineError, Engine};
+use serde_json::json;
+
+/// Counts its calls and always succeeds.
+#[derive(Clone, Default)]
+struct Count {
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Action for Count {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(ActionResult::success(json!({ "call": call })))
+    }
+
+    fn key(&self) -> &str {
+        "test/count"
+    }
+}
+
+fn ping_pong() -> Pipeline {
+    Pipeline::builder("ping-pong")
+        .step(
+            PipelineStep::builder("start", "test/count")
+                .next("ping")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("ping", "test/count")
+                .next("pong")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("pong", "test/count")
+                .next("ping")
+                .build(),
+        )
+        .max_visits_per_step(3)
+        .build()
+}
+
+#[tokio::test]
+async fn cycles_stop_at_the_visit_budget_with_a_trace() {
+    let count = Count::default();
+    let calls = count.calls.clone();
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = Engine::default()
+        .with_action(count)
+        .with_state_store(store.clone());
+
+    let err = engine
+        .execute_run("looping", ping_pong(), json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::Budget(_)), "{err}");
+    let message = err.to_string();
+    assert!(message.contains("step 'ping'"), "{message}");
+    assert!(
+        message.contains("start -> ping -> pong -> ping -> pong -> ping -> pong -> ping"),
+        "{message}"
+    );
+    // The fourth visit to `ping` is refused before it runs
+    assert_eq!(calls.load(Ordering::SeqCst), 7);
+
+    let result = store.load_result("looping").await.unwrap().unwrap();
+    assert_eq!(result.status, ExecutionStatus::Failed);
+    assert!(result.error.unwrap().contains("max_visits_per_step"));
+}
+
+#[tokio::test]
+async fn long_runs_stop_at_the_step_budget() {
+    let engine = Engine::default().with_action(Count::default());
+    let mut pipeline = ping_pong();
+    pipeline.max_visits_per_step = None;
+    pipeline.max_steps = Some(30);
+
+    let err = engine.execute(pipeline, json!({})).await.unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("more than 30 steps"), "{message}");
+    // Only the most recent steps are listed
+    assert!(
+        message.contains("(11 earlier steps) -> ping -> pong"),
+        "{message}"
+    );
+}
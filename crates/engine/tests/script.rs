@@ -0,0 +1,110 @@
+#![cfg(feature = "script")]
+
+use ryvus_core::prelude::{pipeline::Pipeline, ExecutionStatus, PipelineStep};
+use ryvus_engine::{actions::ScriptAction, Engine};
+use serde_json::{json, Value};
+
+fn script_step(key: &str, config: Value) -> PipelineStep {
+    PipelineStep::builder(key, "ryvus/script")
+        .config(config)
+        .build()
+}
+
+#[tokio::test]
+async fn transforms_input_and_previous_outputs() {
+    let engine = Engine::default().with_action(ScriptAction::new());
+    let pipeline = Pipeline::builder("script")
+        .step(
+            PipelineStep::builder("totals", "ryvus/script")
+                .config(json!({
+                    "script": "#{ total: input.items.reduce(|sum, i| sum + i.price, 0), count: input.items.len() }"
+                }))
+                .next("label")
+                .build(),
+        )
+        .step(script_step(
+            "label",
+            json!({
+                "script": r#"
+                    let totals = context.totals.output;
+                    #{ customer: context.payload.customer, summary: `${totals.count} items, ${totals.total} EUR` }
+                "#
+            }),
+        ))
+        .build();
+
+    let input = json!({ "customer": "ada", "items": [{ "price": 3 }, { "price": 4 }] });
+    let result = engine.execute(pipeline, input).await.unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(
+        result.steps[0].output,
+        Some(json!({ "total": 7, "count": 2 }))
+    );
+    assert_eq!(
+        result.result,
+        Some(json!({ "customer": "ada", "summary": "2 items, 7 EUR" }))
+    );
+}
+
+#[tokio::test]
+async fn enforces_operation_and_size_limits() {
+    let engine = Engine::default().with_action(ScriptAction::new());
+    let run = |config: Value| {
+        let engine = &engine;
+        async move {
+            let pipeline = Pipeline::builder("limits")
+                .step(script_step("run", config))
+                .build();
+            engine.execute(pipeline, json!({})).await
+        }
+    };
+
+    let err = run(json!({ "script": "loop {}", "max_operations": 1000 }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Too many operations"), "{err}");
+
+    let err = run(json!({
+        "script": "let a = []; for i in 0..100 { a.push(i) } a",
+        "max_array_size": 10
+    }))
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("Size of array"), "{err}");
+
+    let err = run(json!({ "script": "let s = \"x\"; loop { s += s }", "max_string_size": 64 }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Length of string"), "{err}");
+}
+
+#[tokio::test]
+async fn rejects_invalid_scripts_and_sandbox_escapes() {
+    let engine = Engine::default().with_action(ScriptAction::new());
+
+    for (config, message) in [
+        (json!({ "script": "let = 1" }), "does not compile"),
+        (json!({ "script": "eval(\"1\")" }), "does not compile"),
+        (json!({ "scrpt": "1" }), "unknown field"),
+    ] {
+        let pipeline = Pipeline::builder("invalid")
+            .step(script_step("run", config))
+            .build();
+        // Configuration errors fail the run before the step starts
+        let result = engine.execute(pipeline, json!({})).await.unwrap();
+        assert_eq!(result.status, ExecutionStatus::Failed);
+        let error = result.error.unwrap_or_default();
+        assert!(error.contains(message), "{error}");
+    }
+
+    // Imports are resolved at run time and nothing can be loaded
+    let pipeline = Pipeline::builder("import")
+        .step(script_step(
+            "run",
+            json!({ "script": "import \"fs\" as fs; 1" }),
+        ))
+        .build();
+    let err = engine.execute(pipeline, json!({})).await.unwrap_err();
+    assert!(err.to_string().contains("Module not found"), "{err}");
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use common::Echo;
+use ryvus_core::{
+    prelude::{pipeline::Pipeline, ExecutionStatus, PipelineState, PipelineStep, StateStore},
+    state::in_memory::InMemoryStateStore,
+};
+use ryvus_engine::{error::EngineError, Engine};
+use serde_json::json;
+
+fn deployment(timeout: Duration) -> Pipeline {
+    Pipeline::builder("deploy")
+        .step(
+            PipelineStep::builder("build", "test/echo")
+                .params(json!({ "artifact": "app-1.2" }))
+                .next("approve")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("approve", "")
+                .wait_for("approval", Some(timeout))
+                .next("release")
+                .otherwise("abort")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("release", "test/echo")
+                .params(json!({
+                    "artifact": "$.build.output.artifact",
+                    "approved_by": "$.approve.output.approved_by"
+                }))
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("abort", "test/echo")
+                .params(json!({ "aborted": true }))
+                .build(),
+        )
+        .build()
+}
+
+fn engine(store: Arc<InMemoryStateStore>) -> Engine {
+    Engine::default().with_action(Echo).with_state_store(store)
+}
+
+#[tokio::test]
+async fn suspends_until_the_signal_arrives() {
+    let store = Arc::new(InMemoryStateStore::default());
+    let result = engine(store.clone())
+        .execute_run("deploy-1", deployment(Duration::from_secs(3600)), json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Suspended);
+    assert_eq!(result.steps.len(), 1);
+
+    let checkpoint = store.load_checkpoint("deploy-1").await.unwrap().unwrap();
+    assert_eq!(checkpoint.state, PipelineState::Suspended);
+    assert_eq!(checkpoint.next_step.as_deref(), Some("approve"));
+
+    // A fresh engine over the same store picks the run up, as after a restart
+    let engine = engine(store);
+    let err = engine
+        .signal("deploy-1", "rollback", json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::State(_)), "{err}");
+
+    let result = engine
+        .signal("deploy-1", "approval", json!({ "approved_by": "ada" }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(
+        result.result,
+        Some(json!({ "artifact": "app-1.2", "approved_by": "ada" }))
+    );
+    let keys: Vec<_> = result.steps.iter().map(|s| s.key.as_str()).collect();
+    assert_eq!(keys, ["build", "approve", "release"]);
+
+    // The signal was consumed with the wait
+    assert!(engine
+        .signal("deploy-1", "approval", json!({}))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn timed_out_waits_take_otherwise_when_woken() {
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = engine(store);
+    engine
+        .execute_run("short", deployment(Duration::from_millis(20)), json!({}))
+        .await
+        .unwrap();
+    engine
+        .execute_run("long", deployment(Duration::from_secs(3600)), json!({}))
+        .await
+        .unwrap();
+
+    assert!(engine.wake_due().await.unwrap().is_empty());
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    let woken = engine.wake_due().await.unwrap();
+    assert_eq!(woken.len(), 1);
+    let result = &woken[0];
+    assert_eq!(result.run_id, "short");
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result, Some(json!({ "aborted": true })));
+    assert_eq!(result.steps[1].status, ExecutionStatus::Timeout);
+}
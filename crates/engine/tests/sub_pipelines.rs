@@ -0,0 +1,249 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use common::Echo;
+use ryvus_core::{
+    action::result::ExecutionResult,
+    error::Error,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+        StateStore,
+    },
+    state::in_memory::InMemoryStateStore,
+};
+use ryvus_engine::{cancellation::CancellationListener, error::EngineError, Engine};
+use serde_json::json;
+
+#[derive(Clone)]
+struct Fail;
+
+#[async_trait]
+impl Action for Fail {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Err(Error::Action("upstream rejected the token".into()))
+    }
+
+    fn key(&self) -> &str {
+        "test/fail"
+    }
+}
+
+/// Waits long enough to be canceled and counts the calls that got through.
+#[derive(Clone)]
+struct Slow {
+    finished: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Action for Slow {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        self.finished.fetch_add(1, Ordering::SeqCst);
+        Ok(ActionResult::success(json!({})))
+    }
+
+    fn key(&self) -> &str {
+        "test/slow"
+    }
+}
+
+fn authenticate() -> Pipeline {
+    Pipeline::builder("authenticate")
+        .step(
+            PipelineStep::builder("login", "test/echo")
+                .params(json!({ "token": "t-${$.payload.user}" }))
+                .next("fetch")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("fetch", "test/echo")
+                .params(json!({ "token": "$.login.output.token", "user": "$.payload.user" }))
+                .build(),
+        )
+        .build()
+}
+
+#[tokio::test]
+async fn runs_child_with_mapped_input_and_links_runs() {
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = Engine::default()
+        .with_action(Echo)
+        .with_pipeline(authenticate())
+        .with_state_store(store.clone());
+
+    let parent = Pipeline::builder("orders")
+        .step(
+            PipelineStep::builder("auth", "")
+                .pipeline(
+                    "authenticate",
+                    Some(json!({ "user": "$.payload.customer" })),
+                )
+                .next("use")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("use", "test/echo")
+                .params(json!({ "token": "$.auth.output.result.token" }))
+                .build(),
+        )
+        .build();
+
+    let result = engine
+        .execute(parent, json!({ "customer": "ada" }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result.as_ref().unwrap()["token"], json!("t-ada"));
+
+    // The step output is the child's ExecutionResult
+    let child: ExecutionResult =
+        serde_json::from_value(result.steps[0].output.clone().unwrap()).unwrap();
+    assert_eq!(child.pipeline_key.as_deref(), Some("authenticate"));
+    assert_eq!(child.parent_run_id.as_deref(), Some(result.run_id.as_str()));
+    assert_eq!(child.steps.len(), 2);
+    assert_ne!(child.run_id, result.run_id);
+
+    let stored = store.load_result(&child.run_id).await.unwrap().unwrap();
+    assert_eq!(stored.parent_run_id, Some(result.run_id.clone()));
+}
+
+#[tokio::test]
+async fn failed_child_fails_the_step_and_routes_to_on_error() {
+    let engine = Engine::default()
+        .with_action(Echo)
+        .with_action(Fail)
+        .with_pipeline(
+            Pipeline::builder("authenticate")
+                .step(PipelineStep::builder("login", "test/fail").build())
+                .build(),
+        );
+
+    let parent = Pipeline::builder("orders")
+        .step(
+            PipelineStep::builder("auth", "")
+                .pipeline("authenticate", None)
+                .on_error("fallback")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("fallback", "test/echo")
+                .params(json!({ "anonymous": true }))
+                .build(),
+        )
+        .build();
+
+    let result = engine.execute(parent, json!({})).await.unwrap();
+    let step = &result.steps[0];
+    assert_eq!(step.status, ExecutionStatus::Failed);
+    let message = step.message.clone().unwrap();
+    assert!(message.contains("Pipeline 'authenticate'"), "{message}");
+    assert!(message.contains("upstream rejected the token"), "{message}");
+    assert_eq!(result.result.unwrap()["anonymous"], json!(true));
+
+    // Unknown pipelines stop the run like unknown actions
+    let missing = Pipeline::builder("missing")
+        .step(
+            PipelineStep::builder("call", "")
+                .pipeline("nope", None)
+                .build(),
+        )
+        .build();
+    let result = engine.execute(missing, json!({})).await.unwrap();
+    assert_eq!(result.status, ExecutionStatus::Failed);
+    assert!(result.error.unwrap().contains("Pipeline 'nope' not found"));
+}
+
+#[tokio::test]
+async fn canceling_the_parent_cancels_the_child() {
+    let finished = Arc::new(AtomicU32::new(0));
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = Engine::default()
+        .with_action(Echo)
+        .with_action(Slow {
+            finished: finished.clone(),
+        })
+        .with_cancel_listener(CancellationListener::new())
+        .with_state_store(store.clone())
+        .with_pipeline(
+            Pipeline::builder("slow")
+                .step(
+                    PipelineStep::builder("wait", "test/slow")
+                        .next("after")
+                        .build(),
+                )
+                .step(PipelineStep::builder("after", "test/echo").build())
+                .build(),
+        );
+
+    let parent = Pipeline::builder("parent")
+        .step(
+            PipelineStep::builder("call", "")
+                .pipeline("slow", None)
+                .next("done")
+                .build(),
+        )
+        .step(PipelineStep::builder("done", "test/echo").build())
+        .build();
+
+    let token = engine.cancel_token().unwrap();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        token.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let err = engine
+        .execute_run("parent-run", parent, json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::Canceled), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+
+    let parent = store.load_checkpoint("parent-run").await.unwrap().unwrap();
+    let call = &parent.context.steps[0];
+    assert_eq!(call.status, ExecutionStatus::Canceled);
+    let child: ExecutionResult = serde_json::from_value(call.output.clone().unwrap()).unwrap();
+    assert_eq!(child.status, ExecutionStatus::Canceled);
+    assert_eq!(child.parent_run_id.as_deref(), Some("parent-run"));
+}
+
+#[tokio::test]
+async fn recursive_pipelines_stop_at_the_depth_limit() {
+    let engine = Engine::default().with_pipeline(
+        Pipeline::builder("loop")
+            .step(
+                PipelineStep::builder("again", "")
+                    .pipeline("loop", None)
+                    .build(),
+            )
+            .build(),
+    );
+
+    // Each level fails its parent step, up to the top-level run
+    let err = engine
+        .execute(
+            Pipeline::builder("start")
+                .step(
+                    PipelineStep::builder("call", "")
+                        .pipeline("loop", None)
+                        .build(),
+                )
+                .build(),
+            json!({}),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("exceeds 16 nested sub-pipelines"),
+        "{err}"
+    );
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::Echo;
+use ryvus_core::{
+    pipeline::template::{Lookup, Template},
+    prelude::{pipeline::Pipeline, PipelineStep},
+};
+use ryvus_engine::Engine;
+use serde_json::{json, Value};
+
+fn render(text: &str, doc: &Value) -> Value {
+    Template::parse(text)
+        .unwrap()
+        .render(&mut |subject| match subject.strip_prefix("$.") {
+            Some(key) => doc.get(key).cloned().map_or(Lookup::Missing, Lookup::Found),
+            None => Lookup::Defer,
+        })
+}
+
+#[test]
+fn interpolates_and_applies_filters() {
+    let doc = json!({ "id": 42, "name": "Ada Lovelace", "tags": ["a", "b"], "none": null });
+
+    assert_eq!(render("/users/${$.id}", &doc), json!("/users/42"));
+    assert_eq!(render("${ $.name | upper }", &doc), json!("ADA LOVELACE"));
+    assert_eq!(render("${$.name | lower}", &doc), json!("ada lovelace"));
+    assert_eq!(
+        render("q=${$.name | urlencode}", &doc),
+        json!("q=Ada%20Lovelace")
+    );
+    assert_eq!(
+        render("${$.name | base64}", &doc),
+        json!("QWRhIExvdmVsYWNl")
+    );
+    assert_eq!(
+        render("tags=${$.tags | json}", &doc),
+        json!(r#"tags=["a","b"]"#)
+    );
+    assert_eq!(render("tags=${$.tags}", &doc), json!(r#"tags=["a","b"]"#));
+    assert_eq!(
+        render("hi ${$.missing | default('you') | upper}", &doc),
+        json!("hi YOU")
+    );
+    assert_eq!(render("[${$.none}]", &doc), json!("[]"));
+}
+
+#[test]
+fn keeps_types_escapes_and_deferred_placeholders() {
+    let doc = json!({ "id": 42, "tags": ["a"] });
+
+    // A lone placeholder keeps the JSON type
+    assert_eq!(render("${$.id}", &doc), json!(42));
+    assert_eq!(render("${$.tags}", &doc), json!(["a"]));
+    assert_eq!(render("${$.missing | default(0)}", &doc), json!(0));
+
+    assert_eq!(render("cost: $${literal}", &doc), json!("cost: ${literal}"));
+    assert_eq!(
+        render("${env.HOST | upper}/${$.id}", &doc),
+        json!("${env.HOST | upper}/42")
+    );
+    assert_eq!(render("${env.HOST}", &doc), json!("${env.HOST}"));
+}
+
+#[test]
+fn reports_malformed_templates() {
+    for (text, message) in [
+        ("x ${$.a", "unterminated"),
+        ("${}", "empty placeholder"),
+        ("${$.a | shout}", "unknown filter 'shout'"),
+        ("${$.a | default}", "exactly one value"),
+        ("${$.a | upper(1)}", "takes no arguments"),
+        ("${$.a | default(anon)}", "quote strings"),
+    ] {
+        let err = Template::parse(text).unwrap_err();
+        assert!(err.message.contains(message), "{text}: {err}");
+    }
+
+    // Brackets and quotes may contain braces and pipes
+    let template = Template::parse("${$.items[?(@.a || @.b)] | default('}')}").unwrap();
+    let subjects: Vec<_> = template
+        .placeholders()
+        .map(|p| p.subject.as_str())
+        .collect();
+    assert_eq!(subjects, ["$.items[?(@.a || @.b)]"]);
+}
+
+#[tokio::test]
+async fn resolves_templates_in_params_from_the_run() {
+    let engine = Engine::default().with_action(Echo);
+    let pipeline = Pipeline::builder("templates")
+        .step(
+            PipelineStep::builder("first", "test/echo")
+                .params(json!({ "greeting": "hello" }))
+                .next("second")
+                .build(),
+        )
+        .step(
+            PipelineStep::builder("second", "test/echo")
+                .params(json!({
+                    "url": "https://api.test/users/${$.payload.id}?q=${$.payload.name | urlencode}",
+                    "message": "${$.first.output.greeting | upper}, ${$.payload.name}!",
+                    "id": "${$.payload.id}"
+                }))
+                .build(),
+        )
+        .build();
+
+    let result = engine
+        .execute(pipeline, json!({ "id": 7, "name": "Ada L" }))
+        .await
+        .unwrap();
+    let output = result.result.unwrap();
+
+    assert_eq!(output["url"], json!("https://api.test/users/7?q=Ada%20L"));
+    assert_eq!(output["message"], json!("HELLO, Ada L!"));
+    assert_eq!(output["id"], json!(7));
+}
@@ -0,0 +1,140 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use ryvus_core::{
+    error::Error,
+    pipeline::hook::ActionHook,
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, ExecutionStatus, PipelineStep,
+        RetryPolicy,
+    },
+};
+use ryvus_engine::{engine::EngineApi, Engine};
+use serde_json::json;
+
+/// Sleeps for `delay_ms` from params before succeeding.
+#[derive(Clone)]
+struct SleepAction;
+
+#[async_trait]
+impl Action for SleepAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let delay = ctx
+            .input
+            .as_ref()
+            .and_then(|i| i.get("delay_ms"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        Ok(ActionResult::success(json!({ "slept": delay })))
+    }
+
+    fn key(&self) -> &str {
+        "test/sleep"
+    }
+}
+
+/// Collects errors passed to the `error` hook.
+#[derive(Clone, Default)]
+struct ErrorRecorder {
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl ActionHook for ErrorRecorder {
+    async fn before(&self, _context: &mut ActionContext) {}
+    async fn after(&self, _context: &mut ActionContext) {}
+    async fn error(&self, _context: &mut ActionContext, err: &Error) {
+        self.errors.lock().unwrap().push(format!("{err:?}"));
+    }
+}
+
+fn sleep_step(key: &str, delay_ms: u64) -> PipelineStep {
+    PipelineStep::builder(key, "test/sleep")
+        .params(json!({ "delay_ms": delay_ms }))
+        .build()
+}
+
+#[tokio::test]
+async fn step_timeout_routes_to_on_error_and_fires_error_hook() {
+    let recorder = ErrorRecorder::default();
+    let engine = Engine::default()
+        .with_action(SleepAction)
+        .with_action_hook(Arc::new(recorder.clone()));
+
+    let mut hung = sleep_step("hung", 10_000);
+    hung.timeout_ms = Some(50);
+    hung.on_error = Some("fallback".into());
+
+    let pipeline = Pipeline::builder("timeouts")
+        .steps(vec![hung, sleep_step("fallback", 0)])
+        .build();
+
+    let started = Instant::now();
+    let result = engine.execute(pipeline, json!({})).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(result.steps[0].status, ExecutionStatus::Timeout);
+    assert_eq!(result.steps[1].key, "fallback");
+    assert!(recorder.errors.lock().unwrap()[0].starts_with("Timeout("));
+}
+
+#[tokio::test]
+async fn pipeline_deadline_produces_timeout_status() {
+    let engine = Engine::default()
+        .with_action(SleepAction)
+        .with_pipeline_timeout(Duration::from_millis(100));
+
+    let mut first = sleep_step("first", 60);
+    first.next = Some("second".into());
+    let pipeline = Pipeline::builder("deadline")
+        .steps(vec![first, sleep_step("second", 10_000)])
+        .build();
+
+    let result = engine.execute_pipeline(pipeline, json!({})).await.unwrap();
+    assert_eq!(result.status, ExecutionStatus::Timeout);
+}
+
+#[tokio::test]
+async fn pipeline_deadline_ends_the_run_without_following_on_error() {
+    let recorder = ErrorRecorder::default();
+    let engine = Engine::default()
+        .with_action(SleepAction)
+        .with_action_hook(Arc::new(recorder.clone()))
+        .with_pipeline_timeout(Duration::from_millis(50));
+
+    let mut hung = sleep_step("hung", 10_000);
+    hung.on_error = Some("fallback".into());
+    let pipeline = Pipeline::builder("deadline_on_error")
+        .steps(vec![hung, sleep_step("fallback", 0)])
+        .build();
+
+    let result = engine.execute_pipeline(pipeline, json!({})).await.unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Timeout);
+    assert!(result.steps.iter().all(|s| s.key != "fallback"));
+    assert!(recorder.errors.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn pipeline_deadline_cuts_retry_backoff_short() {
+    let engine = Engine::default()
+        .with_action(SleepAction)
+        .with_pipeline_timeout(Duration::from_millis(100));
+
+    let mut hung = sleep_step("hung", 10_000);
+    hung.timeout_ms = Some(20);
+    hung.retry = Some(RetryPolicy::fixed(3, Duration::from_secs(30)));
+    let pipeline = Pipeline::builder("deadline_backoff")
+        .steps(vec![hung])
+        .build();
+
+    let started = Instant::now();
+    let result = engine.execute_pipeline(pipeline, json!({})).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(result.status, ExecutionStatus::Timeout);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use common::Echo;
+use ryvus_core::{
+    prelude::{pipeline::Pipeline, ExecutionStatus, PipelineState, PipelineStep, StateStore},
+    state::in_memory::InMemoryStateStore,
+};
+use ryvus_engine::Engine;
+use serde_json::json;
+
+/// Signs a user up, pauses at `pause`, then sends a reminder.
+fn reminder(pause: PipelineStep) -> Pipeline {
+    Pipeline::builder("reminder")
+        .step(
+            PipelineStep::builder("signup", "test/echo")
+                .params(json!({ "user": "ada" }))
+                .next("pause")
+                .build(),
+        )
+        .step(pause)
+        .step(
+            PipelineStep::builder("remind", "test/echo")
+                .params(json!({ "user": "$.signup.output.user" }))
+                .build(),
+        )
+        .build()
+}
+
+fn sleep(duration: Duration) -> PipelineStep {
+    PipelineStep::builder("pause", "")
+        .sleep(duration)
+        .next("remind")
+        .build()
+}
+
+#[tokio::test]
+async fn short_delays_sleep_inline() {
+    let result = Engine::default()
+        .with_action(Echo)
+        .execute(reminder(sleep(Duration::from_millis(30))), json!({}))
+        .await
+        .unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result, Some(json!({ "user": "ada" })));
+    let pause = &result.steps[1];
+    assert_eq!(pause.key, "pause");
+    assert!(pause.duration_ms.unwrap() >= 30);
+    assert!(pause.output.as_ref().unwrap()["until"].is_string());
+}
+
+#[tokio::test]
+async fn long_delays_suspend_on_a_durable_timer() {
+    let store = Arc::new(InMemoryStateStore::default());
+    let engine = |store: Arc<InMemoryStateStore>| {
+        Engine::default()
+            .with_action(Echo)
+            .with_state_store(store)
+            .with_durable_timer_threshold(Duration::from_millis(50))
+    };
+
+    let result = engine(store.clone())
+        .execute_run(
+            "remind-1",
+            reminder(sleep(Duration::from_millis(80))),
+            json!({}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Suspended);
+    let checkpoint = store.load_checkpoint("remind-1").await.unwrap().unwrap();
+    assert_eq!(checkpoint.state, PipelineState::Suspended);
+    assert_eq!(checkpoint.next_step.as_deref(), Some("pause"));
+    assert!(checkpoint.context.sleeping_until.is_some());
+
+    // A fresh engine over the same store wakes the run, as after a restart
+    let engine = engine(store.clone());
+    assert!(engine.wake_due().await.unwrap().is_empty());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let woken = engine.wake_due().await.unwrap();
+    assert_eq!(woken.len(), 1);
+    assert_eq!(woken[0].status, ExecutionStatus::Success);
+    assert_eq!(woken[0].result, Some(json!({ "user": "ada" })));
+    let keys: Vec<_> = woken[0].steps.iter().map(|s| s.key.as_str()).collect();
+    assert_eq!(keys, ["signup", "pause", "remind"]);
+
+    assert!(store.due_timers(Utc::now()).await.unwrap().is_empty());
+    assert!(engine.wake_due().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn wait_until_reads_the_timestamp_from_the_run() {
+    let pause = PipelineStep::builder("pause", "")
+        .wait_until("$.payload.remind_at")
+        .next("remind")
+        .build();
+    let engine = Engine::default().with_action(Echo);
+
+    let remind_at = Utc::now() + chrono::Duration::milliseconds(20);
+    let result = engine
+        .execute(
+            reminder(pause.clone()),
+            json!({ "remind_at": remind_at.to_rfc3339() }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert!(Utc::now() >= remind_at);
+
+    // Epoch milliseconds in the past do not wait at all
+    let result = engine
+        .execute(reminder(pause.clone()), json!({ "remind_at": 0 }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+
+    let err = engine
+        .execute(reminder(pause), json!({ "remind_at": "next tuesday" }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("is not a timestamp"), "{err}");
+}
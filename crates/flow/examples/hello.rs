@@ -1,19 +1,52 @@
-use ryvus_flow::prelude::*;
+use async_trait::async_trait;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error};
+use ryvus_engine::Engine;
+use ryvus_flow::{pipeline::manager::StepDefinition, prelude::*};
+use serde_json::json;
+
+#[derive(Clone)]
+struct HelloAction;
+
+#[async_trait]
+impl Action for HelloAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let name = ctx
+            .input
+            .as_ref()
+            .and_then(|i| i.get("name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("world");
+        Ok(ActionResult::success(
+            json!({ "greeting": format!("Hello, {name}!") }),
+        ))
+    }
+
+    fn key(&self) -> &str {
+        "example/hello"
+    }
+}
 
 #[tokio::main]
 async fn main() -> Result<(), FlowError> {
     let store = InMemoryStateStore::default();
-    let mut manager = FlowPipelineManager::new(store);
+    let engine = Engine::default().with_action(HelloAction);
+    let manager = FlowPipelineManager::new(store, engine);
 
     let pipeline = PipelineDefinition {
         key: "pipe1".to_string(),
         description: Some("Example pipeline".to_string()),
         version: Some("0.1.0".to_string()),
-        steps: Vec::new(),
+        steps: vec![StepDefinition {
+            key: "hello".to_string(),
+            action: "example/hello".to_string(),
+            ..Default::default()
+        }],
         pipeline_hooks: Vec::new(),
+        ..Default::default()
     };
 
     manager.register(pipeline);
-    manager.start("pipe1").await?;
+    let result = manager.start("pipe1", json!({ "name": "Ryvus" })).await?;
+    println!("{:?}", result.result);
     Ok(())
 }
@@ -1,22 +1,166 @@
-use crate::{error::FlowError, pipeline::PipelineDefinition, store::StateStore};
+use crate::{
+    context::sensative_masker::SensitiveMasker,
+    error::FlowError,
+    pipeline::{manager::PipelineRegistry, PipelineDefinition},
+    resolver::{
+        config_resolver::resolve_config, pipeline_resolver::RegisteredPipelines,
+        variable::VariableResolver,
+    },
+    store::StateStore,
+};
+use ryvus_core::{action::result::ExecutionResult, prelude::pipeline::Pipeline};
+use ryvus_engine::{engine::EngineApi, pipeline_resolver::PipelineResolver};
+use serde_json::{json, Value};
 use std::sync::Arc;
+use tracing::debug;
 
 /// FlowContext wraps execution and persistence concerns for a pipeline.
 pub struct FlowContext<S: StateStore> {
     pub pipeline: PipelineDefinition,
     pub store: Arc<S>,
+    pub engine: Arc<dyn EngineApi>,
+    pub resolver: Arc<dyn VariableResolver>,
+    /// Pipelines that sub-pipeline steps may call
+    pub pipelines: Option<PipelineRegistry>,
+    /// Id to run under; the engine generates one when unset
+    pub run_id: Option<String>,
 }
 
 impl<S: StateStore> FlowContext<S> {
-    pub fn new(pipeline: PipelineDefinition, store: Arc<S>) -> Self {
-        Self { pipeline, store }
+    pub fn new(
+        pipeline: PipelineDefinition,
+        store: Arc<S>,
+        engine: Arc<dyn EngineApi>,
+        resolver: Arc<dyn VariableResolver>,
+    ) -> Self {
+        Self {
+            pipeline,
+            store,
+            engine,
+            resolver,
+            pipelines: None,
+            run_id: None,
+        }
     }
 
-    pub async fn run(&self) -> Result<(), FlowError> {
-        // self.store.save_state(&self.pipeline.id, "started").await?;
-        // Placeholder for real engine orchestration
-        println!("Starting pipeline: {}", self.pipeline.key);
-        // self.store.save_state(&self.pipeline.id, "finished").await?;
-        Ok(())
+    /// Lets sub-pipeline steps call the pipelines in `registry`.
+    pub fn with_pipelines(mut self, registry: PipelineRegistry) -> Self {
+        self.pipelines = Some(registry);
+        self
+    }
+
+    /// Runs under `run_id` instead of a generated id.
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// Runs the pipeline on the engine, which resolves its variables through
+    /// the flow, and stores the outcome.
+    ///
+    /// The definition is stored with its placeholders intact, and so is the
+    /// pipeline the engine checkpoints; resolved secrets are masked in the
+    /// stored and the returned result.
+    pub async fn run(&self, input: Value) -> Result<ExecutionResult, FlowError> {
+        let definition = Pipeline::try_from(self.pipeline.clone()).map_err(FlowError::Loader)?;
+        self.store
+            .save_pipeline(&definition)
+            .await
+            .map_err(FlowError::Store)?;
+
+        let input = match input {
+            Value::Null => json!({}),
+            _ => input,
+        };
+
+        debug!("Starting pipeline: {}", self.pipeline.key);
+        let pipelines = self.registered_pipelines();
+        let result = self
+            .engine
+            .execute_pipeline_as(
+                self.run_id.clone(),
+                definition,
+                input,
+                Some(pipelines.clone()),
+            )
+            .await
+            .map_err(|e| FlowError::Engine(e.to_string()))?;
+
+        self.save_run(&result, pipelines.as_ref()).await
+    }
+
+    /// Continues the run under `run_id` from the checkpoint the engine kept
+    /// for it and stores the outcome; `None` when there is no checkpoint.
+    pub async fn resume(&self) -> Result<Option<ExecutionResult>, FlowError> {
+        let Some(run_id) = &self.run_id else {
+            return Ok(None);
+        };
+        let pipelines = self.registered_pipelines();
+        let result = self
+            .engine
+            .resume_pipeline(run_id, Some(pipelines.clone()))
+            .await
+            .map_err(|e| FlowError::Engine(e.to_string()))?;
+        let Some(result) = result else {
+            return Ok(None);
+        };
+
+        debug!("Resumed pipeline: {}", self.pipeline.key);
+        self.save_run(&result, pipelines.as_ref()).await.map(Some)
+    }
+
+    /// Delivers signal `name` with `payload` to the run under `run_id`,
+    /// suspended at a `wait_for` step, and stores the outcome once the run
+    /// stops again.
+    pub async fn signal(&self, name: &str, payload: Value) -> Result<ExecutionResult, FlowError> {
+        let Some(run_id) = &self.run_id else {
+            return Err(FlowError::Engine("No run to signal".into()));
+        };
+        let pipelines = self.registered_pipelines();
+        let result = self
+            .engine
+            .signal_pipeline(run_id, name, payload, Some(pipelines.clone()))
+            .await
+            .map_err(|e| FlowError::Engine(e.to_string()))?;
+
+        debug!("Signaled pipeline: {}", self.pipeline.key);
+        self.save_run(&result, pipelines.as_ref()).await
+    }
+
+    /// Stores `result` of a run the engine executed for the flow, masking the
+    /// secrets of the definition and of the pipelines `pipelines` prepared.
+    pub(crate) async fn save_run(
+        &self,
+        result: &ExecutionResult,
+        pipelines: &dyn PipelineResolver,
+    ) -> Result<ExecutionResult, FlowError> {
+        let mut secrets = resolve_config(&mut self.pipeline.clone(), self.resolver.as_ref());
+        secrets.extend(pipelines.secrets());
+        self.save_result(result, secrets).await
+    }
+
+    /// Resolves variables for the engine and serves sub-pipeline steps from
+    /// `pipelines`, when set.
+    fn registered_pipelines(&self) -> Arc<dyn PipelineResolver> {
+        Arc::new(RegisteredPipelines::new(
+            self.pipelines.clone().unwrap_or_default(),
+            self.resolver.clone(),
+        ))
+    }
+
+    /// Stores `result` with the `secrets` masked and returns the masked copy.
+    async fn save_result(
+        &self,
+        result: &ExecutionResult,
+        secrets: Vec<String>,
+    ) -> Result<ExecutionResult, FlowError> {
+        let stored = SensitiveMasker::new(secrets)
+            .mask(result)
+            .map_err(|e| FlowError::Store(e.to_string()))?;
+        self.store
+            .save_result(&stored)
+            .await
+            .map_err(FlowError::Store)?;
+        Ok(stored)
     }
 }
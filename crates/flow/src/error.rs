@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::pipeline::{PipelineFormat, ValidationReport};
+
 #[derive(Debug, Error)]
 pub enum FlowError {
     #[error("Pipeline not found: {0}")]
@@ -13,4 +15,29 @@ pub enum FlowError {
 
     #[error("Loader error: {0}")]
     Loader(String),
+
+    #[error("Trigger error: {0}")]
+    Trigger(String),
+
+    #[error("Queue error: {0}")]
+    Queue(String),
+
+    #[error("Invalid pipeline: {0}")]
+    Invalid(ValidationReport),
+
+    #[error("{format} parse error{}: {message}", location(*.line, *.column))]
+    Parse {
+        format: PipelineFormat,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+}
+
+fn location(line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(" at line {}, column {}", line, column),
+        (Some(line), None) => format!(" at line {}", line),
+        _ => String::new(),
+    }
 }
@@ -2,14 +2,16 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use ryvus_core::{action::result::ExecutionResult, prelude::pipeline::Pipeline};
-use ryvus_engine::engine::EngineApi;
+use ryvus_engine::{engine::EngineApi, pipeline_resolver::PipelineResolver};
 use serde_json::json;
 use tracing::{debug, info};
 
 use crate::{
-    pipeline::loader::PipelineLoader,
+    context::sensative_masker::SensitiveMasker,
+    pipeline::{loader::PipelineLoader, manager::PipelineRegistry},
     resolver::{
-        config_resolver::resolve_config, env_resolver::EnvResolver, variable::ChainedResolver,
+        env_resolver::EnvResolver, pipeline_resolver::RegisteredPipelines,
+        variable::ChainedResolver,
     },
     FlowError,
 };
@@ -42,33 +44,27 @@ impl FlowExecutor for EngineAdapter {
         pipeline: String,
         input: serde_json::Value,
     ) -> Result<ExecutionResult, FlowError> {
-        tracing_subscriber::fmt::try_init();
+        let _ = tracing_subscriber::fmt::try_init();
         // Try to load as file first
         info!("Try loading pipeline from file");
 
-        let pipeline_def_result = PipelineLoader::from_file(&pipeline);
-        debug!("Loaded pipeline_def");
-
-        // If file not found, treat as inline JSON
-        let mut pipeline_def = match pipeline_def_result {
-            Ok(def) => def,
-            Err(_) => {
-                // Try to parse as inline JSON string
-                let parsed = serde_json::from_str(&pipeline)
-                    .map_err(|e| FlowError::Loader(format!("Invalid pipeline JSON: {}", e)))?;
-
-                parsed
-            }
+        // If no such file exists, treat the string as an inline definition
+        let pipeline_def = if std::path::Path::new(&pipeline).is_file() {
+            PipelineLoader::from_file(&pipeline)?
+        } else {
+            PipelineLoader::parse(&pipeline)?
         };
+        debug!("Loaded pipeline_def");
 
         debug!("Converted pipeline");
 
+        // The engine resolves variables through the flow, so it never stores them
         let resolver = ChainedResolver::new(vec![Box::new(EnvResolver)]);
+        let pipelines = Arc::new(RegisteredPipelines::new(
+            PipelineRegistry::default(),
+            Arc::new(resolver),
+        ));
         debug!("Resolver init");
-
-        // Resolve all vars
-        resolve_config(&mut pipeline_def, &resolver);
-        debug!("Resolved config");
         let runtime_input = match input {
             serde_json::Value::Null => json!({}),
             _ => input,
@@ -80,9 +76,15 @@ impl FlowExecutor for EngineAdapter {
         debug!("Convert pipeline_def to pipeline");
         debug!("Starting engine, brrr");
         // Execute pipeline
-        self.engine
-            .execute_pipeline(pipeline, runtime_input)
+        let result = self
+            .engine
+            .execute_pipeline_as(None, pipeline, runtime_input, Some(pipelines.clone()))
             .await
-            .map_err(|e| FlowError::Loader(e.to_string()))
+            .map_err(|e| FlowError::Loader(e.to_string()))?;
+
+        // Resolved secrets never leave the flow unmasked
+        SensitiveMasker::new(pipelines.secrets())
+            .mask(&result)
+            .map_err(|e| FlowError::Engine(e.to_string()))
     }
 }
@@ -2,6 +2,7 @@ pub mod context;
 pub mod error;
 pub mod pipeline;
 pub mod prelude;
+pub mod queue;
 pub mod resolver;
 pub mod store;
 pub mod trigger;
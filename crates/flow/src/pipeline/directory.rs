@@ -0,0 +1,207 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use notify::{RecursiveMode, Watcher};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{info, warn};
+
+use super::{format::PipelineFormat, loader::PipelineLoader, FlowPipelineManager};
+use crate::{error::FlowError, store::StateStore};
+
+/// Quiet period that groups the several events an editor emits for one save.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// What happened to a definition file during a (re)load.
+#[derive(Debug, Clone)]
+pub enum ReloadEvent {
+    Loaded {
+        path: PathBuf,
+        key: String,
+    },
+    Removed {
+        path: PathBuf,
+        key: String,
+    },
+    /// The file was rejected; any previously loaded version stays active.
+    Failed {
+        path: PathBuf,
+        error: String,
+    },
+}
+
+/// Loads every `.json`, `.yaml`, `.yml` and `.toml` definition in a directory
+/// into a `FlowPipelineManager`, optionally watching it for changes.
+pub struct PipelineDirectory {
+    root: PathBuf,
+    // Key each file owns, so removals and renamed keys unregister the right
+    // pipeline; a key defined by two files stays with the first
+    loaded: HashMap<PathBuf, String>,
+}
+
+impl PipelineDirectory {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    /// Registers every valid definition file. Invalid files are reported and skipped.
+    pub fn load_all<S: StateStore>(
+        &mut self,
+        manager: &FlowPipelineManager<S>,
+    ) -> Result<Vec<ReloadEvent>, FlowError> {
+        let entries = fs::read_dir(&self.root)
+            .map_err(|e| FlowError::Loader(format!("{}: {}", self.root.display(), e)))?;
+
+        let paths: BTreeSet<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_definition(path))
+            .collect();
+
+        Ok(paths
+            .into_iter()
+            .filter_map(|path| self.reload(manager, &path))
+            .collect())
+    }
+
+    /// Loads everything, then keeps the manager in sync with the directory
+    /// until the returned watcher is dropped.
+    pub fn watch<S: StateStore + 'static>(
+        mut self,
+        manager: Arc<FlowPipelineManager<S>>,
+    ) -> Result<DirectoryWatcher, FlowError> {
+        // Events carry absolute paths; keep ours comparable
+        self.root = fs::canonicalize(&self.root)
+            .map_err(|e| FlowError::Loader(format!("{}: {}", self.root.display(), e)))?;
+
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => {
+                    let _ = event_tx.send(event.paths);
+                }
+                Err(e) => warn!("Pipeline directory watch error: {}", e),
+            })
+            .map_err(|e| FlowError::Loader(e.to_string()))?;
+        watcher
+            .watch(&self.root, RecursiveMode::NonRecursive)
+            .map_err(|e| FlowError::Loader(format!("{}: {}", self.root.display(), e)))?;
+
+        let (report_tx, report_rx) = mpsc::unbounded_channel();
+        for event in self.load_all(&manager)? {
+            let _ = report_tx.send(event);
+        }
+
+        let task = tokio::spawn(async move {
+            while let Some(paths) = event_rx.recv().await {
+                let mut changed: BTreeSet<PathBuf> = paths.into_iter().collect();
+                tokio::time::sleep(DEBOUNCE).await;
+                while let Ok(more) = event_rx.try_recv() {
+                    changed.extend(more);
+                }
+
+                for path in changed.iter().filter(|p| is_definition(p)) {
+                    if let Some(event) = self.reload(&manager, path) {
+                        let _ = report_tx.send(event);
+                    }
+                }
+            }
+        });
+
+        Ok(DirectoryWatcher {
+            _watcher: watcher,
+            task,
+            events: report_rx,
+        })
+    }
+
+    /// Brings the manager in line with the current state of `path`.
+    fn reload<S: StateStore>(
+        &mut self,
+        manager: &FlowPipelineManager<S>,
+        path: &Path,
+    ) -> Option<ReloadEvent> {
+        if !path.exists() {
+            let key = self.loaded.remove(path)?;
+            manager.unregister(&key);
+            info!("Pipeline '{}' removed with {}", key, path.display());
+            return Some(ReloadEvent::Removed {
+                path: path.to_path_buf(),
+                key,
+            });
+        }
+
+        let registered = PipelineLoader::from_file(path).and_then(|def| {
+            if let Some(owner) = self.owner(&def.key).filter(|owner| *owner != path) {
+                return Err(FlowError::Loader(format!(
+                    "pipeline '{}' is already defined in {}",
+                    def.key,
+                    owner.display()
+                )));
+            }
+            let key = def.key.clone();
+            manager.try_register(def).map(|_| key)
+        });
+
+        match registered {
+            Ok(key) => {
+                // The file used to define a different pipeline
+                if let Some(previous) = self.loaded.insert(path.to_path_buf(), key.clone()) {
+                    if previous != key {
+                        manager.unregister(&previous);
+                    }
+                }
+                info!("Pipeline '{}' loaded from {}", key, path.display());
+                Some(ReloadEvent::Loaded {
+                    path: path.to_path_buf(),
+                    key,
+                })
+            }
+            Err(e) => {
+                warn!("Keeping previous pipeline for {}: {}", path.display(), e);
+                Some(ReloadEvent::Failed {
+                    path: path.to_path_buf(),
+                    error: e.to_string(),
+                })
+            }
+        }
+    }
+
+    /// File that registered `key`, if any.
+    fn owner(&self, key: &str) -> Option<&Path> {
+        self.loaded
+            .iter()
+            .find(|(_, loaded)| *loaded == key)
+            .map(|(path, _)| path.as_path())
+    }
+}
+
+/// Keeps a watched directory in sync; stops watching when dropped.
+pub struct DirectoryWatcher {
+    _watcher: notify::RecommendedWatcher,
+    task: JoinHandle<()>,
+    events: mpsc::UnboundedReceiver<ReloadEvent>,
+}
+
+impl DirectoryWatcher {
+    /// Next load, removal or failure, starting with the initial load.
+    pub async fn next_event(&mut self) -> Option<ReloadEvent> {
+        self.events.recv().await
+    }
+}
+
+impl Drop for DirectoryWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn is_definition(path: &Path) -> bool {
+    PipelineFormat::from_path(path).is_some()
+}
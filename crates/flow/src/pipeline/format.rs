@@ -0,0 +1,109 @@
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::PipelineDefinition;
+use crate::error::FlowError;
+
+/// Serialization format of a pipeline definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl PipelineFormat {
+    /// Format implied by the file extension, if it is a known one.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first significant line of `content`.
+    /// Anything that is neither a JSON object nor TOML is read as YAML.
+    pub fn detect(content: &str) -> Self {
+        let first = content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+
+        match first {
+            Some(line) if line.starts_with('{') => Self::Json,
+            Some(line) if is_toml_line(line) => Self::Toml,
+            _ => Self::Yaml,
+        }
+    }
+
+    pub fn parse(self, content: &str) -> Result<PipelineDefinition, FlowError> {
+        match self {
+            Self::Json => serde_json::from_str(content).map_err(|e| {
+                let (line, column) = (e.line(), e.column());
+                self.error(strip_location(&e.to_string()), Some((line, column)))
+            }),
+            Self::Yaml => serde_yaml::from_str(content).map_err(|e| {
+                let location = e.location().map(|l| (l.line(), l.column()));
+                self.error(strip_location(&e.to_string()), location)
+            }),
+            Self::Toml => toml::from_str(content).map_err(|e| {
+                let location = e.span().map(|span| line_column(content, span.start));
+                self.error(e.message().to_string(), location)
+            }),
+        }
+    }
+
+    fn error(self, message: String, location: Option<(usize, usize)>) -> FlowError {
+        FlowError::Parse {
+            format: self,
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for PipelineFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+        })
+    }
+}
+
+/// `[table]`, `[[array]]` or `key = value`
+fn is_toml_line(line: &str) -> bool {
+    if line.starts_with('[') {
+        return line.ends_with(']');
+    }
+    line.split_once('=').is_some_and(|(key, _)| {
+        let key = key.trim();
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '"'))
+    })
+}
+
+/// 1-based line and column of a byte offset.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+/// Drops the " at line X column Y" suffix, which is reported separately.
+fn strip_location(message: &str) -> String {
+    match message.rsplit_once(" at line ") {
+        Some((head, _)) => head.to_string(),
+        None => message.to_string(),
+    }
+}
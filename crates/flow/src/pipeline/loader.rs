@@ -1,16 +1,31 @@
-use super::PipelineDefinition;
+use super::{format::PipelineFormat, PipelineDefinition};
 use crate::error::FlowError;
 use std::{fs, path::Path};
 
 pub struct PipelineLoader;
 
 impl PipelineLoader {
+    /// Loads a definition, picking the format from the file extension or,
+    /// for unknown extensions, from the content.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<PipelineDefinition, FlowError> {
-        let data = fs::read_to_string(path).map_err(|e| FlowError::Loader(e.to_string()))?;
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .map_err(|e| FlowError::Loader(format!("{}: {}", path.display(), e)))?;
 
-        let pipeline: PipelineDefinition = serde_json::from_str(&data)
-            .map_err(|e| FlowError::Store(format!("JSON parse error: {}", e)))?;
+        let format =
+            PipelineFormat::from_path(path).unwrap_or_else(|| PipelineFormat::detect(&data));
+        format.parse(&data)
+    }
+
+    /// Parses a definition whose format is detected from the content.
+    pub fn parse(content: &str) -> Result<PipelineDefinition, FlowError> {
+        PipelineFormat::detect(content).parse(content)
+    }
 
-        Ok(pipeline)
+    pub fn from_str_with_format(
+        content: &str,
+        format: PipelineFormat,
+    ) -> Result<PipelineDefinition, FlowError> {
+        format.parse(content)
     }
 }
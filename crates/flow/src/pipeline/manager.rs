@@ -1,16 +1,46 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::sync::Arc;
-
-use crate::{context::FlowContext, error::FlowError, store::StateStore};
-use ryvus_core::prelude::pipeline::{Pipeline, PipelineStep};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use crate::{
+    context::FlowContext,
+    error::FlowError,
+    resolver::{
+        env_resolver::EnvResolver,
+        pipeline_resolver::RegisteredPipelines,
+        variable::{ChainedResolver, VariableResolver},
+    },
+    store::StateStore,
+};
+use ryvus_core::action::result::ExecutionResult;
+use ryvus_core::error::ErrorKind;
+use ryvus_core::pipeline::condition::Condition;
+use ryvus_core::pipeline::retry::{Backoff, RetryPolicy};
+use ryvus_core::prelude::pipeline::{
+    ExecutionMode, ForEach, ParallelBranches, Pipeline, PipelineStep, SubPipeline, WaitFor,
+    DEFAULT_MAX_ITERATIONS,
+};
+use ryvus_core::state::QueuedRun;
+use ryvus_core::utils::id::generate_id;
+use ryvus_engine::{engine::EngineApi, pipeline_resolver::PipelineResolver};
+use tokio::sync::Notify;
+use tracing::warn;
 
 /// -----------------------------
 /// Step Definition
 /// -----------------------------
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default, JsonSchema)]
+#[schemars(description = "A step: the action it runs and where control goes next")]
 pub struct StepDefinition {
     pub key: String,
+
+    /// May be left empty on pure fan-out steps
+    #[serde(default)]
     pub action: String,
 
     #[serde(default)]
@@ -40,6 +70,54 @@ pub struct StepDefinition {
     /// Fallback if this step fails
     #[serde(default)]
     pub on_error: Option<String>,
+
+    /// Branches started concurrently once this step completes
+    #[serde(default)]
+    pub parallel: Option<ParallelBranches>,
+
+    /// Steps that must succeed first (DAG mode)
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// Maximum duration of a single attempt
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// Registered pipeline to run instead of an action
+    #[serde(default)]
+    pub pipeline: Option<SubPipeline>,
+
+    /// Runs the step once per array element (`$.item`)
+    #[serde(default)]
+    pub for_each: Option<ForEach>,
+
+    /// Repeats the step while a condition holds
+    #[serde(default, rename = "while")]
+    pub repeat: Option<WhileDef>,
+
+    /// Action that undoes this step if the run fails later on
+    #[serde(default)]
+    pub compensate: Option<String>,
+
+    /// Suspends the run until a signal arrives; `otherwise` runs on timeout
+    #[serde(default)]
+    pub wait_for: Option<WaitFor>,
+
+    /// Pauses the run for this many milliseconds
+    #[serde(default)]
+    pub sleep_ms: Option<u64>,
+
+    /// Pauses the run until this RFC 3339 timestamp or epoch milliseconds,
+    /// usually a JSONPath such as `$.payload.remind_at`
+    #[serde(default)]
+    pub wait_until: Option<String>,
+}
+
+impl StepDefinition {
+    /// Whether the step only pauses the run, through `sleep_ms` or `wait_until`.
+    pub fn is_delay(&self) -> bool {
+        self.sleep_ms.is_some() || self.wait_until.is_some()
+    }
 }
 
 fn empty_json_object() -> Value {
@@ -47,21 +125,73 @@ fn empty_json_object() -> Value {
 }
 
 /// Conditional branch definition
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ConditionalNextDef {
+    /// Condition expression, e.g. `$.payload.total >= 100 && $.payload.country in ['DE', 'FR']`
     pub when: String,
     pub next: String,
 }
 
-/// Retry configuration (future feature)
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// `while` loop definition
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct WhileDef {
+    /// Condition checked before every iteration; `$.index` counts iterations
+    pub when: String,
+
+    /// The step fails once it needs more iterations than this
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+}
+
+fn default_max_iterations() -> u32 {
+    DEFAULT_MAX_ITERATIONS
+}
+
+/// Retry configuration
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct RetryConfig {
     pub max_attempts: u32,
+    #[serde(default)]
     pub delay: u64, // ms
+
+    /// `fixed` (default) or `exponential`
+    #[serde(default)]
+    pub backoff: Backoff,
+
+    #[serde(default)]
+    pub multiplier: Option<f64>,
+
+    #[serde(default)]
+    pub max_delay: Option<u64>, // ms
+
+    #[serde(default)]
+    pub jitter: bool,
+
+    #[serde(default)]
+    pub max_elapsed: Option<u64>, // ms
+
+    /// Error kinds to retry on; retries every failure when empty
+    #[serde(default)]
+    pub retry_on: Vec<ErrorKind>,
+}
+
+impl From<RetryConfig> for RetryPolicy {
+    fn from(cfg: RetryConfig) -> Self {
+        let mut policy = RetryPolicy::fixed(cfg.max_attempts, Duration::from_millis(cfg.delay));
+        policy.backoff = cfg.backoff;
+        if let Some(multiplier) = cfg.multiplier {
+            policy.multiplier = multiplier;
+        }
+        policy.max_delay_ms = cfg.max_delay;
+        policy.jitter = cfg.jitter;
+        policy.max_elapsed_ms = cfg.max_elapsed;
+        policy.retry_on = cfg.retry_on;
+        policy
+    }
 }
 
 /// Hook definition
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct HookDefinition {
     #[serde(rename = "type")]
     pub hook_type: String,
@@ -72,7 +202,8 @@ pub struct HookDefinition {
 /// -----------------------------
 /// Pipeline Definition
 /// -----------------------------
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default, JsonSchema)]
+#[schemars(description = "A Ryvus pipeline definition file")]
 pub struct PipelineDefinition {
     pub key: String,
     pub description: Option<String>,
@@ -81,39 +212,246 @@ pub struct PipelineDefinition {
 
     #[serde(default)]
     pub pipeline_hooks: Vec<HookDefinition>,
+
+    /// `routed` (default) or `dag`
+    #[serde(default)]
+    pub mode: ExecutionMode,
+
+    /// Maximum number of steps running at once in DAG mode
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+
+    /// Deadline for the whole run
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// Times routing may enter one step before the run fails (default 100)
+    #[serde(default)]
+    pub max_visits_per_step: Option<u32>,
+
+    /// Steps routing may enter in total before the run fails (default 10000)
+    #[serde(default)]
+    pub max_steps: Option<u32>,
 }
 
+impl PipelineDefinition {
+    /// JSON Schema of the definition file format, as published in `schema/pipeline.schema.json`.
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(PipelineDefinition)
+    }
+}
+
+/// Registered definitions by key, shared with runs that call them as sub-pipelines.
+pub type PipelineRegistry = Arc<RwLock<HashMap<String, Arc<PipelineDefinition>>>>;
+
 /// -----------------------------
 /// Flow Pipeline Manager
 /// -----------------------------
 pub struct FlowPipelineManager<S: StateStore> {
     store: Arc<S>,
-    pipelines: Vec<PipelineDefinition>,
+    engine: Arc<dyn EngineApi>,
+    resolver: Arc<dyn VariableResolver>,
+    pipelines: PipelineRegistry,
+    /// Wakes idle dispatchers when a run is queued
+    queued: Arc<Notify>,
 }
 
 impl<S: StateStore> FlowPipelineManager<S> {
-    pub fn new(store: S) -> Self {
+    /// Runs registered pipelines on `engine`, resolving `$VAR` placeholders from the environment.
+    pub fn new(store: S, engine: impl EngineApi + 'static) -> Self {
+        Self::with_shared_store(Arc::new(store), engine)
+    }
+
+    /// Like `new`, for a store shared with others, e.g. the one `engine`
+    /// checkpoints suspended runs into.
+    pub fn with_shared_store(store: Arc<S>, engine: impl EngineApi + 'static) -> Self {
         Self {
-            store: Arc::new(store),
-            pipelines: Vec::new(),
+            store,
+            engine: Arc::new(engine),
+            resolver: Arc::new(ChainedResolver::new(vec![Box::new(EnvResolver)])),
+            pipelines: Arc::new(RwLock::new(HashMap::new())),
+            queued: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Replaces the resolver used for `$VAR` and `secret:$VAR` placeholders.
+    pub fn with_resolver(mut self, resolver: impl VariableResolver + 'static) -> Self {
+        self.resolver = Arc::new(resolver);
+        self
+    }
+
+    pub fn store(&self) -> &Arc<S> {
+        &self.store
+    }
+
+    /// Registers `pipeline`, replacing any definition with the same key.
+    /// Runs already started keep the definition they began with.
+    pub fn register(&self, pipeline: PipelineDefinition) {
+        let mut guard = self.pipelines.write().unwrap_or_else(|e| e.into_inner());
+        guard.insert(pipeline.key.clone(), Arc::new(pipeline));
+    }
+
+    /// Validates `pipeline` before registering it; on error the current definition stays active.
+    /// Warnings are logged and do not block registration.
+    pub fn try_register(&self, pipeline: PipelineDefinition) -> Result<(), FlowError> {
+        let report = pipeline.validate();
+        if !report.is_valid() {
+            return Err(FlowError::Invalid(report));
+        }
+        for issue in report.warnings() {
+            warn!("Pipeline '{}': {}", pipeline.key, issue);
+        }
+        Pipeline::try_from(pipeline.clone()).map_err(FlowError::Loader)?;
+        self.register(pipeline);
+        Ok(())
+    }
+
+    pub fn unregister(&self, pipeline_key: &str) -> Option<Arc<PipelineDefinition>> {
+        let mut guard = self.pipelines.write().unwrap_or_else(|e| e.into_inner());
+        guard.remove(pipeline_key)
+    }
+
+    pub fn get(&self, pipeline_key: &str) -> Option<Arc<PipelineDefinition>> {
+        let guard = self.pipelines.read().unwrap_or_else(|e| e.into_inner());
+        guard.get(pipeline_key).cloned()
+    }
+
+    /// Keys of all registered pipelines, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        let guard = self.pipelines.read().unwrap_or_else(|e| e.into_inner());
+        let mut keys: Vec<String> = guard.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Runs a registered pipeline with `input` as its payload and returns the
+    /// result, with resolved secrets masked.
+    /// Sub-pipeline steps can call any other registered pipeline.
+    pub async fn start(
+        &self,
+        pipeline_key: &str,
+        input: Value,
+    ) -> Result<ExecutionResult, FlowError> {
+        self.context(pipeline_key)?.run(input).await
+    }
+
+    /// Like `start`, under `run_id` instead of a generated id, so the id can
+    /// be handed out before the run finishes.
+    pub async fn start_as(
+        &self,
+        run_id: impl Into<String>,
+        pipeline_key: &str,
+        input: Value,
+    ) -> Result<ExecutionResult, FlowError> {
+        self.context(pipeline_key)?
+            .with_run_id(run_id)
+            .run(input)
+            .await
+    }
+
+    /// Continues run `run_id` of `pipeline_key` from the checkpoint the engine
+    /// kept for it, or returns its result when it had already stopped.
+    /// `None` when the engine has no checkpoint for the run.
+    pub async fn resume(
+        &self,
+        run_id: impl Into<String>,
+        pipeline_key: &str,
+    ) -> Result<Option<ExecutionResult>, FlowError> {
+        self.context(pipeline_key)?
+            .with_run_id(run_id)
+            .resume()
+            .await
+    }
+
+    /// Delivers signal `name` with `payload` to run `run_id` of `pipeline_key`,
+    /// suspended at a `wait_for` step, and returns its result once the run
+    /// stops again.
+    pub async fn signal(
+        &self,
+        run_id: impl Into<String>,
+        pipeline_key: &str,
+        name: &str,
+        payload: Value,
+    ) -> Result<ExecutionResult, FlowError> {
+        self.context(pipeline_key)?
+            .with_run_id(run_id)
+            .signal(name, payload)
+            .await
+    }
+
+    /// Continues the suspended runs whose timer fired, finished sleeps and
+    /// timed-out signal waits, and stores their results. Meant to be called
+    /// periodically. Results are returned with their secrets masked; those
+    /// that could not be stored, e.g. of a pipeline no longer registered,
+    /// are logged and left out.
+    pub async fn wake_due(&self) -> Result<Vec<ExecutionResult>, FlowError> {
+        let pipelines: Arc<dyn PipelineResolver> = Arc::new(RegisteredPipelines::new(
+            self.pipelines.clone(),
+            self.resolver.clone(),
+        ));
+        let woken = self
+            .engine
+            .wake_due_pipelines(Some(pipelines.clone()))
+            .await
+            .map_err(|e| FlowError::Engine(e.to_string()))?;
+
+        let mut stored = Vec::with_capacity(woken.len());
+        for result in woken {
+            let pipeline_key = result.pipeline_key.as_deref().unwrap_or_default();
+            let saved = match self.context(pipeline_key) {
+                Ok(context) => context.save_run(&result, pipelines.as_ref()).await,
+                Err(e) => Err(e),
+            };
+            match saved {
+                Ok(result) => stored.push(result),
+                Err(e) => warn!("Could not store the result of run {}: {}", result.run_id, e),
+            }
         }
+        Ok(stored)
     }
 
-    pub fn register(&mut self, pipeline: PipelineDefinition) {
-        self.pipelines.push(pipeline);
+    /// Queues a run of a registered pipeline for a `RunDispatcher` and returns
+    /// its run id. The run survives restarts with a durable store.
+    pub async fn enqueue(&self, pipeline_key: &str, input: Value) -> Result<String, FlowError> {
+        self.enqueue_with_priority(pipeline_key, input, 0).await
     }
 
-    pub fn get(&self, pipeline_key: &str) -> Option<&PipelineDefinition> {
-        self.pipelines.iter().find(|p| p.key == pipeline_key)
+    /// Like `enqueue`; runs with a higher `priority` are dispatched first.
+    pub async fn enqueue_with_priority(
+        &self,
+        pipeline_key: &str,
+        input: Value,
+        priority: i32,
+    ) -> Result<String, FlowError> {
+        if self.get(pipeline_key).is_none() {
+            return Err(FlowError::PipelineNotFound(pipeline_key.to_string()));
+        }
+
+        let run = QueuedRun::new(generate_id("run"), pipeline_key, input).with_priority(priority);
+        self.store
+            .save_queued(&run)
+            .await
+            .map_err(FlowError::Store)?;
+        self.queued.notify_one();
+        Ok(run.run_id)
     }
 
-    pub async fn start(&self, pipeline_key: &str) -> Result<(), FlowError> {
+    pub(crate) fn queued(&self) -> &Arc<Notify> {
+        &self.queued
+    }
+
+    fn context(&self, pipeline_key: &str) -> Result<FlowContext<S>, FlowError> {
         let Some(def) = self.get(pipeline_key) else {
             return Err(FlowError::PipelineNotFound(pipeline_key.to_string()));
         };
 
-        let ctx = FlowContext::new(def.clone(), self.store.clone());
-        ctx.run().await
+        Ok(FlowContext::new(
+            (*def).clone(),
+            self.store.clone(),
+            self.engine.clone(),
+            self.resolver.clone(),
+        )
+        .with_pipelines(self.pipelines.clone()))
     }
 }
 
@@ -134,7 +472,12 @@ impl TryFrom<PipelineDefinition> for Pipeline {
             .steps
             .into_iter()
             .map(|s| {
-                if s.action.trim().is_empty() {
+                if s.action.trim().is_empty()
+                    && s.parallel.is_none()
+                    && s.pipeline.is_none()
+                    && s.wait_for.is_none()
+                    && !s.is_delay()
+                {
                     return Err(format!("Step '{}' is missing an action", s.key));
                 }
 
@@ -145,7 +488,9 @@ impl TryFrom<PipelineDefinition> for Pipeline {
 
                 // Apply conditional branches
                 for cond in &s.next_when {
-                    step_builder = step_builder.when(cond.when.clone(), cond.next.clone());
+                    let condition = Condition::parse(&cond.when)
+                        .map_err(|e| format!("Step '{}': {}", s.key, e))?;
+                    step_builder = step_builder.when(condition, cond.next.clone());
                 }
 
                 // Apply otherwise
@@ -163,6 +508,60 @@ impl TryFrom<PipelineDefinition> for Pipeline {
                     step_builder = step_builder.next(next.clone());
                 }
 
+                // Apply retry policy
+                if let Some(retry) = &s.retry {
+                    step_builder = step_builder.retry(retry.clone().into());
+                }
+
+                // Apply timeout
+                if let Some(timeout_ms) = s.timeout_ms {
+                    step_builder = step_builder.timeout(Duration::from_millis(timeout_ms));
+                }
+
+                // Apply dependencies
+                for dep in &s.depends_on {
+                    step_builder = step_builder.depends_on(dep.clone());
+                }
+
+                // Apply fan-out
+                if let Some(parallel) = &s.parallel {
+                    step_builder = step_builder.parallel(
+                        parallel.branches.clone(),
+                        parallel.join.clone(),
+                        parallel.wait,
+                    );
+                }
+
+                // Apply sub-pipeline
+                if let Some(sub) = &s.pipeline {
+                    step_builder = step_builder.pipeline(sub.key.clone(), sub.input.clone());
+                }
+
+                // Apply loops
+                if let Some(for_each) = &s.for_each {
+                    step_builder =
+                        step_builder.for_each(for_each.items.clone(), for_each.concurrency);
+                }
+                if let Some(repeat) = &s.repeat {
+                    let condition = Condition::parse(&repeat.when)
+                        .map_err(|e| format!("Step '{}': {}", s.key, e))?;
+                    step_builder = step_builder.repeat_while(condition, repeat.max_iterations);
+                }
+
+                if let Some(compensate) = &s.compensate {
+                    step_builder = step_builder.compensate(compensate.clone());
+                }
+                if let Some(wait) = &s.wait_for {
+                    let timeout = wait.timeout_ms.map(Duration::from_millis);
+                    step_builder = step_builder.wait_for(wait.signal.clone(), timeout);
+                }
+                if let Some(ms) = s.sleep_ms {
+                    step_builder = step_builder.sleep(Duration::from_millis(ms));
+                }
+                if let Some(timestamp) = &s.wait_until {
+                    step_builder = step_builder.wait_until(timestamp.clone());
+                }
+
                 Ok(step_builder.build())
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -201,8 +600,42 @@ impl TryFrom<PipelineDefinition> for Pipeline {
                     ));
                 }
             }
+            if let Some(ref parallel) = step.parallel {
+                for branch in &parallel.branches {
+                    if !all_keys.contains(branch) {
+                        return Err(format!(
+                            "Step '{}' references undefined parallel branch '{}'",
+                            step.key, branch
+                        ));
+                    }
+                }
+                if !all_keys.contains(&parallel.join) {
+                    return Err(format!(
+                        "Step '{}' references undefined 'join' step '{}'",
+                        step.key, parallel.join
+                    ));
+                }
+            }
+        }
+
+        let mut builder = Pipeline::builder(def.key).steps(steps).mode(def.mode);
+        if let Some(limit) = def.max_concurrency {
+            builder = builder.max_concurrency(limit);
+        }
+        if let Some(timeout_ms) = def.timeout_ms {
+            builder = builder.timeout(Duration::from_millis(timeout_ms));
         }
+        if let Some(max) = def.max_visits_per_step {
+            builder = builder.max_visits_per_step(max);
+        }
+        if let Some(max) = def.max_steps {
+            builder = builder.max_steps(max);
+        }
+        let pipeline = builder.build();
+
+        // Dependencies must reference known steps and form a DAG
+        pipeline.dependency_order()?;
 
-        Ok(Pipeline::builder(def.key).steps(steps).build())
+        Ok(pipeline)
     }
 }
@@ -1,4 +1,10 @@
+pub mod directory;
+pub mod format;
 pub mod loader;
 pub mod manager;
+pub mod validation;
 
+pub use directory::{DirectoryWatcher, PipelineDirectory, ReloadEvent};
+pub use format::PipelineFormat;
 pub use manager::{FlowPipelineManager, PipelineDefinition};
+pub use validation::{PipelineValidator, Severity, ValidationIssue, ValidationReport};
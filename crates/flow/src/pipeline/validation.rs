@@ -0,0 +1,589 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+};
+
+use chrono::DateTime;
+use ryvus_core::pipeline::{condition::Condition, pipeline::ExecutionMode, template::Template};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::manager::{PipelineDefinition, StepDefinition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The pipeline runs, but probably not as intended
+    Warning,
+    /// The pipeline cannot be registered
+    Error,
+}
+
+/// One problem found in a definition, located by a path such as `steps[2].next_when[0].when`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+/// Every problem found in a definition, in document order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// True when there are no errors; warnings do not block registration.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+    }
+
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Error, path.into(), message.into());
+    }
+
+    fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, path.into(), message.into());
+    }
+
+    fn push(&mut self, severity: Severity, path: String, message: String) {
+        self.issues.push(ValidationIssue {
+            severity,
+            path,
+            message,
+        });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.issues.iter().map(ToString::to_string).collect();
+        f.write_str(&lines.join("; "))
+    }
+}
+
+/// Checks a `PipelineDefinition` as a whole and reports every problem instead of the first.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineValidator {
+    actions: Option<HashSet<String>>,
+}
+
+impl PipelineValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also reports steps whose action is not one of `actions`.
+    pub fn with_actions<I, K>(mut self, actions: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.actions = Some(actions.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn validate(&self, def: &PipelineDefinition) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        if def.key.trim().is_empty() {
+            report.error("key", "Pipeline key is empty");
+        }
+        if def.steps.is_empty() {
+            report.error("steps", format!("Pipeline '{}' has no steps", def.key));
+            return report;
+        }
+
+        if def.max_visits_per_step == Some(0) {
+            report.error(
+                "max_visits_per_step",
+                "max_visits_per_step must be at least 1",
+            );
+        }
+        if def.max_steps == Some(0) {
+            report.error("max_steps", "max_steps must be at least 1");
+        }
+
+        let mut first_index: HashMap<&str, usize> = HashMap::new();
+        for (index, step) in def.steps.iter().enumerate() {
+            if let Some(first) = first_index.get(step.key.as_str()) {
+                report.error(
+                    format!("steps[{index}].key"),
+                    format!(
+                        "Duplicate step key '{}', first used by steps[{first}]",
+                        step.key
+                    ),
+                );
+            } else {
+                first_index.insert(&step.key, index);
+            }
+        }
+
+        for (index, step) in def.steps.iter().enumerate() {
+            self.validate_step(&mut report, &first_index, index, step);
+        }
+
+        match def.mode {
+            ExecutionMode::Routed => {
+                check_reachable(&mut report, def, &first_index);
+                check_unconditional_cycles(&mut report, def, &first_index);
+            }
+            ExecutionMode::Dag => check_acyclic(&mut report, def, &first_index),
+        }
+
+        report
+    }
+
+    fn validate_step(
+        &self,
+        report: &mut ValidationReport,
+        keys: &HashMap<&str, usize>,
+        index: usize,
+        step: &StepDefinition,
+    ) {
+        let path = format!("steps[{index}]");
+
+        if step.key.trim().is_empty() {
+            report.error(format!("{path}.key"), "Step key is empty");
+        }
+
+        if step.action.trim().is_empty() {
+            if step.parallel.is_none()
+                && step.pipeline.is_none()
+                && step.wait_for.is_none()
+                && !step.is_delay()
+            {
+                report.error(
+                    format!("{path}.action"),
+                    format!("Step '{}' is missing an action", step.key),
+                );
+            }
+        } else if step.pipeline.is_some() {
+            report.error(
+                format!("{path}.action"),
+                format!("Step '{}' sets both an action and a pipeline", step.key),
+            );
+        } else if step.wait_for.is_some() {
+            report.error(
+                format!("{path}.action"),
+                format!("Step '{}' sets both an action and wait_for", step.key),
+            );
+        } else if step.is_delay() {
+            report.error(
+                format!("{path}.action"),
+                format!("Step '{}' sets both an action and a delay", step.key),
+            );
+        } else if let Some(actions) = &self.actions {
+            if !actions.contains(&step.action) {
+                report.error(
+                    format!("{path}.action"),
+                    format!("Unknown action '{}'", step.action),
+                );
+            }
+        }
+
+        if let Some(compensate) = &step.compensate {
+            if compensate.trim().is_empty() {
+                report.error(format!("{path}.compensate"), "Compensating action is empty");
+            } else if self
+                .actions
+                .as_ref()
+                .is_some_and(|a| !a.contains(compensate))
+            {
+                report.error(
+                    format!("{path}.compensate"),
+                    format!("Unknown action '{}'", compensate),
+                );
+            }
+        }
+
+        let mut reference = |field: String, target: &str| {
+            if !keys.contains_key(target) {
+                report.error(
+                    format!("{path}.{field}"),
+                    format!("References undefined step '{}'", target),
+                );
+            }
+        };
+        if let Some(next) = step.next.as_deref().filter(|n| !n.is_empty()) {
+            reference("next".into(), next);
+        }
+        for (i, cond) in step.next_when.iter().enumerate() {
+            reference(format!("next_when[{i}].next"), &cond.next);
+        }
+        if let Some(otherwise) = &step.otherwise {
+            reference("otherwise".into(), otherwise);
+        }
+        if let Some(on_error) = &step.on_error {
+            reference("on_error".into(), on_error);
+        }
+        if let Some(parallel) = &step.parallel {
+            for (i, branch) in parallel.branches.iter().enumerate() {
+                reference(format!("parallel.branches[{i}]"), branch);
+            }
+            reference("parallel.join".into(), &parallel.join);
+        }
+        for (i, dep) in step.depends_on.iter().enumerate() {
+            reference(format!("depends_on[{i}]"), dep);
+        }
+
+        for (i, cond) in step.next_when.iter().enumerate() {
+            check_condition(report, &format!("{path}.next_when[{i}].when"), &cond.when);
+        }
+
+        check_paths(report, &format!("{path}.params"), &step.params);
+        match step.config.as_object() {
+            // Script sources use `${...}` for their own string interpolation
+            Some(config) => {
+                for (key, value) in config.iter().filter(|(key, _)| *key != "script") {
+                    check_paths(report, &format!("{path}.config.{key}"), value);
+                }
+            }
+            None => check_paths(report, &format!("{path}.config"), &step.config),
+        }
+
+        if let Some(for_each) = &step.for_each {
+            if let Err(message) = check_json_path(&for_each.items) {
+                report.error(format!("{path}.for_each.items"), message);
+            }
+            if for_each.concurrency == Some(0) {
+                report.error(
+                    format!("{path}.for_each.concurrency"),
+                    "Concurrency must be at least 1",
+                );
+            }
+            if step.repeat.is_some() {
+                report.error(
+                    format!("{path}.while"),
+                    format!("Step '{}' sets both for_each and while", step.key),
+                );
+            }
+        }
+        if let Some(repeat) = &step.repeat {
+            check_condition(report, &format!("{path}.while.when"), &repeat.when);
+            if repeat.max_iterations == 0 {
+                report.error(
+                    format!("{path}.while.max_iterations"),
+                    "max_iterations must be at least 1",
+                );
+            }
+        }
+
+        if let Some(wait) = &step.wait_for {
+            if wait.signal.trim().is_empty() {
+                report.error(format!("{path}.wait_for.signal"), "Signal name is empty");
+            }
+            if step.for_each.is_some() || step.repeat.is_some() || step.pipeline.is_some() {
+                report.error(
+                    format!("{path}.wait_for"),
+                    format!(
+                        "Step '{}' waits for a signal and cannot also loop or run a pipeline",
+                        step.key
+                    ),
+                );
+            }
+            if step.is_delay() {
+                report.error(
+                    format!("{path}.wait_for"),
+                    format!("Step '{}' sets both wait_for and a delay", step.key),
+                );
+            }
+            if step.otherwise.is_some() && wait.timeout_ms.is_none() {
+                report.warning(
+                    format!("{path}.otherwise"),
+                    format!(
+                        "Step '{}' only takes `otherwise` when its signal times out, but sets no timeout_ms",
+                        step.key
+                    ),
+                );
+            }
+        }
+
+        if let Some(timestamp) = &step.wait_until {
+            let path = format!("{path}.wait_until");
+            if step.sleep_ms.is_some() {
+                report.error(
+                    &path,
+                    format!("Step '{}' sets both sleep_ms and wait_until", step.key),
+                );
+            }
+            if Template::is_template(timestamp) || timestamp.starts_with("$.") {
+                check_paths(report, &path, &Value::String(timestamp.clone()));
+            } else if DateTime::parse_from_rfc3339(timestamp).is_err() {
+                report.error(
+                    path,
+                    format!(
+                        "'{}' is neither a JSONPath nor an RFC 3339 timestamp",
+                        timestamp
+                    ),
+                );
+            }
+        }
+        if step.is_delay() && (step.pipeline.is_some() || step.parallel.is_some()) {
+            report.error(
+                format!("{path}.pipeline"),
+                format!(
+                    "Step '{}' sets a delay and cannot also run a pipeline or fan out",
+                    step.key
+                ),
+            );
+        }
+
+        if let Some(sub) = &step.pipeline {
+            if sub.key.trim().is_empty() {
+                report.error(format!("{path}.pipeline.key"), "Pipeline key is empty");
+            }
+            if let Some(input) = &sub.input {
+                check_paths(report, &format!("{path}.pipeline.input"), input);
+            }
+        }
+    }
+}
+
+impl PipelineDefinition {
+    /// Runs every structural check; action keys are not checked.
+    pub fn validate(&self) -> ValidationReport {
+        PipelineValidator::new().validate(self)
+    }
+}
+
+/// Parses the condition and checks every JSONPath it reads. Unquoted words
+/// still compare as strings but are reported so they can be quoted.
+fn check_condition(report: &mut ValidationReport, path: &str, expr: &str) {
+    let condition = match Condition::parse(expr) {
+        Ok(condition) => condition,
+        Err(e) => return report.error(path, e.to_string()),
+    };
+    if let Err(message) = condition.paths().into_iter().try_for_each(check_json_path) {
+        report.error(path, message);
+    }
+    for word in condition.bare_words() {
+        report.warning(
+            path,
+            format!("Bare word '{word}' is read as the string \"{word}\"; quote it"),
+        );
+    }
+}
+
+/// Reports strings that the engine would resolve as JSONPath but that do not parse,
+/// and `${...}` templates that are malformed or use an unknown subject.
+fn check_paths(report: &mut ValidationReport, path: &str, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                check_paths(report, &format!("{path}.{key}"), v);
+            }
+        }
+        Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                check_paths(report, &format!("{path}[{i}]"), v);
+            }
+        }
+        Value::String(s) if Template::is_template(s) => {
+            if let Err(message) = check_template(s) {
+                report.error(path, message);
+            }
+        }
+        Value::String(s) => {
+            let expr = s.strip_prefix("secret:").unwrap_or(s);
+            if expr.starts_with("$.") {
+                if let Err(message) = check_json_path(expr) {
+                    report.error(path, message);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_template(text: &str) -> Result<(), String> {
+    let template = Template::parse(text).map_err(|e| e.to_string())?;
+    let checked = template
+        .placeholders()
+        .try_for_each(|p| match p.subject.split_once('.') {
+            _ if p.subject.starts_with('$') => check_json_path(&p.subject),
+            Some(("env" | "secret", name)) if !name.trim().is_empty() => Ok(()),
+            _ => Err(format!(
+                "Unknown placeholder '{}'; expected a JSONPath, env.NAME or secret.NAME",
+                p.subject
+            )),
+        });
+    checked
+}
+
+fn check_json_path(expr: &str) -> Result<(), String> {
+    jsonpath_rust::parser::parse_json_path(expr)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid JSONPath '{}': {}", expr, e))
+}
+
+/// Every step key a routed step can hand control to.
+fn successors(step: &StepDefinition) -> impl Iterator<Item = &str> {
+    step.next
+        .iter()
+        .chain(step.next_when.iter().map(|c| &c.next))
+        .chain(&step.otherwise)
+        .chain(&step.on_error)
+        .chain(
+            step.parallel
+                .iter()
+                .flat_map(|p| p.branches.iter().chain(std::iter::once(&p.join))),
+        )
+        .map(String::as_str)
+}
+
+/// Routed runs start at the first step, so anything it cannot reach never runs.
+fn check_reachable(
+    report: &mut ValidationReport,
+    def: &PipelineDefinition,
+    keys: &HashMap<&str, usize>,
+) {
+    let mut seen = vec![false; def.steps.len()];
+    let mut queue = VecDeque::from([0]);
+    seen[0] = true;
+
+    while let Some(index) = queue.pop_front() {
+        for next in successors(&def.steps[index]) {
+            if let Some(&target) = keys.get(next) {
+                if !seen[target] {
+                    seen[target] = true;
+                    queue.push_back(target);
+                }
+            }
+        }
+    }
+
+    for (index, step) in def.steps.iter().enumerate() {
+        // Duplicates are already reported as errors
+        if !seen[index] && keys.get(step.key.as_str()) == Some(&index) {
+            report.warning(
+                format!("steps[{index}]"),
+                format!(
+                    "Step '{}' is unreachable from '{}'",
+                    step.key, def.steps[0].key
+                ),
+            );
+        }
+    }
+}
+
+/// The step a routed step always hands control to when it succeeds, if any.
+fn unconditional_successor(step: &StepDefinition) -> Option<&str> {
+    // Signal steps only move on once a signal arrives
+    if !step.next_when.is_empty() || step.wait_for.is_some() {
+        return None;
+    }
+    match &step.parallel {
+        Some(parallel) => Some(&parallel.join),
+        None => step.otherwise.as_deref().or(step.next.as_deref()),
+    }
+}
+
+/// Reports routing cycles that no condition can leave, which only stop at
+/// the run's visit budget, and `on_error` routes back into the failing step.
+fn check_unconditional_cycles(
+    report: &mut ValidationReport,
+    def: &PipelineDefinition,
+    keys: &HashMap<&str, usize>,
+) {
+    // Each step has at most one unconditional successor, so following them
+    // from every unvisited step finds each cycle exactly once
+    let mut walked = vec![false; def.steps.len()];
+    for start in 0..def.steps.len() {
+        let mut walk = Vec::new();
+        let mut current = Some(start);
+        while let Some(index) = current.filter(|&i| !walked[i]) {
+            walked[index] = true;
+            walk.push(index);
+            current =
+                unconditional_successor(&def.steps[index]).and_then(|next| keys.get(next).copied());
+        }
+
+        let Some(cycle_start) = current.and_then(|i| walk.iter().position(|&w| w == i)) else {
+            continue;
+        };
+        let cycle = &walk[cycle_start..];
+        let route: Vec<&str> = cycle
+            .iter()
+            .chain(std::iter::once(&cycle[0]))
+            .map(|&i| def.steps[i].key.as_str())
+            .collect();
+        report.error(
+            format!("steps[{}]", cycle[0]),
+            format!("Steps {} loop unconditionally", route.join(" -> ")),
+        );
+    }
+
+    for (index, step) in def.steps.iter().enumerate() {
+        if step.on_error.as_deref() == Some(step.key.as_str()) {
+            report.warning(
+                format!("steps[{index}].on_error"),
+                format!(
+                    "Step '{}' handles its own failure by running again; use `retry` to bound the attempts",
+                    step.key
+                ),
+            );
+        }
+    }
+}
+
+/// Reports each step that sits on, or waits behind, a `depends_on` cycle.
+fn check_acyclic(
+    report: &mut ValidationReport,
+    def: &PipelineDefinition,
+    keys: &HashMap<&str, usize>,
+) {
+    // Kahn's algorithm; whatever never reaches in-degree zero is on or behind a cycle
+    let mut in_degree = vec![0usize; def.steps.len()];
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); def.steps.len()];
+    for (index, step) in def.steps.iter().enumerate() {
+        for dep in &step.depends_on {
+            if let Some(&dep_index) = keys.get(dep.as_str()) {
+                in_degree[index] += 1;
+                dependents[dep_index].push(index);
+            }
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0..def.steps.len())
+        .filter(|&i| in_degree[i] == 0)
+        .collect();
+    while let Some(index) = queue.pop_front() {
+        for &dependent in &dependents[index] {
+            in_degree[dependent] -= 1;
+            if in_degree[dependent] == 0 {
+                queue.push_back(dependent);
+            }
+        }
+    }
+
+    for (index, step) in def.steps.iter().enumerate() {
+        if in_degree[index] > 0 {
+            report.error(
+                format!("steps[{index}].depends_on"),
+                format!("Step '{}' is blocked by a dependency cycle", step.key),
+            );
+        }
+    }
+}
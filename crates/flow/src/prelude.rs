@@ -1,5 +1,5 @@
 pub use crate::{
-    FlowPipelineManager, FlowContext, StateStore, FlowError,
-    store::InMemoryStateStore,
     pipeline::PipelineDefinition,
+    store::{FileStateStore, InMemoryStateStore},
+    FlowContext, FlowError, FlowPipelineManager, StateStore,
 };
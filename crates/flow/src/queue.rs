@@ -0,0 +1,303 @@
+//! Dispatches queued runs to a pool of workers.
+//!
+//! `FlowPipelineManager::enqueue` stores a run in the state store's queue;
+//! a `RunDispatcher` claims queued runs and executes them, so bursts of work
+//! wait in the queue instead of all running at once.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use ryvus_core::{
+    environment::{Environment, EnvironmentKind},
+    prelude::ExecutionContext,
+    state::QueuedRun,
+};
+use tokio::sync::Mutex as AsyncMutex;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{debug, warn};
+
+use crate::{error::FlowError, store::StateStore, FlowPipelineManager};
+
+const DEFAULT_WORKERS: usize = 4;
+
+/// How long a claimed run stays hidden from other workers. Workers renew
+/// the lease while the run is going, so only runs of a crashed worker
+/// become visible again.
+const DEFAULT_VISIBILITY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How often idle workers look for runs that became visible on their own.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const DEFAULT_MAX_DELIVERIES: u32 = 5;
+
+/// Executes queued runs on a fixed number of workers.
+///
+/// Runs are claimed by priority, then in enqueue order. A run stays in the
+/// queue until it finished, so runs held by a worker that crashed are
+/// delivered again once their visibility timeout passes, going on from their
+/// last checkpoint when the engine has a state store; after
+/// `max_deliveries` attempts the run is stored as failed and dropped.
+pub struct RunDispatcher<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    workers: usize,
+    limits: HashMap<String, usize>,
+    visibility_timeout: Duration,
+    poll_interval: Duration,
+    max_deliveries: u32,
+    running: Mutex<Option<Running>>,
+}
+
+/// The workers of a started dispatcher.
+struct Running {
+    cancel: CancellationToken,
+    workers: TaskTracker,
+}
+
+impl<S: StateStore + 'static> RunDispatcher<S> {
+    pub fn new(manager: Arc<FlowPipelineManager<S>>) -> Self {
+        Self {
+            manager,
+            workers: DEFAULT_WORKERS,
+            limits: HashMap::new(),
+            visibility_timeout: DEFAULT_VISIBILITY_TIMEOUT,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_deliveries: DEFAULT_MAX_DELIVERIES,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Number of runs executing at once across all pipelines.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// Runs at most `limit` runs of `pipeline_key` at once; other pipelines'
+    /// runs go ahead of the ones over the limit.
+    pub fn with_concurrency_limit(mut self, pipeline_key: impl Into<String>, limit: usize) -> Self {
+        self.limits.insert(pipeline_key.into(), limit);
+        self
+    }
+
+    pub fn with_visibility_timeout(mut self, timeout: Duration) -> Self {
+        self.visibility_timeout = timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn with_max_deliveries(mut self, max_deliveries: u32) -> Self {
+        self.max_deliveries = max_deliveries;
+        self
+    }
+
+    /// Spawns the workers.
+    pub fn start(&self) -> Result<(), FlowError> {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.is_some() {
+            return Err(FlowError::Queue("Dispatcher is already running".into()));
+        }
+        if self.workers == 0 || self.visibility_timeout.is_zero() {
+            return Err(FlowError::Queue(
+                "Dispatcher needs at least one worker and a visibility timeout".into(),
+            ));
+        }
+
+        let cancel = CancellationToken::new();
+        let pool = Arc::new(Pool {
+            manager: self.manager.clone(),
+            limits: self.limits.clone(),
+            active: AsyncMutex::new(HashMap::new()),
+            visibility_timeout: self.visibility_timeout,
+            poll_interval: self.poll_interval,
+            max_deliveries: self.max_deliveries,
+            cancel: cancel.clone(),
+        });
+        let workers = TaskTracker::new();
+        for _ in 0..self.workers {
+            workers.spawn(pool.clone().work());
+        }
+        workers.close();
+
+        *running = Some(Running { cancel, workers });
+        Ok(())
+    }
+
+    /// Stops claiming runs and waits for the runs in flight. Runs still
+    /// queued stay in the store for the next start.
+    pub async fn shutdown(&self) {
+        let running = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(running) = running {
+            running.cancel.cancel();
+            running.workers.wait().await;
+        }
+    }
+}
+
+/// State shared by the workers of one dispatcher.
+struct Pool<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    limits: HashMap<String, usize>,
+    /// Runs in flight per pipeline; locked across claims so limits hold
+    active: AsyncMutex<HashMap<String, usize>>,
+    visibility_timeout: Duration,
+    poll_interval: Duration,
+    max_deliveries: u32,
+    cancel: CancellationToken,
+}
+
+impl<S: StateStore + 'static> Pool<S> {
+    async fn work(self: Arc<Self>) {
+        while !self.cancel.is_cancelled() {
+            match self.claim().await {
+                Ok(Some(run)) => {
+                    let pipeline_key = run.pipeline_key.clone();
+                    self.dispatch(run).await;
+                    self.release(&pipeline_key).await;
+                }
+                Ok(None) => {
+                    tokio::select! {
+                        _ = self.cancel.cancelled() => {}
+                        _ = self.manager.queued().notified() => {}
+                        _ = tokio::time::sleep(self.poll_interval) => {}
+                    }
+                }
+                Err(e) => {
+                    warn!("Could not claim a queued run: {}", e);
+                    tokio::select! {
+                        _ = self.cancel.cancelled() => {}
+                        _ = tokio::time::sleep(self.poll_interval) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Leases the next run of a pipeline below its concurrency limit.
+    async fn claim(&self) -> Result<Option<QueuedRun>, String> {
+        let mut active = self.active.lock().await;
+        let skip: Vec<String> = self
+            .limits
+            .iter()
+            .filter(|(key, limit)| active.get(*key).copied().unwrap_or(0) >= **limit)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let now = Utc::now();
+        let run = self
+            .manager
+            .store()
+            .claim_queued(now, now + self.visibility_timeout, &skip)
+            .await?;
+        if let Some(run) = &run {
+            *active.entry(run.pipeline_key.clone()).or_default() += 1;
+        }
+        Ok(run)
+    }
+
+    async fn release(&self, pipeline_key: &str) {
+        let mut active = self.active.lock().await;
+        if let Some(count) = active.get_mut(pipeline_key) {
+            *count = count.saturating_sub(1);
+        }
+        // A run held back by the limit may go now
+        if self.limits.contains_key(pipeline_key) {
+            self.manager.queued().notify_one();
+        }
+    }
+
+    /// Executes `run` while renewing its lease, then removes it from the queue.
+    async fn dispatch(&self, run: QueuedRun) {
+        let store = self.manager.store();
+        if run.deliveries > self.max_deliveries {
+            warn!(
+                "Giving up on run {} of '{}' after {} deliveries",
+                run.run_id, run.pipeline_key, self.max_deliveries
+            );
+            let mut ctx = ExecutionContext::new(
+                &run.pipeline_key,
+                Environment::new("local", EnvironmentKind::Local),
+            );
+            ctx.run_id = run.run_id.clone();
+            ctx.error = Some(format!(
+                "Run was delivered {} times without finishing",
+                self.max_deliveries
+            ));
+            if let Err(e) = store.save_result(&ctx.into_result()).await {
+                warn!("Could not save result of run {}: {}", run.run_id, e);
+            }
+            self.remove(&run).await;
+            return;
+        }
+
+        debug!(
+            "Dispatching run {} of '{}' (delivery {})",
+            run.run_id, run.pipeline_key, run.deliveries
+        );
+        let execution = async {
+            // A redelivered run goes on from where the previous worker left it
+            if run.deliveries > 1 {
+                if let Some(result) = self.manager.resume(&run.run_id, &run.pipeline_key).await? {
+                    return Ok(result);
+                }
+            }
+            self.manager
+                .start_as(run.run_id.clone(), &run.pipeline_key, run.input.clone())
+                .await
+        };
+        tokio::pin!(execution);
+        let renew_every = self.visibility_timeout / 2;
+        let mut renewals =
+            tokio::time::interval_at(tokio::time::Instant::now() + renew_every, renew_every);
+
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut execution => break outcome,
+                _ = renewals.tick() => {
+                    let until = Utc::now() + self.visibility_timeout;
+                    match store.renew_lease(&run.run_id, run.deliveries, until).await {
+                        Ok(true) => {}
+                        // Dropping the execution stops the run; the new holder
+                        // continues it and removes it from the queue
+                        Ok(false) => {
+                            warn!(
+                                "Run {} was delivered to another worker; stopping here",
+                                run.run_id
+                            );
+                            return;
+                        }
+                        Err(e) => warn!("Could not renew the lease of run {}: {}", run.run_id, e),
+                    }
+                }
+            }
+        };
+        match outcome {
+            Ok(result) => debug!(
+                "Queued run {} of '{}' finished: {:?}",
+                run.run_id, run.pipeline_key, result.status
+            ),
+            Err(e) => warn!(
+                "Queued run {} of '{}' failed to start: {}",
+                run.run_id, run.pipeline_key, e
+            ),
+        }
+        self.remove(&run).await;
+    }
+
+    async fn remove(&self, run: &QueuedRun) {
+        if let Err(e) = self.manager.store().delete_queued(&run.run_id).await {
+            warn!("Could not remove run {} from the queue: {}", run.run_id, e);
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use crate::{prelude::PipelineDefinition, resolver::variable::VariableResolver};
+use ryvus_core::pipeline::template::{Lookup, Template};
+use ryvus_core::prelude::pipeline::Pipeline;
 use serde_json::Value;
+use tracing::warn;
 
-/// Resolves `$VAR` and `secret:$VAR` placeholders in-place,
-/// and returns all resolved *secret values* for later masking.
+/// Resolves `$VAR` and `secret:$VAR` placeholders in-place, along with
+/// `${env.VAR}` and `${secret.VAR}` inside larger strings, and returns all
+/// resolved *secret values* for later masking. `${$.path}` placeholders are
+/// left for the engine.
 pub fn resolve_config(
     pipeline: &mut PipelineDefinition,
     resolver: &dyn VariableResolver,
@@ -17,6 +22,18 @@ pub fn resolve_config(
     secrets
 }
 
+/// Like [`resolve_config`], for a pipeline already converted for the engine.
+pub fn resolve_pipeline(pipeline: &mut Pipeline, resolver: &dyn VariableResolver) -> Vec<String> {
+    let mut secrets = Vec::new();
+
+    for step in &mut pipeline.steps {
+        resolve_value(&mut step.config, resolver, &mut secrets);
+        resolve_value(&mut step.params, resolver, &mut secrets);
+    }
+
+    secrets
+}
+
 fn resolve_value(value: &mut Value, resolver: &dyn VariableResolver, secrets: &mut Vec<String>) {
     match value {
         Value::Object(map) => {
@@ -29,6 +46,10 @@ fn resolve_value(value: &mut Value, resolver: &dyn VariableResolver, secrets: &m
                 resolve_value(v, resolver, secrets);
             }
         }
+        Value::String(s) if Template::is_template(s) => match Template::parse(s) {
+            Ok(template) => *value = render(&template, resolver, secrets),
+            Err(e) => warn!("Leaving string unresolved: {}", e),
+        },
         Value::String(s) => {
             if let Some(name) = s.strip_prefix("secret:$") {
                 if let Some(val) = resolver.resolve(name.trim()) {
@@ -44,3 +65,31 @@ fn resolve_value(value: &mut Value, resolver: &dyn VariableResolver, secrets: &m
         _ => {}
     }
 }
+
+fn render(
+    template: &Template,
+    resolver: &dyn VariableResolver,
+    secrets: &mut Vec<String>,
+) -> Value {
+    // The engine parses the result again for `${$.path}` placeholders
+    template.render_for_later(&mut |subject| {
+        let (name, secret) = match subject.split_once('.') {
+            Some(("env", name)) => (name, false),
+            Some(("secret", name)) => (name, true),
+            _ => return Lookup::Defer,
+        };
+        let Some(val) = resolver.resolve(name.trim()) else {
+            return Lookup::Missing;
+        };
+        if secret {
+            // Filters can change the text, so mask the filtered form as well
+            for placeholder in template.placeholders().filter(|p| p.subject == subject) {
+                if let Value::String(filtered) = placeholder.apply(Value::String(val.clone())) {
+                    secrets.push(filtered);
+                }
+            }
+            secrets.push(val.clone());
+        }
+        Lookup::Found(Value::String(val))
+    })
+}
@@ -1,4 +1,5 @@
 pub mod config_resolver;
 pub mod env_resolver;
 pub mod file_resolver;
+pub mod pipeline_resolver;
 pub mod variable;
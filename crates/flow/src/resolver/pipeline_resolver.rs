@@ -0,0 +1,64 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use ryvus_core::prelude::pipeline::Pipeline;
+use ryvus_engine::pipeline_resolver::PipelineResolver;
+use tracing::warn;
+
+use crate::{
+    pipeline::manager::PipelineRegistry,
+    resolver::{config_resolver::resolve_pipeline, variable::VariableResolver},
+};
+
+/// Serves sub-pipeline steps from the manager's registered definitions, and
+/// resolves the variables of every pipeline the engine runs for the flow.
+///
+/// Definitions are handed out with their placeholders intact, so that is
+/// what the engine checkpoints; the secrets resolved while preparing them
+/// are collected so results can be masked.
+pub struct RegisteredPipelines {
+    registry: PipelineRegistry,
+    resolver: Arc<dyn VariableResolver>,
+    secrets: Mutex<Vec<String>>,
+}
+
+impl RegisteredPipelines {
+    pub fn new(registry: PipelineRegistry, resolver: Arc<dyn VariableResolver>) -> Self {
+        Self {
+            registry,
+            resolver,
+            secrets: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl PipelineResolver for RegisteredPipelines {
+    async fn resolve(&self, key: &str) -> Option<Pipeline> {
+        let def = {
+            let guard = self.registry.read().unwrap_or_else(|e| e.into_inner());
+            guard.get(key).cloned()
+        }?;
+
+        Pipeline::try_from((*def).clone())
+            .map_err(|e| warn!("Sub-pipeline '{}' cannot run: {}", key, e))
+            .ok()
+    }
+
+    fn prepare(&self, pipeline: &Pipeline) -> Pipeline {
+        let mut resolved = pipeline.clone();
+        let secrets = resolve_pipeline(&mut resolved, self.resolver.as_ref());
+        self.secrets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(secrets);
+        resolved
+    }
+
+    fn secrets(&self) -> Vec<String> {
+        self.secrets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
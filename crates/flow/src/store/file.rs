@@ -0,0 +1,419 @@
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ryvus_core::{
+    action::result::{ExecutionResult, ExecutionStatus},
+    pipeline::pipeline::Pipeline,
+    state::{QueuedRun, RunCheckpoint, RunQuery, RunTimer, StateStore, STATE_STORE_VERSION},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::Mutex,
+};
+use tracing::warn;
+
+use crate::error::FlowError;
+
+const RESULTS_FILE: &str = "results.jsonl";
+const VERSION_FILE: &str = "VERSION";
+
+/// JSON-lines state store rooted at a directory.
+///
+/// Results are appended to `results.jsonl`, where the last line for a run wins.
+/// Step records are appended to `steps/<run>.jsonl`. Checkpoints, timers, queued
+/// runs and pipeline definitions are whole files replaced through a temp file and rename,
+/// so readers never see a partial write. A torn trailing line left by a crash is skipped.
+pub struct FileStateStore {
+    root: PathBuf,
+    // Serializes writers so appended lines never interleave
+    write_lock: Mutex<()>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StepLine {
+    step: String,
+    data: Value,
+}
+
+impl FileStateStore {
+    /// Opens the store at `root`, creating the directory layout if needed.
+    /// Fails if the data was written by a newer layout version.
+    pub async fn open(root: impl Into<PathBuf>) -> Result<Self, FlowError> {
+        let root = root.into();
+        for dir in ["steps", "checkpoints", "pipelines", "timers", "queue"] {
+            fs::create_dir_all(root.join(dir))
+                .await
+                .map_err(|e| FlowError::Store(format!("{}: {}", root.display(), e)))?;
+        }
+
+        let version_path = root.join(VERSION_FILE);
+        match read_optional(&version_path)
+            .await
+            .map_err(FlowError::Store)?
+        {
+            Some(bytes) => {
+                let version: u32 =
+                    String::from_utf8_lossy(&bytes)
+                        .trim()
+                        .parse()
+                        .map_err(|_| {
+                            FlowError::Store(format!("{}: invalid version", version_path.display()))
+                        })?;
+                if version > STATE_STORE_VERSION {
+                    return Err(FlowError::Store(format!(
+                        "{} uses store version {}, newer than supported version {}",
+                        root.display(),
+                        version,
+                        STATE_STORE_VERSION
+                    )));
+                }
+            }
+            None => write_atomic(&version_path, STATE_STORE_VERSION.to_string().as_bytes())
+                .await
+                .map_err(FlowError::Store)?,
+        }
+
+        Ok(Self {
+            root,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, dir: &str, id: &str, extension: &str) -> PathBuf {
+        self.root
+            .join(dir)
+            .join(format!("{}.{}", file_name(id), extension))
+    }
+
+    async fn load_pipeline_file(&self, path: &Path) -> Result<Option<Pipeline>, String> {
+        match read_optional(path).await? {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            None => Ok(None),
+        }
+    }
+
+    /// Queued runs in claim order.
+    async fn read_queue(&self) -> Result<Vec<QueuedRun>, String> {
+        let dir = self.root.join("queue");
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+        let mut runs = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            // A run deleted since the directory was listed is no longer queued
+            let Some(bytes) = read_optional(&path).await? else {
+                continue;
+            };
+            runs.push(
+                serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?,
+            );
+        }
+        runs.sort_by(QueuedRun::claim_order);
+        Ok(runs)
+    }
+
+    /// Latest result per run, replaying the results log.
+    async fn read_results(&self) -> Result<HashMap<String, ExecutionResult>, String> {
+        let lines: Vec<ExecutionResult> = read_lines(&self.root.join(RESULTS_FILE)).await?;
+        Ok(lines.into_iter().map(|r| (r.run_id.clone(), r)).collect())
+    }
+}
+
+#[async_trait]
+impl StateStore for FileStateStore {
+    async fn save_result(&self, result: &ExecutionResult) -> Result<(), String> {
+        let _guard = self.write_lock.lock().await;
+        append_line(&self.root.join(RESULTS_FILE), result).await
+    }
+
+    async fn load_result(&self, run_id: &str) -> Result<Option<ExecutionResult>, String> {
+        Ok(self.read_results().await?.remove(run_id))
+    }
+
+    async fn update_step(&self, run_id: &str, step_name: &str, data: Value) -> Result<(), String> {
+        let line = StepLine {
+            step: step_name.to_string(),
+            data,
+        };
+        let _guard = self.write_lock.lock().await;
+        append_line(&self.path("steps", run_id, "jsonl"), &line).await
+    }
+
+    async fn load_steps(&self, run_id: &str) -> Result<HashMap<String, Value>, String> {
+        let lines: Vec<StepLine> = read_lines(&self.path("steps", run_id, "jsonl")).await?;
+        Ok(lines.into_iter().map(|l| (l.step, l.data)).collect())
+    }
+
+    async fn save_pipeline(&self, pipeline: &Pipeline) -> Result<(), String> {
+        let bytes = serde_json::to_vec(pipeline).map_err(|e| e.to_string())?;
+        let _guard = self.write_lock.lock().await;
+        write_atomic(&self.path("pipelines", &pipeline.key, "json"), &bytes).await
+    }
+
+    async fn load_pipeline(&self, key: &str) -> Result<Option<Pipeline>, String> {
+        self.load_pipeline_file(&self.path("pipelines", key, "json"))
+            .await
+    }
+
+    async fn list_pipelines(&self) -> Result<Vec<String>, String> {
+        let dir = self.root.join("pipelines");
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+        let mut keys = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(Pipeline { key, .. }) = self.load_pipeline_file(&path).await? {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &RunCheckpoint) -> Result<(), String> {
+        let bytes = serde_json::to_vec(checkpoint).map_err(|e| e.to_string())?;
+        let _guard = self.write_lock.lock().await;
+        write_atomic(
+            &self.path("checkpoints", &checkpoint.run_id, "json"),
+            &bytes,
+        )
+        .await
+    }
+
+    async fn load_checkpoint(&self, run_id: &str) -> Result<Option<RunCheckpoint>, String> {
+        match read_optional(&self.path("checkpoints", run_id, "json")).await? {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_results(&self, query: &RunQuery) -> Result<Vec<ExecutionResult>, String> {
+        Ok(query.apply(self.read_results().await?.into_values()))
+    }
+
+    async fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        let _guard = self.write_lock.lock().await;
+        let (expired, kept): (Vec<_>, Vec<_>) =
+            self.read_results().await?.into_values().partition(|r| {
+                r.metrics.finished_at < cutoff && r.status != ExecutionStatus::Suspended
+            });
+
+        // Rewriting the log also compacts superseded lines
+        let mut log = Vec::new();
+        for result in &kept {
+            serde_json::to_writer(&mut log, result).map_err(|e| e.to_string())?;
+            log.push(b'\n');
+        }
+        write_atomic(&self.root.join(RESULTS_FILE), &log).await?;
+
+        for result in &expired {
+            remove_if_exists(&self.path("steps", &result.run_id, "jsonl")).await?;
+            remove_if_exists(&self.path("checkpoints", &result.run_id, "json")).await?;
+        }
+        Ok(expired.len())
+    }
+
+    async fn save_timer(&self, timer: &RunTimer) -> Result<(), String> {
+        let bytes = serde_json::to_vec(timer).map_err(|e| e.to_string())?;
+        let _guard = self.write_lock.lock().await;
+        write_atomic(&self.path("timers", &timer.run_id, "json"), &bytes).await
+    }
+
+    async fn due_timers(&self, now: DateTime<Utc>) -> Result<Vec<RunTimer>, String> {
+        let dir = self.root.join("timers");
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+        let mut due = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| e.to_string())? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            // A timer deleted since the directory was listed is no longer due
+            let Some(bytes) = read_optional(&path).await? else {
+                continue;
+            };
+            let timer: RunTimer =
+                serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+            if timer.fire_at <= now {
+                due.push(timer);
+            }
+        }
+        due.sort_by_key(|t| t.fire_at);
+        Ok(due)
+    }
+
+    async fn delete_timer(&self, run_id: &str) -> Result<(), String> {
+        let _guard = self.write_lock.lock().await;
+        remove_if_exists(&self.path("timers", run_id, "json")).await
+    }
+
+    async fn save_queued(&self, run: &QueuedRun) -> Result<(), String> {
+        let bytes = serde_json::to_vec(run).map_err(|e| e.to_string())?;
+        let _guard = self.write_lock.lock().await;
+        write_atomic(&self.path("queue", &run.run_id, "json"), &bytes).await
+    }
+
+    async fn claim_queued(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        skip: &[String],
+    ) -> Result<Option<QueuedRun>, String> {
+        // Held across read and write so two workers never lease the same run
+        let _guard = self.write_lock.lock().await;
+        let Some(mut run) = self
+            .read_queue()
+            .await?
+            .into_iter()
+            .find(|r| r.visible_at <= now && !skip.contains(&r.pipeline_key))
+        else {
+            return Ok(None);
+        };
+        run.visible_at = lease_until;
+        run.deliveries += 1;
+        let bytes = serde_json::to_vec(&run).map_err(|e| e.to_string())?;
+        write_atomic(&self.path("queue", &run.run_id, "json"), &bytes).await?;
+        Ok(Some(run))
+    }
+
+    async fn renew_lease(
+        &self,
+        run_id: &str,
+        deliveries: u32,
+        until: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        let path = self.path("queue", run_id, "json");
+        let _guard = self.write_lock.lock().await;
+        let Some(bytes) = read_optional(&path).await? else {
+            return Ok(false);
+        };
+        let mut run: QueuedRun =
+            serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if run.deliveries != deliveries {
+            return Ok(false);
+        }
+        run.visible_at = until;
+        let bytes = serde_json::to_vec(&run).map_err(|e| e.to_string())?;
+        write_atomic(&path, &bytes).await?;
+        Ok(true)
+    }
+
+    async fn list_queued(&self) -> Result<Vec<QueuedRun>, String> {
+        self.read_queue().await
+    }
+
+    async fn delete_queued(&self, run_id: &str) -> Result<(), String> {
+        let _guard = self.write_lock.lock().await;
+        remove_if_exists(&self.path("queue", run_id, "json")).await
+    }
+}
+
+/// Percent-encodes everything but `[A-Za-z0-9_-]` so ids map to distinct, safe file names.
+fn file_name(id: &str) -> String {
+    id.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+async fn append_line<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let mut line = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    line.push(b'\n');
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    // Terminate a line torn by an earlier crash so it cannot swallow this one
+    let len = file.metadata().await.map_err(|e| e.to_string())?.len();
+    if len > 0 {
+        file.seek(SeekFrom::End(-1))
+            .await
+            .map_err(|e| e.to_string())?;
+        if file.read_u8().await.map_err(|e| e.to_string())? != b'\n' {
+            line.insert(0, b'\n');
+        }
+    }
+
+    file.write_all(&line).await.map_err(|e| e.to_string())?;
+    file.sync_data().await.map_err(|e| e.to_string())
+}
+
+async fn read_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, String> {
+    let Some(bytes) = read_optional(path).await? else {
+        return Ok(Vec::new());
+    };
+
+    let mut values = Vec::new();
+    for (number, line) in bytes.split(|b| *b == b'\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_slice(line) {
+            Ok(value) => values.push(value),
+            Err(e) => warn!("Skipping {}:{}: {}", path.display(), number + 1, e),
+        }
+    }
+    Ok(values)
+}
+
+async fn read_optional(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    match fs::read(path).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+pub(crate) async fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp)
+        .await
+        .map_err(|e| format!("{}: {}", tmp.display(), e))?;
+    file.write_all(bytes).await.map_err(|e| e.to_string())?;
+    file.sync_all().await.map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path)
+        .await
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+async fn remove_if_exists(path: &Path) -> Result<(), String> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(format!("{}: {}", path.display(), e)),
+        _ => Ok(()),
+    }
+}
@@ -1,3 +1,9 @@
-mod trait_impl;
+mod file;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
-pub use trait_impl::{StateStore, InMemoryStateStore};
+pub(crate) use file::write_atomic;
+pub use file::FileStateStore;
+pub use ryvus_core::state::{InMemoryStateStore, StateStore};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStateStore;
@@ -0,0 +1,425 @@
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use ryvus_core::{
+    action::result::ExecutionResult,
+    pipeline::pipeline::Pipeline,
+    state::{QueuedRun, RunCheckpoint, RunQuery, RunTimer, StateStore, STATE_STORE_VERSION},
+};
+use serde_json::Value;
+
+use crate::error::FlowError;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        run_id       TEXT PRIMARY KEY,
+        pipeline_key TEXT,
+        status       TEXT NOT NULL,
+        started_at   INTEGER NOT NULL,
+        finished_at  INTEGER NOT NULL,
+        result       TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS runs_pipeline_status ON runs (pipeline_key, status);
+    CREATE TABLE IF NOT EXISTS steps (
+        run_id TEXT NOT NULL,
+        step   TEXT NOT NULL,
+        data   TEXT NOT NULL,
+        PRIMARY KEY (run_id, step)
+    );
+    CREATE TABLE IF NOT EXISTS checkpoints (
+        run_id     TEXT PRIMARY KEY,
+        checkpoint TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS pipelines (
+        key      TEXT PRIMARY KEY,
+        pipeline TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS timers (
+        run_id  TEXT PRIMARY KEY,
+        fire_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS timers_fire_at ON timers (fire_at);
+    CREATE TABLE IF NOT EXISTS queue (
+        run_id       TEXT PRIMARY KEY,
+        pipeline_key TEXT NOT NULL,
+        priority     INTEGER NOT NULL,
+        enqueued_at  INTEGER NOT NULL,
+        visible_at   INTEGER NOT NULL,
+        run          TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS queue_claim_order ON queue (priority DESC, enqueued_at, run_id);
+";
+
+/// Embedded SQLite state store.
+///
+/// Every write is a single statement or transaction, so a crash never leaves a
+/// half-written run behind. Queries run on the calling task; they are local and short.
+pub struct SqliteStateStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStateStore {
+    /// Opens or creates the database file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FlowError> {
+        let conn = Connection::open(path).map_err(store_error)?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(store_error)?;
+        Self::init(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self, FlowError> {
+        Self::init(Connection::open_in_memory().map_err(store_error)?)
+    }
+
+    /// Creates the schema, tracking the layout version in `user_version`.
+    fn init(conn: Connection) -> Result<Self, FlowError> {
+        let version: u32 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(store_error)?;
+        if version > STATE_STORE_VERSION {
+            return Err(FlowError::Store(format!(
+                "Database uses store version {}, newer than supported version {}",
+                version, STATE_STORE_VERSION
+            )));
+        }
+
+        conn.execute_batch(SCHEMA).map_err(store_error)?;
+        conn.pragma_update(None, "user_version", STATE_STORE_VERSION)
+            .map_err(store_error)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut conn = self.conn.lock().map_err(|e| e.to_string())?;
+        f(&mut conn).map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl StateStore for SqliteStateStore {
+    async fn save_result(&self, result: &ExecutionResult) -> Result<(), String> {
+        let json = serde_json::to_string(result).map_err(|e| e.to_string())?;
+        let status = status_name(result)?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO runs (run_id, pipeline_key, status, started_at, finished_at, result)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    result.run_id,
+                    result.pipeline_key,
+                    status,
+                    result.metrics.started_at.timestamp_millis(),
+                    result.metrics.finished_at.timestamp_millis(),
+                    json
+                ],
+            )
+            .map(|_| ())
+        })
+    }
+
+    async fn load_result(&self, run_id: &str) -> Result<Option<ExecutionResult>, String> {
+        let json: Option<String> = self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT result FROM runs WHERE run_id = ?1",
+                [run_id],
+                |row| row.get(0),
+            )
+            .optional()
+        })?;
+        json.map(|j| serde_json::from_str(&j).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn update_step(&self, run_id: &str, step_name: &str, data: Value) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO steps (run_id, step, data) VALUES (?1, ?2, ?3)",
+                params![run_id, step_name, data.to_string()],
+            )
+            .map(|_| ())
+        })
+    }
+
+    async fn load_steps(&self, run_id: &str) -> Result<HashMap<String, Value>, String> {
+        let rows: Vec<(String, String)> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT step, data FROM steps WHERE run_id = ?1")?;
+            let rows = stmt.query_map([run_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect()
+        })?;
+        rows.into_iter()
+            .map(|(step, data)| {
+                Ok((
+                    step,
+                    serde_json::from_str(&data).map_err(|e| e.to_string())?,
+                ))
+            })
+            .collect()
+    }
+
+    async fn save_pipeline(&self, pipeline: &Pipeline) -> Result<(), String> {
+        let json = serde_json::to_string(pipeline).map_err(|e| e.to_string())?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO pipelines (key, pipeline) VALUES (?1, ?2)",
+                params![pipeline.key, json],
+            )
+            .map(|_| ())
+        })
+    }
+
+    async fn load_pipeline(&self, key: &str) -> Result<Option<Pipeline>, String> {
+        let json: Option<String> = self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT pipeline FROM pipelines WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()
+        })?;
+        json.map(|j| serde_json::from_str(&j).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn list_pipelines(&self) -> Result<Vec<String>, String> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT key FROM pipelines ORDER BY key")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &RunCheckpoint) -> Result<(), String> {
+        let json = serde_json::to_string(checkpoint).map_err(|e| e.to_string())?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO checkpoints (run_id, checkpoint) VALUES (?1, ?2)",
+                params![checkpoint.run_id, json],
+            )
+            .map(|_| ())
+        })
+    }
+
+    async fn load_checkpoint(&self, run_id: &str) -> Result<Option<RunCheckpoint>, String> {
+        let json: Option<String> = self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT checkpoint FROM checkpoints WHERE run_id = ?1",
+                [run_id],
+                |row| row.get(0),
+            )
+            .optional()
+        })?;
+        json.map(|j| serde_json::from_str(&j).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn list_results(&self, query: &RunQuery) -> Result<Vec<ExecutionResult>, String> {
+        let status = query
+            .status
+            .as_ref()
+            .map(|s| serde_json::to_value(s).map_err(|e| e.to_string()))
+            .transpose()?
+            .and_then(|v| v.as_str().map(str::to_string));
+        let limit = query.limit.map_or(-1, |l| l as i64);
+
+        let rows: Vec<String> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT result FROM runs
+                 WHERE (?1 IS NULL OR pipeline_key = ?1) AND (?2 IS NULL OR status = ?2)
+                 ORDER BY started_at DESC LIMIT ?3",
+            )?;
+            let rows =
+                stmt.query_map(params![query.pipeline_key, status, limit], |row| row.get(0))?;
+            rows.collect()
+        })?;
+        rows.iter()
+            .map(|j| serde_json::from_str(j).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    async fn prune(&self, cutoff: DateTime<Utc>) -> Result<usize, String> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let expired =
+                "SELECT run_id FROM runs WHERE finished_at < ?1 AND status != 'Suspended'";
+            let cutoff = cutoff.timestamp_millis();
+            tx.execute(
+                &format!("DELETE FROM steps WHERE run_id IN ({expired})"),
+                [cutoff],
+            )?;
+            tx.execute(
+                &format!("DELETE FROM checkpoints WHERE run_id IN ({expired})"),
+                [cutoff],
+            )?;
+            let removed = tx.execute(
+                "DELETE FROM runs WHERE finished_at < ?1 AND status != 'Suspended'",
+                [cutoff],
+            )?;
+            tx.commit()?;
+            Ok(removed)
+        })
+    }
+
+    async fn save_timer(&self, timer: &RunTimer) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO timers (run_id, fire_at) VALUES (?1, ?2)",
+                params![timer.run_id, timer.fire_at.timestamp_millis()],
+            )
+            .map(|_| ())
+        })
+    }
+
+    async fn due_timers(&self, now: DateTime<Utc>) -> Result<Vec<RunTimer>, String> {
+        let rows: Vec<(String, i64)> = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT run_id, fire_at FROM timers WHERE fire_at <= ?1 ORDER BY fire_at",
+            )?;
+            let rows = stmt.query_map([now.timestamp_millis()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+            rows.collect()
+        })?;
+        rows.into_iter()
+            .map(|(run_id, fire_at)| {
+                DateTime::from_timestamp_millis(fire_at)
+                    .map(|fire_at| RunTimer::new(run_id, fire_at))
+                    .ok_or_else(|| format!("Invalid timer timestamp {}", fire_at))
+            })
+            .collect()
+    }
+
+    async fn delete_timer(&self, run_id: &str) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM timers WHERE run_id = ?1", [run_id])
+                .map(|_| ())
+        })
+    }
+
+    async fn save_queued(&self, run: &QueuedRun) -> Result<(), String> {
+        let json = serde_json::to_string(run).map_err(|e| e.to_string())?;
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO queue (run_id, pipeline_key, priority, enqueued_at, visible_at, run)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    run.run_id,
+                    run.pipeline_key,
+                    run.priority,
+                    run.enqueued_at.timestamp_micros(),
+                    run.visible_at.timestamp_micros(),
+                    json
+                ],
+            )
+            .map(|_| ())
+        })
+    }
+
+    async fn claim_queued(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+        skip: &[String],
+    ) -> Result<Option<QueuedRun>, String> {
+        let skip = serde_json::to_string(skip).map_err(|e| e.to_string())?;
+        let claimed: Option<String> = self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let json: Option<String> = tx
+                .query_row(
+                    "SELECT run FROM queue
+                     WHERE visible_at <= ?1 AND pipeline_key NOT IN (SELECT value FROM json_each(?2))
+                     ORDER BY priority DESC, enqueued_at, run_id LIMIT 1",
+                    params![now.timestamp_micros(), skip],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(json) = json else {
+                return Ok(None);
+            };
+            let mut run: QueuedRun = serde_json::from_str(&json)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+            run.visible_at = lease_until;
+            run.deliveries += 1;
+            let json = serde_json::to_string(&run)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+            tx.execute(
+                "UPDATE queue SET visible_at = ?2, run = ?3 WHERE run_id = ?1",
+                params![run.run_id, lease_until.timestamp_micros(), json],
+            )?;
+            tx.commit()?;
+            Ok(Some(json))
+        })?;
+        claimed
+            .map(|j| serde_json::from_str(&j).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    async fn renew_lease(
+        &self,
+        run_id: &str,
+        deliveries: u32,
+        until: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let json: Option<String> = tx
+                .query_row("SELECT run FROM queue WHERE run_id = ?1", [run_id], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            let Some(json) = json else {
+                return Ok(false);
+            };
+            let mut run: QueuedRun = serde_json::from_str(&json)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+            if run.deliveries != deliveries {
+                return Ok(false);
+            }
+            run.visible_at = until;
+            let json = serde_json::to_string(&run)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+            tx.execute(
+                "UPDATE queue SET visible_at = ?2, run = ?3 WHERE run_id = ?1",
+                params![run_id, until.timestamp_micros(), json],
+            )?;
+            tx.commit()?;
+            Ok(true)
+        })
+    }
+
+    async fn list_queued(&self) -> Result<Vec<QueuedRun>, String> {
+        let rows: Vec<String> = self.with_conn(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT run FROM queue ORDER BY priority DESC, enqueued_at, run_id")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })?;
+        rows.iter()
+            .map(|j| serde_json::from_str(j).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    async fn delete_queued(&self, run_id: &str) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute("DELETE FROM queue WHERE run_id = ?1", [run_id])
+                .map(|_| ())
+        })
+    }
+}
+
+fn status_name(result: &ExecutionResult) -> Result<String, String> {
+    match serde_json::to_value(&result.status).map_err(|e| e.to_string())? {
+        Value::String(name) => Ok(name),
+        other => Err(format!("Unexpected status encoding: {}", other)),
+    }
+}
+
+fn store_error(e: rusqlite::Error) -> FlowError {
+    FlowError::Store(e.to_string())
+}
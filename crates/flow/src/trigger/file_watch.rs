@@ -0,0 +1,460 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::{sync::mpsc, task::JoinHandle, time::Instant};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{debug, info, warn};
+
+use super::Trigger;
+use crate::{
+    error::FlowError,
+    store::{write_atomic, StateStore},
+    FlowPipelineManager,
+};
+
+/// Quiet period that groups the events of one burst of writes.
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How long a file must stay unchanged before it counts as complete.
+const DEFAULT_STABLE_FOR: Duration = Duration::from_secs(2);
+
+/// Starts a registered pipeline for every file created or modified in the
+/// watched directories, once the file stopped changing.
+///
+/// The payload is `{ path, size, mtime }`. Patterns are globs matched against
+/// the path relative to the watched directory (`*.csv`, `partners/**/*.csv`);
+/// without patterns every file matches. Each version of a file, by size and
+/// modification time, starts one run; a ledger file keeps that record across
+/// restarts, and files that arrived while the trigger was down are picked up
+/// when it starts.
+pub struct FileWatchTrigger<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    pipeline_key: String,
+    dirs: Vec<PathBuf>,
+    patterns: Vec<String>,
+    recursive: bool,
+    debounce: Duration,
+    stable_for: Duration,
+    ledger: Option<PathBuf>,
+    running: Mutex<Option<Running>>,
+}
+
+/// The watch loop of a started trigger and the runs it started.
+struct Running {
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+    runs: TaskTracker,
+}
+
+impl<S: StateStore + 'static> FileWatchTrigger<S> {
+    pub fn new(
+        manager: Arc<FlowPipelineManager<S>>,
+        pipeline_key: impl Into<String>,
+        dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            manager,
+            pipeline_key: pipeline_key.into(),
+            dirs: vec![dir.into()],
+            patterns: Vec::new(),
+            recursive: false,
+            debounce: DEFAULT_DEBOUNCE,
+            stable_for: DEFAULT_STABLE_FOR,
+            ledger: None,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Watches `dir` as well.
+    pub fn watch(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push(dir.into());
+        self
+    }
+
+    /// Only starts runs for files matching `glob`; patterns add up.
+    pub fn pattern(mut self, glob: impl Into<String>) -> Self {
+        self.patterns.push(glob.into());
+        self
+    }
+
+    /// Watches subdirectories too.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Waits until a file kept its size and modification time for `duration`,
+    /// so half-written files do not start runs.
+    pub fn with_stable_for(mut self, duration: Duration) -> Self {
+        self.stable_for = duration;
+        self
+    }
+
+    /// Records processed files in `path`, a JSON file, so a restart does not
+    /// start their runs again. Without a ledger the record lives in memory.
+    pub fn with_ledger(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ledger = Some(path.into());
+        self
+    }
+
+    fn globs(&self) -> Result<Option<GlobSet>, FlowError> {
+        if self.patterns.is_empty() {
+            return Ok(None);
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.patterns {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| FlowError::Trigger(format!("Invalid pattern '{}': {}", pattern, e)))?;
+            builder.add(glob);
+        }
+        builder
+            .build()
+            .map(Some)
+            .map_err(|e| FlowError::Trigger(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl<S: StateStore + 'static> Trigger for FileWatchTrigger<S> {
+    async fn start(&self) -> Result<(), FlowError> {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.is_some() {
+            return Err(FlowError::Trigger(format!(
+                "File watch for '{}' is already running",
+                self.pipeline_key
+            )));
+        }
+
+        // Events carry absolute paths; keep ours comparable
+        let dirs = self
+            .dirs
+            .iter()
+            .map(|dir| {
+                fs::canonicalize(dir)
+                    .map_err(|e| FlowError::Trigger(format!("{}: {}", dir.display(), e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let files = WatchedFiles {
+            dirs,
+            globs: self.globs()?,
+            recursive: self.recursive,
+        };
+        let ledger = Ledger::load(self.ledger.clone())?;
+
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => {
+                    let _ = event_tx.send(event.paths);
+                }
+                Err(e) => warn!("File watch error: {}", e),
+            })
+            .map_err(|e| FlowError::Trigger(e.to_string()))?;
+        let mode = match self.recursive {
+            true => RecursiveMode::Recursive,
+            false => RecursiveMode::NonRecursive,
+        };
+        for dir in &files.dirs {
+            watcher
+                .watch(dir, mode)
+                .map_err(|e| FlowError::Trigger(format!("{}: {}", dir.display(), e)))?;
+        }
+
+        let cancel = CancellationToken::new();
+        let runs = TaskTracker::new();
+        let watch = WatchLoop {
+            manager: self.manager.clone(),
+            pipeline_key: self.pipeline_key.clone(),
+            files,
+            ledger: Arc::new(tokio::sync::Mutex::new(ledger)),
+            debounce: self.debounce,
+            stable_for: self.stable_for,
+            pending: HashMap::new(),
+            cancel: cancel.clone(),
+            runs: runs.clone(),
+        };
+        let task = tokio::spawn(async move {
+            // Watching stops when the loop drops the watcher
+            let _watcher = watcher;
+            watch.run(event_rx).await;
+        });
+
+        *running = Some(Running { cancel, task, runs });
+        Ok(())
+    }
+
+    /// Stops watching and waits for the runs in flight.
+    async fn shutdown(&self) -> Result<(), FlowError> {
+        let running = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let Some(running) = running else {
+            return Ok(());
+        };
+
+        running.cancel.cancel();
+        running
+            .task
+            .await
+            .map_err(|e| FlowError::Trigger(e.to_string()))?;
+        running.runs.close();
+        running.runs.wait().await;
+        Ok(())
+    }
+}
+
+/// Which files under the watched directories start runs.
+struct WatchedFiles {
+    dirs: Vec<PathBuf>,
+    globs: Option<GlobSet>,
+    recursive: bool,
+}
+
+impl WatchedFiles {
+    fn matches(&self, path: &Path) -> bool {
+        let Some(relative) = self.dirs.iter().find_map(|dir| path.strip_prefix(dir).ok()) else {
+            return false;
+        };
+        if !self.recursive && relative.components().count() != 1 {
+            return false;
+        }
+        self.globs
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(relative))
+    }
+
+    /// Matching files already in the directories, sorted.
+    fn scan(&self) -> BTreeSet<PathBuf> {
+        let mut found = BTreeSet::new();
+        let mut dirs = self.dirs.clone();
+        while let Some(dir) = dirs.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Could not scan {}: {}", dir.display(), e);
+                    continue;
+                }
+            };
+            for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+                if path.is_dir() {
+                    if self.recursive {
+                        dirs.push(path);
+                    }
+                } else if self.matches(&path) {
+                    found.insert(path);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Size and modification time that identify one version of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileVersion {
+    size: u64,
+    modified: DateTime<Utc>,
+}
+
+impl FileVersion {
+    /// Version of the regular file at `path`; `None` once it is gone.
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+        Some(Self {
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH).into(),
+        })
+    }
+}
+
+/// File versions that already started a run.
+struct Ledger {
+    path: Option<PathBuf>,
+    processed: HashMap<PathBuf, FileVersion>,
+    /// Versions whose run is starting; recorded once the run started
+    starting: HashMap<PathBuf, FileVersion>,
+}
+
+impl Ledger {
+    /// Reads the ledger at `path`, forgetting files that no longer exist.
+    fn load(path: Option<PathBuf>) -> Result<Self, FlowError> {
+        let mut processed: HashMap<PathBuf, FileVersion> = match &path {
+            Some(path) if path.exists() => fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+                .map_err(|e| FlowError::Trigger(format!("{}: {}", path.display(), e)))?,
+            _ => HashMap::new(),
+        };
+        processed.retain(|file, _| file.exists());
+        Ok(Self {
+            path,
+            processed,
+            starting: HashMap::new(),
+        })
+    }
+
+    fn contains(&self, file: &Path, version: &FileVersion) -> bool {
+        self.processed.get(file) == Some(version) || self.starting.get(file) == Some(version)
+    }
+
+    fn starting(&mut self, file: PathBuf, version: FileVersion) {
+        self.starting.insert(file, version);
+    }
+
+    /// Forgets a version whose run could not start, so it is picked up again.
+    fn abandon(&mut self, file: &Path) {
+        self.starting.remove(file);
+    }
+
+    async fn record(&mut self, file: PathBuf, version: FileVersion) {
+        self.starting.remove(&file);
+        self.processed.insert(file, version);
+        let Some(path) = &self.path else {
+            return;
+        };
+        let saved = match serde_json::to_vec_pretty(&self.processed) {
+            Ok(bytes) => write_atomic(path, &bytes).await,
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = saved {
+            warn!("Could not save file watch ledger: {}", e);
+        }
+    }
+}
+
+/// A changed file waiting to stay unchanged for `stable_for`.
+struct Pending {
+    version: FileVersion,
+    since: Instant,
+}
+
+struct WatchLoop<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    pipeline_key: String,
+    files: WatchedFiles,
+    ledger: Arc<tokio::sync::Mutex<Ledger>>,
+    debounce: Duration,
+    stable_for: Duration,
+    pending: HashMap<PathBuf, Pending>,
+    cancel: CancellationToken,
+    runs: TaskTracker,
+}
+
+impl<S: StateStore + 'static> WatchLoop<S> {
+    async fn run(mut self, mut events: mpsc::UnboundedReceiver<Vec<PathBuf>>) {
+        // Files that arrived while the trigger was down
+        for path in self.files.scan() {
+            self.observe(path).await;
+        }
+
+        let mut ticks = tokio::time::interval((self.stable_for / 4).max(Duration::from_millis(10)));
+        loop {
+            tokio::select! {
+                _ = self.cancel.cancelled() => break,
+                Some(paths) = events.recv() => {
+                    let mut changed: BTreeSet<PathBuf> = paths.into_iter().collect();
+                    tokio::time::sleep(self.debounce).await;
+                    while let Ok(more) = events.try_recv() {
+                        changed.extend(more);
+                    }
+                    changed.retain(|path| self.files.matches(path));
+                    for path in changed {
+                        self.observe(path).await;
+                    }
+                }
+                _ = ticks.tick() => self.fire_stable().await,
+            }
+        }
+    }
+
+    /// Starts (or restarts) the stability wait of a new file version.
+    async fn observe(&mut self, path: PathBuf) {
+        let Some(version) = FileVersion::of(&path) else {
+            self.pending.remove(&path);
+            return;
+        };
+        if self.ledger.lock().await.contains(&path, &version) {
+            return;
+        }
+        match self.pending.get(&path) {
+            Some(pending) if pending.version == version => {}
+            _ => {
+                debug!("Waiting for {} to settle", path.display());
+                self.pending.insert(
+                    path,
+                    Pending {
+                        version,
+                        since: Instant::now(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Starts runs for the pending files that stayed unchanged long enough.
+    async fn fire_stable(&mut self) {
+        let paths: BTreeSet<PathBuf> = self.pending.keys().cloned().collect();
+        for path in paths {
+            let Some(version) = FileVersion::of(&path) else {
+                self.pending.remove(&path);
+                continue;
+            };
+            let pending = &self.pending[&path];
+            if pending.version != version {
+                self.observe(path).await;
+                continue;
+            }
+            if pending.since.elapsed() < self.stable_for {
+                continue;
+            }
+
+            self.pending.remove(&path);
+            self.ledger.lock().await.starting(path.clone(), version);
+            self.fire(path, version);
+        }
+    }
+
+    /// Starts the run in the background; the version is recorded in the
+    /// ledger once `start` returns; otherwise it is picked up again when the
+    /// file changes or the trigger restarts.
+    fn fire(&self, path: PathBuf, version: FileVersion) {
+        info!("Starting '{}' for {}", self.pipeline_key, path.display());
+        let manager = self.manager.clone();
+        let ledger = self.ledger.clone();
+        let pipeline_key = self.pipeline_key.clone();
+        let payload = json!({
+            "path": path.to_string_lossy(),
+            "size": version.size,
+            "mtime": version.modified.to_rfc3339(),
+        });
+        self.runs.spawn(async move {
+            match manager.start(&pipeline_key, payload).await {
+                Ok(_) => ledger.lock().await.record(path, version).await,
+                Err(e) => {
+                    warn!("File watch run of '{}' failed: {}", pipeline_key, e);
+                    ledger.lock().await.abandon(&path);
+                }
+            }
+        });
+    }
+}
@@ -1,5 +1,16 @@
-use async_trait::async_trait;
 use crate::error::FlowError;
+use async_trait::async_trait;
+
+mod file_watch;
+mod schedule;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+pub use chrono_tz::Tz;
+pub use file_watch::FileWatchTrigger;
+pub use schedule::{CronTrigger, IntervalTrigger, MisfirePolicy, OverlapPolicy};
+#[cfg(feature = "webhook")]
+pub use webhook::{ResponseMode, WebhookRoute, WebhookSignature, WebhookTrigger};
 
 /// Minimal trigger trait; implementations can call into FlowPipelineManager.
 #[async_trait]
@@ -13,6 +24,10 @@ pub struct NoopTrigger;
 
 #[async_trait]
 impl Trigger for NoopTrigger {
-    async fn start(&self) -> Result<(), FlowError> { Ok(()) }
-    async fn shutdown(&self) -> Result<(), FlowError> { Ok(()) }
+    async fn start(&self) -> Result<(), FlowError> {
+        Ok(())
+    }
+    async fn shutdown(&self) -> Result<(), FlowError> {
+        Ok(())
+    }
 }
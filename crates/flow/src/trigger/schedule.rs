@@ -0,0 +1,422 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use ryvus_core::prelude::RunQuery;
+use serde_json::Value;
+use tokio::{sync::Semaphore, task::JoinHandle};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{debug, info, warn};
+
+use super::Trigger;
+use crate::{error::FlowError, store::StateStore, FlowPipelineManager};
+
+/// How late a fire may start before it counts as missed.
+const MISFIRE_GRACE: Duration = Duration::from_secs(1);
+
+/// What to do about fire times that passed while the trigger was not
+/// running, or while it was held up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MisfirePolicy {
+    /// Drop missed fires and wait for the next one
+    #[default]
+    Skip,
+    /// Start one run for all missed fires, then continue on schedule
+    CatchUpOnce,
+}
+
+/// What to do when a fire comes while an earlier run is still going.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Start the run anyway
+    #[default]
+    Allow,
+    /// Drop the fire
+    Skip,
+    /// Start the run once the earlier ones finished, in fire order
+    Queue,
+}
+
+/// Starts a registered pipeline on a cron schedule, evaluated in a timezone.
+///
+/// Expressions take five fields (`min hour day month weekday`), or six and
+/// seven with leading seconds and trailing years. Five-field weekdays count
+/// like crontab (`0` or `7` is Sunday); six and seven fields use `1` for Sunday.
+pub struct CronTrigger<S: StateStore> {
+    schedule: cron::Schedule,
+    timezone: Tz,
+    runner: ScheduledRuns<S>,
+}
+
+impl<S: StateStore + 'static> CronTrigger<S> {
+    pub fn new(
+        manager: Arc<FlowPipelineManager<S>>,
+        pipeline_key: impl Into<String>,
+        expression: &str,
+    ) -> Result<Self, FlowError> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let normalized = match fields.as_slice() {
+            [minute, hour, day, month, weekday] => format!(
+                "0 {} {} {} {} {}",
+                minute,
+                hour,
+                day,
+                month,
+                crontab_weekdays(weekday)
+            ),
+            _ => expression.to_string(),
+        };
+        let schedule = cron::Schedule::from_str(&normalized).map_err(|e| {
+            FlowError::Trigger(format!("Invalid cron expression '{}': {}", expression, e))
+        })?;
+
+        Ok(Self {
+            schedule,
+            timezone: Tz::UTC,
+            runner: ScheduledRuns::new(manager, pipeline_key.into()),
+        })
+    }
+
+    /// Evaluates the expression in `timezone` instead of UTC.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Payload every run starts with; `{}` by default.
+    pub fn with_input(mut self, input: Value) -> Self {
+        self.runner.input = input;
+        self
+    }
+
+    pub fn with_misfire(mut self, policy: MisfirePolicy) -> Self {
+        self.runner.misfire = policy;
+        self
+    }
+
+    pub fn with_overlap(mut self, policy: OverlapPolicy) -> Self {
+        self.runner.overlap = policy;
+        self
+    }
+
+    /// First fire time after `after`; `None` once the schedule is exhausted.
+    pub fn next_fire(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        next_cron_fire(&self.schedule, self.timezone, after)
+    }
+}
+
+#[async_trait]
+impl<S: StateStore + 'static> Trigger for CronTrigger<S> {
+    async fn start(&self) -> Result<(), FlowError> {
+        let schedule = self.schedule.clone();
+        let timezone = self.timezone;
+        self.runner
+            .start(move |after| next_cron_fire(&schedule, timezone, after))
+    }
+
+    async fn shutdown(&self) -> Result<(), FlowError> {
+        self.runner.shutdown().await
+    }
+}
+
+/// Maps a crontab weekday field (0-7, Sunday is 0 and 7) onto the cron
+/// crate's 1-7 with Sunday as 1. Numeric ranges and steps are expanded into a
+/// list; names, `*` and `*/n` already mean the same in both and are kept.
+fn crontab_weekdays(field: &str) -> String {
+    let days = |item: &str| -> Option<Vec<u32>> {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse().ok().filter(|s| *s > 0)?),
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+            // `n/step` runs to the end of the week
+            None if step > 1 => (range.parse().ok()?, 7),
+            None => (range.parse().ok()?, range.parse().ok()?),
+        };
+        (start <= end && end <= 7).then(|| (start..=end).step_by(step).collect())
+    };
+
+    field
+        .split(',')
+        .map(|item| match days(item) {
+            Some(days) => {
+                let mut days: Vec<u32> = days.into_iter().map(|day| day % 7 + 1).collect();
+                days.sort_unstable();
+                days.dedup();
+                days.iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }
+            // Left for the cron parser to accept or report
+            None => item.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn next_cron_fire(
+    schedule: &cron::Schedule,
+    timezone: Tz,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    schedule
+        .after(&after.with_timezone(&timezone))
+        .next()
+        .map(|fire| fire.with_timezone(&Utc))
+}
+
+/// Starts a registered pipeline at a fixed interval.
+///
+/// The first run comes one interval after the pipeline's newest stored run,
+/// or after the trigger starts when there is none.
+pub struct IntervalTrigger<S: StateStore> {
+    every: Duration,
+    runner: ScheduledRuns<S>,
+}
+
+impl<S: StateStore + 'static> IntervalTrigger<S> {
+    pub fn new(
+        manager: Arc<FlowPipelineManager<S>>,
+        pipeline_key: impl Into<String>,
+        every: Duration,
+    ) -> Result<Self, FlowError> {
+        if every.is_zero() {
+            return Err(FlowError::Trigger(
+                "Interval must be longer than zero".into(),
+            ));
+        }
+        Ok(Self {
+            every,
+            runner: ScheduledRuns::new(manager, pipeline_key.into()),
+        })
+    }
+
+    /// Payload every run starts with; `{}` by default.
+    pub fn with_input(mut self, input: Value) -> Self {
+        self.runner.input = input;
+        self
+    }
+
+    pub fn with_misfire(mut self, policy: MisfirePolicy) -> Self {
+        self.runner.misfire = policy;
+        self
+    }
+
+    pub fn with_overlap(mut self, policy: OverlapPolicy) -> Self {
+        self.runner.overlap = policy;
+        self
+    }
+}
+
+#[async_trait]
+impl<S: StateStore + 'static> Trigger for IntervalTrigger<S> {
+    async fn start(&self) -> Result<(), FlowError> {
+        let every = self.every;
+        self.runner.start(move |after| Some(after + every))
+    }
+
+    async fn shutdown(&self) -> Result<(), FlowError> {
+        self.runner.shutdown().await
+    }
+}
+
+/// Fires runs of one pipeline on a schedule; shared by the scheduled triggers.
+struct ScheduledRuns<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    pipeline_key: String,
+    input: Value,
+    misfire: MisfirePolicy,
+    overlap: OverlapPolicy,
+    running: Mutex<Option<Running>>,
+}
+
+/// The schedule loop of a started trigger and the runs it started.
+struct Running {
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+    runs: TaskTracker,
+}
+
+impl<S: StateStore + 'static> ScheduledRuns<S> {
+    fn new(manager: Arc<FlowPipelineManager<S>>, pipeline_key: String) -> Self {
+        Self {
+            manager,
+            pipeline_key,
+            input: Value::Object(Default::default()),
+            misfire: MisfirePolicy::default(),
+            overlap: OverlapPolicy::default(),
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Spawns the schedule loop; `next_fire` gives the first fire time after an instant.
+    fn start(
+        &self,
+        next_fire: impl Fn(DateTime<Utc>) -> Option<DateTime<Utc>> + Send + 'static,
+    ) -> Result<(), FlowError> {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.is_some() {
+            return Err(FlowError::Trigger(format!(
+                "Trigger for '{}' is already running",
+                self.pipeline_key
+            )));
+        }
+
+        let cancel = CancellationToken::new();
+        let runs = TaskTracker::new();
+        let fire = Fire {
+            manager: self.manager.clone(),
+            pipeline_key: self.pipeline_key.clone(),
+            input: self.input.clone(),
+            overlap: self.overlap,
+            slot: Arc::new(Semaphore::new(1)),
+            cancel: cancel.clone(),
+            runs: runs.clone(),
+        };
+        let task = tokio::spawn(schedule_loop(fire, self.misfire, next_fire));
+
+        *running = Some(Running { cancel, task, runs });
+        Ok(())
+    }
+
+    /// Stops firing and waits for the runs in flight; queued runs are dropped.
+    async fn shutdown(&self) -> Result<(), FlowError> {
+        let running = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let Some(running) = running else {
+            return Ok(());
+        };
+
+        running.cancel.cancel();
+        running
+            .task
+            .await
+            .map_err(|e| FlowError::Trigger(e.to_string()))?;
+        running.runs.close();
+        running.runs.wait().await;
+        Ok(())
+    }
+}
+
+async fn schedule_loop<S: StateStore + 'static>(
+    fire: Fire<S>,
+    misfire: MisfirePolicy,
+    next_fire: impl Fn(DateTime<Utc>) -> Option<DateTime<Utc>>,
+) {
+    // Fires missed while the trigger was down count from the newest stored run
+    let last_run = fire
+        .manager
+        .store()
+        .list_results(&RunQuery::new().pipeline(&fire.pipeline_key).limit(1))
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Could not look up the last run of '{}': {}",
+                fire.pipeline_key, e
+            );
+            Vec::new()
+        })
+        .first()
+        .map(|run| run.metrics.started_at);
+    let mut next = next_fire(last_run.unwrap_or_else(Utc::now));
+
+    while let Some(at) = next {
+        let now = Utc::now();
+        if now - at > chrono::Duration::from_std(MISFIRE_GRACE).unwrap_or_default() {
+            match misfire {
+                MisfirePolicy::Skip => info!("Skipping missed runs of '{}'", fire.pipeline_key),
+                MisfirePolicy::CatchUpOnce => {
+                    info!("Catching up on missed runs of '{}'", fire.pipeline_key);
+                    fire.fire();
+                }
+            }
+            next = next_fire(now);
+            continue;
+        }
+
+        let wait = (at - now).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = fire.cancel.cancelled() => return,
+            _ = tokio::time::sleep(wait) => {}
+        }
+        fire.fire();
+        next = next_fire(at);
+    }
+    debug!("Schedule of '{}' has no more fire times", fire.pipeline_key);
+}
+
+/// Starts runs under the trigger's overlap policy.
+struct Fire<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    pipeline_key: String,
+    input: Value,
+    overlap: OverlapPolicy,
+    /// Held by the run in flight under `Skip` and `Queue`
+    slot: Arc<Semaphore>,
+    cancel: CancellationToken,
+    runs: TaskTracker,
+}
+
+impl<S: StateStore + 'static> Fire<S> {
+    fn fire(&self) {
+        let manager = self.manager.clone();
+        let pipeline_key = self.pipeline_key.clone();
+        let input = self.input.clone();
+
+        match self.overlap {
+            OverlapPolicy::Allow => {
+                self.runs.spawn(async move {
+                    start_run(&manager, &pipeline_key, input).await;
+                });
+            }
+            OverlapPolicy::Skip => match self.slot.clone().try_acquire_owned() {
+                Ok(permit) => {
+                    self.runs.spawn(async move {
+                        start_run(&manager, &pipeline_key, input).await;
+                        drop(permit);
+                    });
+                }
+                Err(_) => info!(
+                    "Skipping run of '{}': previous run still going",
+                    pipeline_key
+                ),
+            },
+            OverlapPolicy::Queue => {
+                let slot = self.slot.clone();
+                let cancel = self.cancel.clone();
+                self.runs.spawn(async move {
+                    let permit = tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        Ok(permit) = slot.acquire_owned() => permit,
+                    };
+                    start_run(&manager, &pipeline_key, input).await;
+                    drop(permit);
+                });
+            }
+        }
+    }
+}
+
+async fn start_run<S: StateStore>(
+    manager: &FlowPipelineManager<S>,
+    pipeline_key: &str,
+    input: Value,
+) {
+    match manager.start(pipeline_key, input).await {
+        Ok(result) => debug!(
+            "Scheduled run {} of '{}' finished: {:?}",
+            result.run_id, pipeline_key, result.status
+        ),
+        Err(e) => warn!("Scheduled run of '{}' failed to start: {}", pipeline_key, e),
+    }
+}
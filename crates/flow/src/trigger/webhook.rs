@@ -0,0 +1,483 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::IntoFuture,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use ryvus_core::utils::id::generate_id;
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use tokio::task::JoinHandle;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{debug, warn};
+
+use super::Trigger;
+use crate::{error::FlowError, store::StateStore, FlowPipelineManager};
+
+/// Largest request body a webhook accepts.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Headers that never reach the payload, as it is stored with the run.
+const HIDDEN_HEADERS: [&str; 2] = ["authorization", "cookie"];
+
+/// How a route answers once it accepted a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseMode {
+    /// Waits for the run and responds with its `ExecutionResult`
+    #[default]
+    Sync,
+    /// Responds `202 Accepted` with the `run_id` right away; the result is
+    /// polled from `GET /runs/{run_id}`
+    Async,
+}
+
+/// HMAC-SHA256 signature a route requires on the raw request body.
+#[derive(Clone)]
+pub enum WebhookSignature {
+    /// Hex digest of the body in `header`, after `prefix`
+    HmacSha256 {
+        header: String,
+        prefix: String,
+        secret: String,
+    },
+    /// Stripe's `Stripe-Signature: t=<unix time>,v1=<hex>` over `<t>.<body>`;
+    /// signatures older than `tolerance` are rejected
+    Stripe { secret: String, tolerance: Duration },
+}
+
+impl WebhookSignature {
+    /// Hex digest in `header`, with nothing in front of it.
+    pub fn hmac_sha256(header: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self::HmacSha256 {
+            header: header.into().to_lowercase(),
+            prefix: String::new(),
+            secret: secret.into(),
+        }
+    }
+
+    /// GitHub's `X-Hub-Signature-256: sha256=<hex>`.
+    pub fn github(secret: impl Into<String>) -> Self {
+        Self::HmacSha256 {
+            header: "x-hub-signature-256".into(),
+            prefix: "sha256=".into(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Stripe's signature scheme with its default five-minute tolerance.
+    pub fn stripe(secret: impl Into<String>) -> Self {
+        Self::Stripe {
+            secret: secret.into(),
+            tolerance: Duration::from_secs(300),
+        }
+    }
+
+    fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), String> {
+        match self {
+            Self::HmacSha256 {
+                header,
+                prefix,
+                secret,
+            } => {
+                let signature = header_value(headers, header)?;
+                let digest = signature
+                    .strip_prefix(prefix.as_str())
+                    .ok_or_else(|| format!("{} does not start with '{}'", header, prefix))?;
+                verify_digest(secret, &[body], digest)
+            }
+            Self::Stripe { secret, tolerance } => {
+                let signature = header_value(headers, "stripe-signature")?;
+                let mut timestamp = None;
+                let mut digests = Vec::new();
+                for part in signature.split(',') {
+                    match part.trim().split_once('=') {
+                        Some(("t", t)) => timestamp = t.parse::<i64>().ok(),
+                        Some(("v1", digest)) => digests.push(digest),
+                        _ => {}
+                    }
+                }
+                let timestamp = timestamp.ok_or("stripe-signature has no timestamp")?;
+                let age = (Utc::now().timestamp() - timestamp).unsigned_abs();
+                if age > tolerance.as_secs() {
+                    return Err("stripe-signature timestamp is outside the tolerance".into());
+                }
+
+                let signed = [timestamp.to_string().as_bytes(), b".", body].concat();
+                digests
+                    .into_iter()
+                    .find(|digest| verify_digest(secret, &[&signed], digest).is_ok())
+                    .map(|_| ())
+                    .ok_or_else(|| "stripe-signature does not match".into())
+            }
+        }
+    }
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str, String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| format!("Missing {} header", name))
+}
+
+/// Checks the hex `digest` against the HMAC of `parts` in constant time.
+fn verify_digest(secret: &str, parts: &[&[u8]], digest: &str) -> Result<(), String> {
+    let expected = hex::decode(digest.trim()).map_err(|_| "Signature is not hex".to_string())?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| e.to_string())?;
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(&expected)
+        .map_err(|_| "Signature does not match".to_string())
+}
+
+/// A `POST` path that starts a registered pipeline.
+#[derive(Clone)]
+pub struct WebhookRoute {
+    pub path: String,
+    pub pipeline_key: String,
+    pub mode: ResponseMode,
+    pub signature: Option<WebhookSignature>,
+}
+
+impl WebhookRoute {
+    pub fn new(path: impl Into<String>, pipeline_key: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            pipeline_key: pipeline_key.into(),
+            mode: ResponseMode::default(),
+            signature: None,
+        }
+    }
+
+    pub fn respond(mut self, mode: ResponseMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Rejects requests without a valid `signature` with `401 Unauthorized`.
+    pub fn signature(mut self, signature: WebhookSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+}
+
+/// Embedded HTTP server that starts registered pipelines from webhooks.
+///
+/// The request becomes the run payload as `{ body, headers, query }`: the body
+/// as JSON when it parses, otherwise as text, header names in lower case, and
+/// without the `Authorization` and `Cookie` headers.
+///
+/// Results of async runs are served from `GET /runs/{run_id}` only once a
+/// `poll_token` is set, and only to requests bearing it.
+pub struct WebhookTrigger<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    addr: SocketAddr,
+    routes: Vec<WebhookRoute>,
+    poll_token: Option<String>,
+    running: Mutex<Option<Server<S>>>,
+}
+
+/// A started server and the state its handlers share.
+struct Server<S: StateStore> {
+    addr: SocketAddr,
+    cancel: CancellationToken,
+    task: JoinHandle<std::io::Result<()>>,
+    hooks: Arc<Webhooks<S>>,
+}
+
+impl<S: StateStore + 'static> WebhookTrigger<S> {
+    /// Serves on `addr`; port 0 picks a free port, see `local_addr`.
+    pub fn new(manager: Arc<FlowPipelineManager<S>>, addr: SocketAddr) -> Self {
+        Self {
+            manager,
+            addr,
+            routes: Vec::new(),
+            poll_token: None,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Adds a route; `start` fails unless its path is a static path starting
+    /// with `/`, used by no other route and outside `/runs/`.
+    pub fn route(mut self, route: WebhookRoute) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Serves `GET /runs/{run_id}` to requests with `Authorization: Bearer <token>`.
+    pub fn poll_token(mut self, token: impl Into<String>) -> Self {
+        self.poll_token = Some(token.into());
+        self
+    }
+
+    /// Address the server listens on while it runs.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        running.as_ref().map(|server| server.addr)
+    }
+
+    /// Rejects the paths axum would panic on when building the router.
+    fn check_routes(&self) -> Result<(), FlowError> {
+        let mut paths = HashSet::new();
+        for route in &self.routes {
+            let path = route.path.as_str();
+            let problem = if !path.starts_with('/') {
+                "does not start with '/'"
+            } else if path.contains(['{', '}', '*']) {
+                "must be static, without '{', '}' or '*'"
+            } else if path.starts_with("/runs/") {
+                "is reserved for polling runs"
+            } else if !paths.insert(path) {
+                "is used by another route"
+            } else {
+                continue;
+            };
+            return Err(FlowError::Trigger(format!(
+                "Webhook path '{}' {}",
+                path, problem
+            )));
+        }
+        Ok(())
+    }
+
+    fn router(&self, hooks: Arc<Webhooks<S>>) -> Router {
+        let mut router = Router::new();
+        if self.poll_token.is_some() {
+            router = router.route("/runs/{run_id}", get(poll::<S>));
+        }
+        for (index, route) in self.routes.iter().enumerate() {
+            router = router.route(
+                &route.path,
+                post(
+                    move |State(hooks): State<Arc<Webhooks<S>>>, request: Request| async move {
+                        hooks.handle(index, request).await
+                    },
+                ),
+            );
+        }
+        router.with_state(hooks)
+    }
+}
+
+#[async_trait]
+impl<S: StateStore + 'static> Trigger for WebhookTrigger<S> {
+    async fn start(&self) -> Result<(), FlowError> {
+        if self.local_addr().is_some() {
+            return Err(FlowError::Trigger(
+                "Webhook server is already running".into(),
+            ));
+        }
+        self.check_routes()?;
+
+        let listener = tokio::net::TcpListener::bind(self.addr)
+            .await
+            .map_err(|e| FlowError::Trigger(format!("Could not bind {}: {}", self.addr, e)))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| FlowError::Trigger(e.to_string()))?;
+
+        let hooks = Arc::new(Webhooks {
+            manager: self.manager.clone(),
+            routes: self.routes.clone(),
+            poll_token: self.poll_token.clone(),
+            in_flight: Mutex::new(HashSet::new()),
+            runs: TaskTracker::new(),
+        });
+        let app = self.router(hooks.clone());
+        let cancel = CancellationToken::new();
+        let task = tokio::spawn(
+            axum::serve(listener, app)
+                .with_graceful_shutdown(cancel.clone().cancelled_owned())
+                .into_future(),
+        );
+        debug!("Webhook server listening on {}", addr);
+
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        *running = Some(Server {
+            addr,
+            cancel,
+            task,
+            hooks,
+        });
+        Ok(())
+    }
+
+    /// Stops accepting requests, then waits for the requests and async runs in flight.
+    async fn shutdown(&self) -> Result<(), FlowError> {
+        let server = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let Some(server) = server else {
+            return Ok(());
+        };
+
+        server.cancel.cancel();
+        server
+            .task
+            .await
+            .map_err(|e| FlowError::Trigger(e.to_string()))?
+            .map_err(|e| FlowError::Trigger(e.to_string()))?;
+        server.hooks.runs.close();
+        server.hooks.runs.wait().await;
+        Ok(())
+    }
+}
+
+struct Webhooks<S: StateStore> {
+    manager: Arc<FlowPipelineManager<S>>,
+    routes: Vec<WebhookRoute>,
+    poll_token: Option<String>,
+    /// Async runs accepted and not finished yet
+    in_flight: Mutex<HashSet<String>>,
+    runs: TaskTracker,
+}
+
+impl<S: StateStore + 'static> Webhooks<S> {
+    async fn handle(self: Arc<Self>, index: usize, request: Request) -> Response {
+        let route = &self.routes[index];
+        let (parts, body) = request.into_parts();
+        let body = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+            Ok(body) => body,
+            Err(e) => return error(StatusCode::PAYLOAD_TOO_LARGE, e.to_string()),
+        };
+
+        if let Some(signature) = &route.signature {
+            if let Err(message) = signature.verify(&parts.headers, &body) {
+                warn!("Rejected webhook for {}: {}", route.path, message);
+                return error(StatusCode::UNAUTHORIZED, message);
+            }
+        }
+        if self.manager.get(&route.pipeline_key).is_none() {
+            return error(
+                StatusCode::NOT_FOUND,
+                format!("Pipeline not found: {}", route.pipeline_key),
+            );
+        }
+
+        let query = Query::<HashMap<String, String>>::try_from_uri(&parts.uri)
+            .map(|Query(query)| query)
+            .unwrap_or_default();
+        let headers: Map<String, Value> = parts
+            .headers
+            .iter()
+            .filter(|(name, _)| !HIDDEN_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), json!(value.to_str().ok()?))))
+            .collect();
+        let body = match serde_json::from_slice(&body) {
+            Ok(body) => body,
+            Err(_) if body.is_empty() => Value::Null,
+            Err(_) => Value::String(String::from_utf8_lossy(&body).into_owned()),
+        };
+        let payload = json!({ "body": body, "headers": headers, "query": query });
+
+        match route.mode {
+            ResponseMode::Sync => match self.manager.start(&route.pipeline_key, payload).await {
+                Ok(result) => Json(result).into_response(),
+                Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            },
+            ResponseMode::Async => {
+                let run_id = generate_id("run");
+                self.in_flight
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(run_id.clone());
+
+                let hooks = self.clone();
+                let pipeline_key = route.pipeline_key.clone();
+                let id = run_id.clone();
+                self.runs.spawn(async move {
+                    if let Err(e) = hooks.manager.start_as(&id, &pipeline_key, payload).await {
+                        warn!("Webhook run {} of '{}' failed: {}", id, pipeline_key, e);
+                    }
+                    hooks
+                        .in_flight
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .remove(&id);
+                });
+
+                let mut accepted = json!({ "run_id": run_id });
+                if self.poll_token.is_some() {
+                    accepted["status_url"] = json!(format!("/runs/{}", run_id));
+                }
+                (StatusCode::ACCEPTED, Json(accepted)).into_response()
+            }
+        }
+    }
+}
+
+/// Result of an async webhook run; `202` while it is still going. Only runs
+/// of pipelines behind async routes can be looked up, with the poll token.
+async fn poll<S: StateStore + 'static>(
+    State(hooks): State<Arc<Webhooks<S>>>,
+    Path(run_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let authorized = header_value(&headers, "authorization")
+        .ok()
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .zip(hooks.poll_token.as_deref())
+        .is_some_and(|(given, token)| tokens_match(given, token));
+    if !authorized {
+        return error(StatusCode::UNAUTHORIZED, "Missing or wrong poll token");
+    }
+
+    let running = hooks
+        .in_flight
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&run_id);
+    if running {
+        return (
+            StatusCode::ACCEPTED,
+            Json(json!({ "run_id": run_id, "status": "Running" })),
+        )
+            .into_response();
+    }
+
+    let result = match hooks.manager.store().load_result(&run_id).await {
+        Ok(result) => result,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+    let pollable = |key: &str| {
+        hooks
+            .routes
+            .iter()
+            .any(|r| r.mode == ResponseMode::Async && r.pipeline_key == key)
+    };
+    match result {
+        Some(result) if result.pipeline_key.as_deref().is_some_and(pollable) => {
+            Json(result).into_response()
+        }
+        _ => error(StatusCode::NOT_FOUND, format!("Run not found: {}", run_id)),
+    }
+}
+
+/// Compares tokens without stopping at the first differing byte.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
@@ -0,0 +1,32 @@
+//! Actions and resolvers shared by the integration tests.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error};
+use ryvus_flow::resolver::variable::VariableResolver;
+
+/// Succeeds with its resolved params as the output.
+#[derive(Clone)]
+pub struct EchoAction;
+
+#[async_trait]
+impl Action for EchoAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Ok(ActionResult::success(ctx.input.clone().unwrap_or_default()))
+    }
+
+    fn key(&self) -> &str {
+        "test/echo"
+    }
+}
+
+/// Resolves `env.*` and `secret.*` names from a fixed map.
+pub struct MapResolver(pub HashMap<&'static str, &'static str>);
+
+impl VariableResolver for MapResolver {
+    fn resolve(&self, key: &str) -> Option<String> {
+        self.0.get(key).map(|v| v.to_string())
+    }
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::EchoAction;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error, ExecutionStatus};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+};
+use serde_json::json;
+
+#[derive(Clone)]
+struct FailAction;
+
+#[async_trait]
+impl Action for FailAction {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Err(Error::Action("quota exceeded".into()))
+    }
+
+    fn key(&self) -> &str {
+        "test/fail"
+    }
+}
+
+const PROVISION: &str = r#"
+key: provision
+steps:
+  - key: bucket
+    action: test/echo
+    params: { name: "$.payload.name" }
+    compensate: test/echo
+    next: vm
+  - key: vm
+    action: test/fail
+"#;
+
+#[tokio::test]
+async fn compensates_steps_declared_in_yaml() {
+    // Failed runs keep their steps and compensations through the engine's checkpoints
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(EchoAction)
+            .with_action(FailAction)
+            .with_state_store(Arc::new(InMemoryStateStore::default())),
+    );
+    let def = PipelineLoader::from_str_with_format(PROVISION, PipelineFormat::Yaml).unwrap();
+    manager.try_register(def).unwrap();
+
+    let result = manager
+        .start("provision", json!({ "name": "logs" }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Compensated);
+    assert!(result.error.unwrap().contains("quota exceeded"));
+
+    let compensation = &result.compensations[0];
+    assert_eq!(compensation.key, "bucket");
+    assert_eq!(
+        compensation.output,
+        Some(json!({ "input": { "name": "logs" }, "output": { "name": "logs" } }))
+    );
+}
+
+#[tokio::test]
+async fn compensates_when_a_step_names_an_unregistered_action() {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(EchoAction)
+            .with_state_store(Arc::new(InMemoryStateStore::default())),
+    );
+    let def = PipelineLoader::from_str_with_format(
+        &PROVISION.replace("test/fail", "cloud/vm"),
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+    manager.try_register(def).unwrap();
+
+    let result = manager
+        .start("provision", json!({ "name": "logs" }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Compensated);
+    assert!(result.error.unwrap().contains("cloud/vm"));
+    assert_eq!(result.compensations[0].key, "bucket");
+}
@@ -0,0 +1,70 @@
+use ryvus_core::prelude::pipeline::{ExecutionMode, Pipeline};
+use ryvus_flow::prelude::*;
+use serde_json::json;
+
+fn definition(value: serde_json::Value) -> PipelineDefinition {
+    serde_json::from_value(value).expect("valid definition")
+}
+
+#[test]
+fn converts_dag_definition() {
+    let def = definition(json!({
+        "key": "dag",
+        "mode": "dag",
+        "max_concurrency": 3,
+        "steps": [
+            { "key": "a", "action": "noop" },
+            { "key": "b", "action": "noop", "depends_on": ["a"] }
+        ]
+    }));
+
+    let pipeline = Pipeline::try_from(def).unwrap();
+    assert_eq!(pipeline.mode, ExecutionMode::Dag);
+    assert_eq!(pipeline.max_concurrency, Some(3));
+    assert_eq!(pipeline.steps[1].depends_on, vec!["a".to_string()]);
+}
+
+#[test]
+fn rejects_dependency_cycles() {
+    let def = definition(json!({
+        "key": "dag",
+        "mode": "dag",
+        "steps": [
+            { "key": "a", "action": "noop", "depends_on": ["b"] },
+            { "key": "b", "action": "noop", "depends_on": ["a"] }
+        ]
+    }));
+
+    let err = Pipeline::try_from(def).err().unwrap();
+    assert!(err.contains("cycle"), "{err}");
+}
+
+#[test]
+fn rejects_missing_dependencies() {
+    let def = definition(json!({
+        "key": "dag",
+        "steps": [{ "key": "a", "action": "noop", "depends_on": ["ghost"] }]
+    }));
+
+    let err = Pipeline::try_from(def).err().unwrap();
+    assert!(err.contains("ghost"), "{err}");
+}
+
+#[test]
+fn carries_retry_config_into_step() {
+    let def = definition(json!({
+        "key": "retry",
+        "steps": [{
+            "key": "a",
+            "action": "noop",
+            "retry": { "max_attempts": 4, "delay": 250, "backoff": "exponential", "jitter": true, "retry_on": ["system"] }
+        }]
+    }));
+
+    let pipeline = Pipeline::try_from(def).unwrap();
+    let policy = pipeline.steps[0].retry.as_ref().unwrap();
+    assert_eq!(policy.max_attempts, 4);
+    assert_eq!(policy.delay_ms, 250);
+    assert!(policy.jitter);
+    assert_eq!(policy.retry_on, vec![ryvus_core::error::ErrorKind::System]);
+}
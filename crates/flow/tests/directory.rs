@@ -0,0 +1,111 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{DirectoryWatcher, PipelineDirectory, ReloadEvent},
+    prelude::*,
+};
+
+fn manager() -> FlowPipelineManager<InMemoryStateStore> {
+    FlowPipelineManager::new(InMemoryStateStore::default(), Engine::default())
+}
+
+fn definition(key: &str, action: &str) -> String {
+    format!("key: {key}\nsteps:\n  - key: only\n    action: {action}\n")
+}
+
+async fn next(watcher: &mut DirectoryWatcher) -> ReloadEvent {
+    tokio::time::timeout(Duration::from_secs(5), watcher.next_event())
+        .await
+        .expect("reload event")
+        .expect("watcher running")
+}
+
+fn write(path: &Path, content: &str) {
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn load_all_registers_valid_files_and_reports_invalid_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    write(&dir.path().join("a.yaml"), &definition("a", "noop"));
+    write(
+        &dir.path().join("b.json"),
+        "{ \"key\": \"b\", \"steps\": [] }",
+    );
+    write(&dir.path().join("notes.txt"), "ignored");
+
+    let manager = manager();
+    let events = PipelineDirectory::new(dir.path())
+        .load_all(&manager)
+        .unwrap();
+
+    assert_eq!(manager.keys(), ["a"]);
+    assert!(matches!(&events[0], ReloadEvent::Loaded { key, .. } if key == "a"));
+    assert!(matches!(&events[1], ReloadEvent::Failed { error, .. } if error.contains("no steps")));
+}
+
+#[tokio::test]
+async fn watch_swaps_definitions_and_keeps_last_good_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("a.yaml");
+    write(&file, &definition("a", "v1"));
+
+    let manager = Arc::new(manager());
+    let mut watcher = PipelineDirectory::new(dir.path())
+        .watch(manager.clone())
+        .unwrap();
+    assert!(matches!(
+        next(&mut watcher).await,
+        ReloadEvent::Loaded { .. }
+    ));
+
+    write(&file, &definition("a", "v2"));
+    assert!(matches!(
+        next(&mut watcher).await,
+        ReloadEvent::Loaded { .. }
+    ));
+    assert_eq!(manager.get("a").unwrap().steps[0].action, "v2");
+
+    // A broken edit is reported and the previous version stays active
+    write(&file, "key: a\nsteps: [");
+    assert!(matches!(
+        next(&mut watcher).await,
+        ReloadEvent::Failed { .. }
+    ));
+    assert_eq!(manager.get("a").unwrap().steps[0].action, "v2");
+
+    std::fs::remove_file(&file).unwrap();
+    assert!(matches!(next(&mut watcher).await, ReloadEvent::Removed { key, .. } if key == "a"));
+    assert!(manager.get("a").is_none());
+}
+
+#[tokio::test]
+async fn duplicate_keys_stay_with_the_first_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("a.yaml");
+    let second = dir.path().join("b.yaml");
+    write(&first, &definition("a", "v1"));
+    write(&second, &definition("a", "v2"));
+
+    let manager = Arc::new(manager());
+    let mut watcher = PipelineDirectory::new(dir.path())
+        .watch(manager.clone())
+        .unwrap();
+    assert!(matches!(next(&mut watcher).await, ReloadEvent::Loaded { key, .. } if key == "a"));
+    assert!(matches!(
+        next(&mut watcher).await,
+        ReloadEvent::Failed { error, .. } if error.contains("already defined")
+    ));
+    assert_eq!(manager.get("a").unwrap().steps[0].action, "v1");
+
+    // Removing the rejected copy leaves the owner's pipeline registered
+    std::fs::remove_file(&second).unwrap();
+    write(&first, &definition("a", "v3"));
+    assert!(matches!(next(&mut watcher).await, ReloadEvent::Loaded { key, .. } if key == "a"));
+    assert_eq!(manager.get("a").unwrap().steps[0].action, "v3");
+
+    std::fs::remove_file(&first).unwrap();
+    assert!(matches!(next(&mut watcher).await, ReloadEvent::Removed { key, .. } if key == "a"));
+    assert!(manager.get("a").is_none());
+}
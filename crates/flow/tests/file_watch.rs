@@ -0,0 +1,162 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+    trigger::{FileWatchTrigger, Trigger},
+};
+use serde_json::{json, Value};
+
+/// Records the params of every run.
+#[derive(Clone, Default)]
+struct Record {
+    seen: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait]
+impl Action for Record {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let input = ctx.input.clone().unwrap_or_default();
+        self.seen.lock().unwrap().push(input);
+        Ok(ActionResult::success(json!({})))
+    }
+
+    fn key(&self) -> &str {
+        "test/record"
+    }
+}
+
+const IMPORT: &str = r#"
+key: import
+steps:
+  - key: record
+    action: test/record
+    params:
+      path: "$.payload.path"
+      size: "$.payload.size"
+      mtime: "$.payload.mtime"
+"#;
+
+fn trigger(record: &Record, dir: &Path, ledger: &Path) -> FileWatchTrigger<InMemoryStateStore> {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(record.clone()),
+    );
+    manager
+        .try_register(PipelineLoader::from_str_with_format(IMPORT, PipelineFormat::Yaml).unwrap())
+        .unwrap();
+
+    FileWatchTrigger::new(Arc::new(manager), "import", dir)
+        .pattern("*.csv")
+        .with_debounce(Duration::from_millis(20))
+        .with_stable_for(Duration::from_millis(100))
+        .with_ledger(ledger)
+}
+
+fn seen_files(record: &Record) -> Vec<String> {
+    let mut files: Vec<String> = record
+        .seen
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|params| {
+            let path = params["path"].as_str().unwrap();
+            Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into()
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[tokio::test]
+async fn matching_files_start_one_run_once_they_settle() {
+    let dir = tempfile::tempdir().unwrap();
+    let inbox = dir.path().join("inbox");
+    std::fs::create_dir(&inbox).unwrap();
+    let ledger = dir.path().join("ledger.json");
+
+    let record = Record::default();
+    let watch = trigger(&record, &inbox, &ledger);
+    watch.start().await.unwrap();
+    assert!(watch.start().await.is_err());
+
+    // A file written in pieces starts a single run for its final version
+    std::fs::write(inbox.join("orders.csv"), "id\n").unwrap();
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    std::fs::write(inbox.join("orders.csv"), "id\n1\n2\n").unwrap();
+    std::fs::write(inbox.join("notes.txt"), "skip me").unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert_eq!(seen_files(&record), ["orders.csv"]);
+    let params = record.seen.lock().unwrap()[0].clone();
+    assert_eq!(params["size"], 7);
+    assert!(params["mtime"].as_str().unwrap().contains('T'));
+
+    // A later change is a new version
+    std::fs::write(inbox.join("orders.csv"), "id\n3\n").unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    watch.shutdown().await.unwrap();
+    assert_eq!(seen_files(&record), ["orders.csv", "orders.csv"]);
+}
+
+#[tokio::test]
+async fn the_ledger_keeps_restarts_from_firing_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let inbox = dir.path().join("inbox");
+    std::fs::create_dir(&inbox).unwrap();
+    let ledger = dir.path().join("ledger.json");
+    std::fs::write(inbox.join("a.csv"), "a").unwrap();
+
+    // Files already there when the trigger starts are picked up
+    let record = Record::default();
+    let watch = trigger(&record, &inbox, &ledger);
+    watch.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    watch.shutdown().await.unwrap();
+    assert_eq!(seen_files(&record), ["a.csv"]);
+
+    // Only the file that arrived while it was down starts a run after a restart
+    std::fs::write(inbox.join("b.csv"), "b").unwrap();
+    let record = Record::default();
+    let watch = trigger(&record, &inbox, &ledger);
+    watch.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    watch.shutdown().await.unwrap();
+    assert_eq!(seen_files(&record), ["b.csv"]);
+}
+
+#[tokio::test]
+async fn files_whose_run_did_not_start_are_not_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let inbox = dir.path().join("inbox");
+    std::fs::create_dir(&inbox).unwrap();
+    let ledger = dir.path().join("ledger.json");
+    std::fs::write(inbox.join("a.csv"), "a").unwrap();
+
+    // The pipeline is not registered yet, so the run cannot start
+    let manager = FlowPipelineManager::new(InMemoryStateStore::default(), Engine::default());
+    let watch = FileWatchTrigger::new(Arc::new(manager), "import", &inbox)
+        .with_stable_for(Duration::from_millis(100))
+        .with_ledger(&ledger);
+    watch.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    watch.shutdown().await.unwrap();
+
+    let record = Record::default();
+    let watch = trigger(&record, &inbox, &ledger);
+    watch.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    watch.shutdown().await.unwrap();
+    assert_eq!(seen_files(&record), ["a.csv"]);
+}
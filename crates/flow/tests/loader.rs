@@ -0,0 +1,111 @@
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    FlowError,
+};
+
+const YAML: &str = "
+# Nightly export
+key: export
+steps:
+  - key: fetch
+    action: http/get
+    params:
+      url: https://example.com
+    next: store
+  - key: store
+    action: s3/put
+";
+
+const TOML: &str = r#"
+# Nightly export
+key = "export"
+
+[[steps]]
+key = "fetch"
+action = "http/get"
+next = "store"
+params = { url = "https://example.com" }
+
+[[steps]]
+key = "store"
+action = "s3/put"
+"#;
+
+const JSON: &str = r#"{ "key": "export", "steps": [{ "key": "fetch", "action": "http/get" }] }"#;
+
+fn parse_error(err: FlowError) -> (PipelineFormat, Option<usize>, Option<usize>) {
+    match err {
+        FlowError::Parse {
+            format,
+            line,
+            column,
+            ..
+        } => (format, line, column),
+        other => panic!("expected a parse error, got {other}"),
+    }
+}
+
+#[test]
+fn detects_format_from_content() {
+    assert_eq!(PipelineFormat::detect(YAML), PipelineFormat::Yaml);
+    assert_eq!(PipelineFormat::detect(TOML), PipelineFormat::Toml);
+    assert_eq!(PipelineFormat::detect(JSON), PipelineFormat::Json);
+
+    for content in [YAML, TOML, JSON] {
+        let def = PipelineLoader::parse(content).unwrap();
+        assert_eq!(def.key, "export");
+        assert_eq!(def.steps[0].action, "http/get");
+    }
+}
+
+#[test]
+fn yaml_and_toml_definitions_match() {
+    let yaml = PipelineLoader::from_str_with_format(YAML, PipelineFormat::Yaml).unwrap();
+    let toml = PipelineLoader::from_str_with_format(TOML, PipelineFormat::Toml).unwrap();
+
+    assert_eq!(yaml.steps.len(), 2);
+    assert_eq!(yaml.steps[0].next, toml.steps[0].next);
+    assert_eq!(yaml.steps[0].params, toml.steps[0].params);
+}
+
+#[test]
+fn loads_files_by_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, content) in [
+        ("a.yml", YAML),
+        ("b.toml", TOML),
+        ("c.json", JSON),
+        ("d.pipeline", YAML),
+    ] {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        assert_eq!(
+            PipelineLoader::from_file(&path).unwrap().key,
+            "export",
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn parse_errors_carry_line_and_column() {
+    let yaml = "key: export\nsteps:\n  - key: fetch\n    action: [oops\n";
+    let (format, line, column) = parse_error(PipelineLoader::parse(yaml).unwrap_err());
+    assert_eq!(format, PipelineFormat::Yaml);
+    assert!(line.is_some() && column.is_some());
+
+    let toml = "key = \"export\"\nsteps = 3\n";
+    let (format, line, column) = parse_error(PipelineLoader::parse(toml).unwrap_err());
+    assert_eq!(format, PipelineFormat::Toml);
+    assert_eq!((line, column), (Some(2), Some(9)));
+
+    let json = "{\n  \"key\": \"export\",\n  \"steps\": [,]\n}";
+    let err = PipelineLoader::parse(json).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("JSON parse error at line 3, column"),
+        "{err}"
+    );
+    let (_, line, _) = parse_error(err);
+    assert_eq!(line, Some(3));
+}
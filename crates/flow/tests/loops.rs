@@ -0,0 +1,67 @@
+mod common;
+
+use common::EchoAction;
+use ryvus_core::prelude::ExecutionStatus;
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn runs_for_each_and_while_steps_from_yaml() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: batches
+steps:
+  - key: each
+    action: test/echo
+    params: { id: "$.item.id", position: "$.index" }
+    for_each: { items: "$.payload.records", concurrency: 4 }
+    next: count
+  - key: count
+    action: test/echo
+    params: { attempt: "$.index" }
+    while: { when: "$.index < 3", max_iterations: 5 }
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    );
+    manager.try_register(def).unwrap();
+
+    let result = manager
+        .start(
+            "batches",
+            json!({ "records": [{ "id": "a" }, { "id": "b" }] }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+
+    let ids: Vec<_> = result.steps[0]
+        .output
+        .as_ref()
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| (o["id"].clone(), o["position"].clone()))
+        .collect();
+    assert_eq!(ids, [(json!("a"), json!(0)), (json!("b"), json!(1))]);
+
+    let attempts: Vec<_> = result
+        .result
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["attempt"].clone())
+        .collect();
+    assert_eq!(attempts, [json!(0), json!(1), json!(2)]);
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error, ExecutionStatus};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::manager::StepDefinition, prelude::*, resolver::variable::VariableResolver,
+};
+use serde_json::json;
+
+/// Echoes its params merged with the runtime payload.
+#[derive(Clone)]
+struct EchoAction;
+
+#[async_trait]
+impl Action for EchoAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Ok(ActionResult::success(ctx.input.clone().unwrap_or_default()))
+    }
+
+    fn key(&self) -> &str {
+        "test/echo"
+    }
+}
+
+struct MapResolver(HashMap<&'static str, &'static str>);
+
+impl VariableResolver for MapResolver {
+    fn resolve(&self, key: &str) -> Option<String> {
+        self.0.get(key).map(|v| v.to_string())
+    }
+}
+
+fn manager() -> FlowPipelineManager<InMemoryStateStore> {
+    let resolver = MapResolver(HashMap::from([("REGION", "eu-west"), ("TOKEN", "s3cr3t")]));
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    )
+    .with_resolver(resolver);
+
+    manager.register(PipelineDefinition {
+        key: "echo".to_string(),
+        steps: vec![StepDefinition {
+            key: "echo".to_string(),
+            action: "test/echo".to_string(),
+            params: json!({ "region": "$REGION", "token": "secret:$TOKEN" }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    });
+    manager
+}
+
+#[tokio::test]
+async fn start_resolves_variables_and_stores_the_run() {
+    let manager = manager();
+    let result = manager.start("echo", json!({ "id": 7 })).await.unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(
+        result.result,
+        Some(json!({ "region": "eu-west", "token": "****", "id": 7 }))
+    );
+
+    let store = manager.store();
+    let stored = store.load_result(&result.run_id).await.unwrap().unwrap();
+    assert_eq!(stored.result.unwrap()["token"], "****");
+
+    // The definition keeps its placeholders
+    let pipeline = store.load_pipeline("echo").await.unwrap().unwrap();
+    assert_eq!(pipeline.steps[0].params["token"], "secret:$TOKEN");
+}
+
+#[tokio::test]
+async fn start_rejects_unknown_pipelines() {
+    let err = manager().start("missing", json!({})).await.unwrap_err();
+    assert!(matches!(err, FlowError::PipelineNotFound(key) if key == "missing"));
+}
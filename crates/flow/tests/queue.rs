@@ -0,0 +1,263 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use ryvus_core::{
+    environment::{Environment, EnvironmentKind},
+    prelude::{
+        pipeline::Pipeline, Action, ActionContext, ActionResult, Error, ExecutionContext,
+        ExecutionStatus, PipelineState, RunCheckpoint, RunQuery,
+    },
+};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+    queue::RunDispatcher,
+};
+use serde_json::{json, Value};
+
+/// Takes 30ms, recording the inputs in start order and how many runs overlapped.
+#[derive(Clone, Default)]
+struct Work {
+    started: Arc<Mutex<Vec<Value>>>,
+    active: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Action for Work {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let input = ctx.input.clone().unwrap_or_default();
+        self.started.lock().unwrap().push(input["n"].clone());
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        Ok(ActionResult::success(json!({})))
+    }
+
+    fn key(&self) -> &str {
+        "test/work"
+    }
+}
+
+fn manager(work: &Work) -> Arc<FlowPipelineManager<InMemoryStateStore>> {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(work.clone()),
+    );
+    for key in ["imports", "reports"] {
+        let yaml = format!(
+            "key: {key}\nsteps:\n  - key: work\n    action: test/work\n    params:\n      n: \"$.payload.n\"\n"
+        );
+        manager
+            .try_register(
+                PipelineLoader::from_str_with_format(&yaml, PipelineFormat::Yaml).unwrap(),
+            )
+            .unwrap();
+    }
+    Arc::new(manager)
+}
+
+/// Waits until the queue is empty.
+async fn drained(manager: &FlowPipelineManager<InMemoryStateStore>) {
+    for _ in 0..200 {
+        if manager.store().list_queued().await.unwrap().is_empty() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("queue was not drained");
+}
+
+#[tokio::test]
+async fn workers_respect_priority_and_concurrency_limits() {
+    let work = Work::default();
+    let manager = manager(&work);
+    assert!(manager.enqueue("missing", json!({})).await.is_err());
+
+    for n in 0..4 {
+        manager.enqueue("imports", json!({ "n": n })).await.unwrap();
+    }
+    manager
+        .enqueue_with_priority("imports", json!({ "n": "urgent" }), 5)
+        .await
+        .unwrap();
+
+    let dispatcher = RunDispatcher::new(manager.clone())
+        .with_workers(3)
+        .with_concurrency_limit("imports", 1);
+    dispatcher.start().unwrap();
+    assert!(dispatcher.start().is_err());
+    drained(&manager).await;
+    dispatcher.shutdown().await;
+
+    // One import at a time despite three workers, the urgent one first
+    let started = work.started.lock().unwrap().clone();
+    assert_eq!(
+        started,
+        [json!("urgent"), json!(0), json!(1), json!(2), json!(3)]
+    );
+    let runs = manager
+        .store()
+        .list_results(&RunQuery::new().pipeline("imports"))
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 5);
+    assert!(runs.iter().all(|r| r.status == ExecutionStatus::Success));
+    assert_eq!(work.peak.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn runs_of_crashed_workers_are_delivered_again() {
+    let work = Work::default();
+    let manager = manager(&work);
+    let run_id = manager
+        .enqueue("imports", json!({ "n": "retried" }))
+        .await
+        .unwrap();
+    let abandoned = manager
+        .enqueue("reports", json!({ "n": "abandoned" }))
+        .await
+        .unwrap();
+
+    // A worker that died mid-run leaves its lease behind
+    let store = manager.store();
+    let now = Utc::now();
+    let lease = now + chrono::Duration::milliseconds(100);
+    store.claim_queued(now, lease, &[]).await.unwrap().unwrap();
+    store.claim_queued(now, lease, &[]).await.unwrap().unwrap();
+    let mut exhausted = store
+        .list_queued()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|r| r.run_id == abandoned)
+        .unwrap();
+    exhausted.deliveries = 3;
+    store.save_queued(&exhausted).await.unwrap();
+
+    let dispatcher = RunDispatcher::new(manager.clone())
+        .with_poll_interval(Duration::from_millis(20))
+        .with_max_deliveries(3);
+    dispatcher.start().unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(work.started.lock().unwrap().is_empty());
+    drained(&manager).await;
+    dispatcher.shutdown().await;
+
+    assert_eq!(*work.started.lock().unwrap(), [json!("retried")]);
+    let retried = store.load_result(&run_id).await.unwrap().unwrap();
+    assert_eq!(retried.status, ExecutionStatus::Success);
+    let given_up = store.load_result(&abandoned).await.unwrap().unwrap();
+    assert_eq!(given_up.status, ExecutionStatus::Failed);
+}
+
+#[tokio::test]
+async fn redelivered_runs_resume_from_their_checkpoint() {
+    let work = Work::default();
+    let checkpoints = Arc::new(InMemoryStateStore::default());
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(work.clone())
+            .with_state_store(checkpoints.clone()),
+    );
+    let yaml = "key: staged
+steps:
+  - key: first
+    action: test/work
+    params: { n: first }
+    next: second
+  - key: second
+    action: test/work
+    params: { n: second }
+";
+    let def = PipelineLoader::from_str_with_format(yaml, PipelineFormat::Yaml).unwrap();
+    let pipeline = Pipeline::try_from(def.clone()).unwrap();
+    manager.try_register(def).unwrap();
+    let manager = Arc::new(manager);
+    let run_id = manager.enqueue("staged", json!({})).await.unwrap();
+
+    // A worker died after the first step, leaving its lease and checkpoint behind
+    let now = Utc::now();
+    let lease = now + chrono::Duration::milliseconds(50);
+    manager.store().claim_queued(now, lease, &[]).await.unwrap();
+    let mut ctx =
+        ExecutionContext::new("staged", Environment::new("local", EnvironmentKind::Local));
+    ctx.run_id = run_id.clone();
+    let mut first = ActionResult::success(json!({}));
+    first.key = "first".into();
+    ctx.insert_result("first", first);
+    let checkpoint = RunCheckpoint::new(
+        &pipeline,
+        &ctx,
+        Some("second".into()),
+        PipelineState::Running,
+    );
+    checkpoints.save_checkpoint(&checkpoint).await.unwrap();
+
+    let dispatcher =
+        RunDispatcher::new(manager.clone()).with_poll_interval(Duration::from_millis(20));
+    dispatcher.start().unwrap();
+    drained(&manager).await;
+    dispatcher.shutdown().await;
+
+    assert_eq!(*work.started.lock().unwrap(), [json!("second")]);
+    let result = manager.store().load_result(&run_id).await.unwrap().unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.steps.len(), 2);
+}
+
+#[tokio::test]
+async fn workers_stop_runs_whose_lease_they_lost() {
+    let work = Work::default();
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(work.clone()),
+    );
+    let yaml = "key: staged
+steps:
+  - key: first
+    action: test/work
+    params: { n: first }
+    next: second
+  - key: second
+    action: test/work
+    params: { n: second }
+";
+    manager
+        .try_register(PipelineLoader::from_str_with_format(yaml, PipelineFormat::Yaml).unwrap())
+        .unwrap();
+    let manager = Arc::new(manager);
+    let run_id = manager.enqueue("staged", json!({})).await.unwrap();
+
+    let dispatcher = RunDispatcher::new(manager.clone())
+        .with_workers(1)
+        .with_visibility_timeout(Duration::from_millis(20));
+    dispatcher.start().unwrap();
+    while work.started.lock().unwrap().is_empty() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    // Another worker claims the run while the first step is going
+    let store = manager.store();
+    let mut taken = store.list_queued().await.unwrap().remove(0);
+    taken.deliveries += 1;
+    taken.visible_at = Utc::now() + chrono::Duration::seconds(60);
+    store.save_queued(&taken).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    dispatcher.shutdown().await;
+
+    assert_eq!(*work.started.lock().unwrap(), [json!("first")]);
+    assert!(store.load_result(&run_id).await.unwrap().is_none());
+    assert_eq!(store.list_queued().await.unwrap().len(), 1);
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use common::{EchoAction, MapResolver};
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error, ExecutionStatus};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+};
+use serde_json::json;
+
+const DEPLOY: &str = r#"
+key: deploy
+steps:
+  - key: build
+    action: test/echo
+    params: { token: "${secret.DEPLOY_TOKEN}" }
+    next: approve
+  - key: approve
+    wait_for: { signal: approval }
+    next: release
+  - key: release
+    action: test/echo
+    params:
+      approved_by: "$.approve.output.approved_by"
+      token: "${secret.DEPLOY_TOKEN}"
+"#;
+
+#[tokio::test]
+async fn runs_stop_at_wait_for_steps() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: deploy
+steps:
+  - key: build
+    action: test/echo
+    next: approve
+  - key: approve
+    wait_for: { signal: approval, timeout_ms: 86400000 }
+    next: release
+    otherwise: abort
+  - key: release
+    action: test/echo
+  - key: abort
+    action: test/echo
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+    assert!(def.validate().issues.is_empty());
+
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    );
+    manager.try_register(def).unwrap();
+
+    let result = manager.start("deploy", json!({})).await.unwrap();
+    assert_eq!(result.status, ExecutionStatus::Suspended);
+    assert_eq!(result.steps.len(), 1);
+}
+
+#[tokio::test]
+async fn signals_continue_suspended_runs_through_the_manager() {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(EchoAction)
+            .with_state_store(Arc::new(InMemoryStateStore::default())),
+    )
+    .with_resolver(MapResolver(HashMap::from([("DEPLOY_TOKEN", "s3cr3t")])));
+    let def = PipelineLoader::from_str_with_format(DEPLOY, PipelineFormat::Yaml).unwrap();
+    manager.try_register(def).unwrap();
+
+    let started = manager.start("deploy", json!({})).await.unwrap();
+    assert_eq!(started.status, ExecutionStatus::Suspended);
+
+    assert!(manager
+        .signal(&started.run_id, "deploy", "rollback", json!({}))
+        .await
+        .is_err());
+    let result = manager
+        .signal(
+            &started.run_id,
+            "deploy",
+            "approval",
+            json!({ "approved_by": "ada" }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result.clone().unwrap()["approved_by"], json!("ada"));
+
+    // The stored run moves on from suspended, with its secrets masked
+    let stored = manager
+        .store()
+        .load_result(&started.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.status, ExecutionStatus::Success);
+    assert_eq!(stored.steps.len(), 3);
+    assert_eq!(stored.result.unwrap()["token"], json!("****"));
+}
+
+/// Fails unless it receives the real deploy token.
+#[derive(Clone)]
+struct Release;
+
+#[async_trait]
+impl Action for Release {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let input = ctx.input.clone().unwrap_or_default();
+        if input["token"] != json!("s3cr3t") {
+            return Err(Error::Action(format!("bad token {}", input["token"])));
+        }
+        Ok(ActionResult::success(input))
+    }
+
+    fn key(&self) -> &str {
+        "test/release"
+    }
+}
+
+#[tokio::test]
+async fn suspended_runs_keep_secrets_out_of_the_engine_store() {
+    let checkpoints = Arc::new(InMemoryStateStore::default());
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(EchoAction)
+            .with_action(Release)
+            .with_state_store(checkpoints.clone()),
+    )
+    .with_resolver(MapResolver(HashMap::from([("DEPLOY_TOKEN", "s3cr3t")])));
+    let def = PipelineLoader::from_str_with_format(
+        &DEPLOY.replace(
+            "action: test/echo\n    params:\n",
+            "action: test/release\n    params:\n",
+        ),
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+    manager.try_register(def).unwrap();
+
+    let started = manager.start("deploy", json!({})).await.unwrap();
+    assert_eq!(started.status, ExecutionStatus::Suspended);
+    let run_id = started.run_id;
+
+    let checkpoint = checkpoints.load_checkpoint(&run_id).await.unwrap().unwrap();
+    let steps = checkpoints.load_steps(&run_id).await.unwrap();
+    let stored = serde_json::to_string(&(checkpoint, steps)).unwrap();
+    assert!(!stored.contains("s3cr3t"), "{stored}");
+    assert!(stored.contains("${secret.DEPLOY_TOKEN}"));
+
+    // The resumed run resolves the secret again instead of using the masked copy
+    let result = manager
+        .signal(
+            &run_id,
+            "deploy",
+            "approval",
+            json!({ "approved_by": "ada" }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.status,
+        ExecutionStatus::Success,
+        "{:?}",
+        result.error
+    );
+
+    let stored = checkpoints.load_result(&run_id).await.unwrap().unwrap();
+    assert_eq!(stored.status, ExecutionStatus::Success);
+    assert!(!serde_json::to_string(&stored).unwrap().contains("s3cr3t"));
+}
@@ -1,17 +1,44 @@
-use ryvus_flow::prelude::*;
+use async_trait::async_trait;
+use ryvus_core::prelude::{Action, ActionContext, ActionResult, Error, ExecutionStatus};
+use ryvus_engine::Engine;
+use ryvus_flow::{pipeline::manager::StepDefinition, prelude::*};
+use serde_json::json;
+
+#[derive(Clone)]
+struct EchoAction;
+
+#[async_trait]
+impl Action for EchoAction {
+    async fn execute(&self, ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        Ok(ActionResult::success(ctx.input.clone().unwrap_or_default()))
+    }
+
+    fn key(&self) -> &str {
+        "test/echo"
+    }
+}
 
 #[tokio::test]
 async fn can_register_and_start() -> Result<(), FlowError> {
     let store = InMemoryStateStore::default();
-    let mut manager = FlowPipelineManager::new(store);
+    let manager = FlowPipelineManager::new(store, Engine::default().with_action(EchoAction));
 
     let pipeline = PipelineDefinition {
-        key: "".to_string(),
+        key: "smoke".to_string(),
         description: None,
         version: None,
-        steps: Vec::new(),
+        steps: vec![StepDefinition {
+            key: "echo".to_string(),
+            action: "test/echo".to_string(),
+            ..Default::default()
+        }],
         pipeline_hooks: Vec::new(),
+        ..Default::default()
     };
     manager.register(pipeline);
-    manager.start("smoke").await
+    let result = manager.start("smoke", json!({ "hello": "flow" })).await?;
+
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result, Some(json!({ "hello": "flow" })));
+    Ok(())
 }
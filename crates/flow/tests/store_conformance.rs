@@ -0,0 +1,389 @@
+use chrono::{Duration, Utc};
+use ryvus_core::{
+    action::result::ExecutionResult,
+    environment::{Environment, EnvironmentKind},
+    pipeline::{pipeline::Pipeline, state::PipelineState},
+    prelude::{
+        ExecutionContext, ExecutionStatus, PipelineStep, QueuedRun, RunCheckpoint, RunQuery,
+        RunTimer,
+    },
+    state::STATE_STORE_VERSION,
+};
+use ryvus_flow::store::{FileStateStore, InMemoryStateStore, StateStore};
+use serde_json::json;
+
+fn context(run_id: &str, pipeline_key: &str) -> ExecutionContext {
+    let mut ctx = ExecutionContext::new(
+        pipeline_key,
+        Environment::new("local", EnvironmentKind::Local),
+    );
+    ctx.run_id = run_id.to_string();
+    ctx
+}
+
+/// A run of `pipeline_key` that finished `age_days` ago with `status`.
+fn run(
+    run_id: &str,
+    pipeline_key: &str,
+    status: ExecutionStatus,
+    age_days: i64,
+) -> ExecutionResult {
+    let mut result = context(run_id, pipeline_key).into_result();
+    result.status = status;
+    result.metrics.started_at -= Duration::days(age_days);
+    result.metrics.finished_at -= Duration::days(age_days);
+    result
+}
+
+/// Behaviour every store must share.
+async fn conformance(store: &dyn StateStore) {
+    assert_eq!(store.version(), STATE_STORE_VERSION);
+
+    assert!(store.load_result("missing").await.unwrap().is_none());
+    assert!(store.load_checkpoint("missing").await.unwrap().is_none());
+    assert!(store.load_steps("missing").await.unwrap().is_empty());
+
+    // Saving again replaces the earlier result
+    store
+        .save_result(&run("a", "orders", ExecutionStatus::Failed, 1))
+        .await
+        .unwrap();
+    store
+        .save_result(&run("a", "orders", ExecutionStatus::Success, 1))
+        .await
+        .unwrap();
+    store
+        .save_result(&run("b", "orders", ExecutionStatus::Failed, 2))
+        .await
+        .unwrap();
+    store
+        .save_result(&run("c", "billing", ExecutionStatus::Success, 30))
+        .await
+        .unwrap();
+
+    let a = store.load_result("a").await.unwrap().unwrap();
+    assert_eq!(a.status, ExecutionStatus::Success);
+
+    let ids =
+        |results: Vec<ExecutionResult>| results.into_iter().map(|r| r.run_id).collect::<Vec<_>>();
+    assert_eq!(
+        ids(store.list_results(&RunQuery::new()).await.unwrap()),
+        ["a", "b", "c"]
+    );
+    assert_eq!(
+        ids(store
+            .list_results(&RunQuery::new().pipeline("orders"))
+            .await
+            .unwrap()),
+        ["a", "b"]
+    );
+    assert_eq!(
+        ids(store
+            .list_results(
+                &RunQuery::new()
+                    .pipeline("orders")
+                    .status(ExecutionStatus::Failed)
+            )
+            .await
+            .unwrap()),
+        ["b"]
+    );
+    assert_eq!(
+        ids(store.list_results(&RunQuery::new().limit(1)).await.unwrap()),
+        ["a"]
+    );
+
+    store
+        .update_step("c", "fetch", json!({ "n": 1 }))
+        .await
+        .unwrap();
+    store
+        .update_step("c", "fetch", json!({ "n": 2 }))
+        .await
+        .unwrap();
+    store.update_step("c", "send", json!(null)).await.unwrap();
+    let steps = store.load_steps("c").await.unwrap();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps["fetch"], json!({ "n": 2 }));
+
+    let pipeline = Pipeline::builder("billing").build();
+    let checkpoint = RunCheckpoint::new(
+        &pipeline,
+        &context("c", "billing"),
+        Some("send".into()),
+        PipelineState::Running,
+    );
+    store.save_checkpoint(&checkpoint).await.unwrap();
+    let loaded = store.load_checkpoint("c").await.unwrap().unwrap();
+    assert_eq!(loaded.next_step.as_deref(), Some("send"));
+    assert_eq!(loaded.state, PipelineState::Running);
+
+    // Only "c" finished before the cutoff
+    let removed = store.prune(Utc::now() - Duration::days(7)).await.unwrap();
+    assert_eq!(removed, 1);
+    assert!(store.load_result("c").await.unwrap().is_none());
+    assert!(store.load_checkpoint("c").await.unwrap().is_none());
+    assert!(store.load_steps("c").await.unwrap().is_empty());
+    assert_eq!(
+        ids(store.list_results(&RunQuery::new()).await.unwrap()),
+        ["a", "b"]
+    );
+}
+
+async fn pipeline_conformance(store: &dyn StateStore) {
+    assert!(store.load_pipeline("orders/v1").await.unwrap().is_none());
+
+    let pipeline = Pipeline::builder("orders/v1")
+        .step(PipelineStep::builder("fetch", "http/get").build())
+        .build();
+    store.save_pipeline(&pipeline).await.unwrap();
+    store
+        .save_pipeline(&Pipeline::builder("billing").build())
+        .await
+        .unwrap();
+
+    let loaded = store.load_pipeline("orders/v1").await.unwrap().unwrap();
+    assert_eq!(loaded.steps.len(), 1);
+    assert_eq!(loaded.steps[0].action, "http/get");
+    assert_eq!(
+        store.list_pipelines().await.unwrap(),
+        ["billing", "orders/v1"]
+    );
+}
+
+async fn timer_conformance(store: &dyn StateStore) {
+    let now = Utc::now();
+    assert!(store.due_timers(now).await.unwrap().is_empty());
+
+    store
+        .save_timer(&RunTimer::new("late", now - Duration::minutes(1)))
+        .await
+        .unwrap();
+    store
+        .save_timer(&RunTimer::new("early", now - Duration::hours(1)))
+        .await
+        .unwrap();
+    store
+        .save_timer(&RunTimer::new("future", now + Duration::days(3)))
+        .await
+        .unwrap();
+    // Saving again moves the run's timer
+    store
+        .save_timer(&RunTimer::new("late", now - Duration::minutes(2)))
+        .await
+        .unwrap();
+
+    let due = store.due_timers(now).await.unwrap();
+    let ids: Vec<_> = due.iter().map(|t| t.run_id.as_str()).collect();
+    assert_eq!(ids, ["early", "late"]);
+    assert_eq!(
+        due[1].fire_at.timestamp_millis(),
+        (now - Duration::minutes(2)).timestamp_millis()
+    );
+
+    store.delete_timer("early").await.unwrap();
+    store.delete_timer("missing").await.unwrap();
+    assert_eq!(store.due_timers(now).await.unwrap().len(), 1);
+    assert_eq!(
+        store
+            .due_timers(now + Duration::days(4))
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // Suspended runs outlive the retention window
+    store
+        .save_result(&run(
+            "sleeping",
+            "reminders",
+            ExecutionStatus::Suspended,
+            30,
+        ))
+        .await
+        .unwrap();
+    store.prune(Utc::now() - Duration::days(7)).await.unwrap();
+    assert!(store.load_result("sleeping").await.unwrap().is_some());
+}
+
+async fn queue_conformance(store: &dyn StateStore) {
+    let now = Utc::now();
+    let lease = now + Duration::minutes(5);
+    assert!(store.claim_queued(now, lease, &[]).await.unwrap().is_none());
+
+    let mut first = QueuedRun::new("first", "orders", json!({ "n": 1 }));
+    first.enqueued_at = now - Duration::seconds(2);
+    first.visible_at = first.enqueued_at;
+    let mut second = QueuedRun::new("second", "orders", json!({ "n": 2 }));
+    second.enqueued_at = now - Duration::seconds(1);
+    second.visible_at = second.enqueued_at;
+    let mut urgent = QueuedRun::new("urgent", "reports", json!({})).with_priority(10);
+    urgent.visible_at = now;
+    let mut later = QueuedRun::new("later", "orders", json!({})).with_priority(20);
+    later.visible_at = now + Duration::hours(1);
+    for run in [&first, &second, &urgent, &later] {
+        store.save_queued(run).await.unwrap();
+    }
+
+    let ids = |runs: Vec<QueuedRun>| runs.into_iter().map(|r| r.run_id).collect::<Vec<_>>();
+    assert_eq!(
+        ids(store.list_queued().await.unwrap()),
+        ["later", "urgent", "first", "second"]
+    );
+
+    // Priority first, then enqueue order; skipped pipelines and hidden runs wait
+    let claimed = store
+        .claim_queued(now, lease, &["reports".to_string()])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.run_id, "first");
+    assert_eq!(claimed.input, json!({ "n": 1 }));
+    assert_eq!(claimed.deliveries, 1);
+    assert_eq!(
+        claimed.visible_at.timestamp_millis(),
+        lease.timestamp_millis()
+    );
+    let claimed = store.claim_queued(now, lease, &[]).await.unwrap().unwrap();
+    assert_eq!(claimed.run_id, "urgent");
+    let claimed = store.claim_queued(now, lease, &[]).await.unwrap().unwrap();
+    assert_eq!(claimed.run_id, "second");
+    assert!(store.claim_queued(now, lease, &[]).await.unwrap().is_none());
+
+    // Leases run out, and claiming again counts another delivery
+    let after_lease = now + Duration::hours(2);
+    let claimed = store
+        .claim_queued(after_lease, after_lease + Duration::minutes(5), &[])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.run_id, "later");
+    let claimed = store
+        .claim_queued(after_lease, after_lease + Duration::minutes(5), &[])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((claimed.run_id.as_str(), claimed.deliveries), ("urgent", 2));
+
+    // Only the latest delivery renews its lease
+    let renewed_until = after_lease + Duration::minutes(30);
+    assert!(!store.renew_lease("urgent", 1, renewed_until).await.unwrap());
+    assert!(store.renew_lease("urgent", 2, renewed_until).await.unwrap());
+    assert!(!store
+        .renew_lease("missing", 1, renewed_until)
+        .await
+        .unwrap());
+    let urgent = store
+        .list_queued()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|r| r.run_id == "urgent")
+        .unwrap();
+    assert_eq!(
+        urgent.visible_at.timestamp_millis(),
+        renewed_until.timestamp_millis()
+    );
+
+    store.delete_queued("first").await.unwrap();
+    store.delete_queued("missing").await.unwrap();
+    assert_eq!(
+        ids(store.list_queued().await.unwrap()),
+        ["later", "urgent", "second"]
+    );
+}
+
+#[tokio::test]
+async fn in_memory_store_conforms() {
+    conformance(&InMemoryStateStore::default()).await;
+    pipeline_conformance(&InMemoryStateStore::default()).await;
+    timer_conformance(&InMemoryStateStore::default()).await;
+    queue_conformance(&InMemoryStateStore::default()).await;
+}
+
+#[tokio::test]
+async fn file_store_conforms() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileStateStore::open(dir.path()).await.unwrap();
+    conformance(&store).await;
+    pipeline_conformance(&store).await;
+    timer_conformance(&store).await;
+    queue_conformance(&store).await;
+}
+
+#[tokio::test]
+async fn file_store_survives_reopen_and_torn_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileStateStore::open(dir.path()).await.unwrap();
+    store
+        .save_result(&run("a", "orders", ExecutionStatus::Success, 0))
+        .await
+        .unwrap();
+    store
+        .save_pipeline(&Pipeline::builder("orders").build())
+        .await
+        .unwrap();
+    drop(store);
+
+    // Simulate a crash halfway through appending a line
+    let log = dir.path().join("results.jsonl");
+    let mut contents = std::fs::read(&log).unwrap();
+    contents.extend_from_slice(b"{\"run_id\":\"b\",");
+    std::fs::write(&log, contents).unwrap();
+
+    let store = FileStateStore::open(dir.path()).await.unwrap();
+    assert!(store.load_result("a").await.unwrap().is_some());
+    assert_eq!(store.list_results(&RunQuery::new()).await.unwrap().len(), 1);
+    assert!(store.load_pipeline("orders").await.unwrap().is_some());
+
+    // Appending after the torn line keeps the new record readable
+    store
+        .save_result(&run("c", "orders", ExecutionStatus::Success, 0))
+        .await
+        .unwrap();
+    assert!(store.load_result("c").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn file_store_rejects_newer_layout_version() {
+    let dir = tempfile::tempdir().unwrap();
+    FileStateStore::open(dir.path()).await.unwrap();
+    std::fs::write(
+        dir.path().join("VERSION"),
+        (STATE_STORE_VERSION + 1).to_string(),
+    )
+    .unwrap();
+
+    assert!(FileStateStore::open(dir.path()).await.is_err());
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use ryvus_flow::store::SqliteStateStore;
+
+    #[tokio::test]
+    async fn sqlite_store_conforms() {
+        let store = SqliteStateStore::open_in_memory().unwrap();
+        conformance(&store).await;
+        pipeline_conformance(&store).await;
+        timer_conformance(&store).await;
+        queue_conformance(&store).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ryvus.db");
+        let store = SqliteStateStore::open(&path).unwrap();
+        store
+            .save_result(&run("a", "orders", ExecutionStatus::Success, 0))
+            .await
+            .unwrap();
+        drop(store);
+
+        let store = SqliteStateStore::open(&path).unwrap();
+        assert!(store.load_result("a").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use std::{collections::HashMap, sync::Arc};
+
+use common::{EchoAction, MapResolver};
+use ryvus_core::{
+    action::result::ExecutionResult,
+    environment::{Environment, EnvironmentKind},
+    prelude::{
+        pipeline::Pipeline, ExecutionContext, ExecutionStatus, PipelineState, RunCheckpoint,
+    },
+};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+};
+use serde_json::json;
+
+const AUTHENTICATE: &str = r#"
+key: authenticate
+steps:
+  - key: login
+    action: test/echo
+    params:
+      user: "$.payload.user"
+      authorization: "Bearer ${secret.API_TOKEN}"
+"#;
+
+const ORDERS: &str = r#"
+key: orders
+steps:
+  - key: auth
+    pipeline:
+      key: authenticate
+      input:
+        user: "$.payload.customer"
+    next: fetch
+  - key: fetch
+    action: test/echo
+    params:
+      user: "$.auth.output.result.user"
+"#;
+
+#[tokio::test]
+async fn calls_registered_pipelines_and_masks_their_secrets() {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    )
+    .with_resolver(MapResolver(HashMap::from([("API_TOKEN", "s3cr3t")])));
+
+    for source in [AUTHENTICATE, ORDERS] {
+        let def = PipelineLoader::from_str_with_format(source, PipelineFormat::Yaml).unwrap();
+        manager.try_register(def).unwrap();
+    }
+
+    let result = manager
+        .start("orders", json!({ "customer": "ada" }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result.clone().unwrap()["user"], json!("ada"));
+
+    let child: ExecutionResult =
+        serde_json::from_value(result.steps[0].output.clone().unwrap()).unwrap();
+    assert_eq!(child.parent_run_id.as_deref(), Some(result.run_id.as_str()));
+    assert_eq!(child.result.unwrap()["authorization"], json!("Bearer ****"));
+
+    // Secrets resolved for the child are masked in the stored parent run
+    let stored = manager
+        .store()
+        .load_result(&result.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let stored_child = stored.steps[0].output.clone().unwrap();
+    assert_eq!(
+        stored_child["result"]["authorization"],
+        json!("Bearer ****")
+    );
+}
+
+#[tokio::test]
+async fn resumed_runs_call_registered_pipelines() {
+    let checkpoints = Arc::new(InMemoryStateStore::default());
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(EchoAction)
+            .with_state_store(checkpoints.clone()),
+    )
+    .with_resolver(MapResolver(HashMap::from([("API_TOKEN", "s3cr3t")])));
+
+    for source in [AUTHENTICATE, ORDERS] {
+        let def = PipelineLoader::from_str_with_format(source, PipelineFormat::Yaml).unwrap();
+        manager.try_register(def).unwrap();
+    }
+    let pipeline = Pipeline::try_from((*manager.get("orders").unwrap()).clone()).unwrap();
+
+    // The run was cut off before its sub-pipeline step
+    let mut ctx =
+        ExecutionContext::new("orders", Environment::new("local", EnvironmentKind::Local));
+    ctx.run_id = "run_cut_off".into();
+    ctx.insert("payload", json!({ "customer": "ada" }));
+    let checkpoint =
+        RunCheckpoint::new(&pipeline, &ctx, Some("auth".into()), PipelineState::Running);
+    checkpoints.save_checkpoint(&checkpoint).await.unwrap();
+
+    let result = manager
+        .resume("run_cut_off", "orders")
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.result.clone().unwrap()["user"], json!("ada"));
+    let stored = manager
+        .store()
+        .load_result("run_cut_off")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        stored.steps[0].output.clone().unwrap()["result"]["authorization"],
+        json!("Bearer ****")
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{EchoAction, MapResolver};
+use ryvus_core::prelude::ExecutionStatus;
+use ryvus_engine::Engine;
+use ryvus_flow::{pipeline::manager::StepDefinition, prelude::*};
+use serde_json::json;
+
+#[tokio::test]
+async fn interpolates_variables_then_jsonpaths_and_masks_secrets() {
+    let resolver = MapResolver(HashMap::from([("HOST", "api.test"), ("TOKEN", "s3cr3t")]));
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    )
+    .with_resolver(resolver);
+
+    manager
+        .try_register(PipelineDefinition {
+            key: "templated".to_string(),
+            steps: vec![StepDefinition {
+                key: "call".to_string(),
+                action: "test/echo".to_string(),
+                params: json!({
+                    "url": "https://${env.HOST}/users/${$.payload.id}",
+                    "auth": "Bearer ${secret.TOKEN}",
+                    "basic": "Basic ${secret.TOKEN | base64}",
+                    "host": "${env.HOST | base64}",
+                    "region": "${env.REGION | default('eu')}"
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+
+    let result = manager
+        .start("templated", json!({ "id": 7 }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    let output = result.result.clone().unwrap();
+    assert_eq!(output["url"], json!("https://api.test/users/7"));
+    assert_eq!(output["host"], json!("YXBpLnRlc3Q="));
+    assert_eq!(output["region"], json!("eu"));
+
+    // Raw and filtered secrets are masked in the returned and the stored run
+    assert_eq!(output["auth"], json!("Bearer ****"));
+    assert_eq!(output["basic"], json!("Basic ****"));
+    let stored = manager
+        .store()
+        .load_result(&result.run_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .result
+        .unwrap();
+    assert_eq!(stored["auth"], json!("Bearer ****"));
+    assert_eq!(stored["basic"], json!("Basic ****"));
+    assert_eq!(stored["url"], json!("https://api.test/users/7"));
+}
+
+#[tokio::test]
+async fn literals_and_variable_values_survive_both_phases() {
+    let resolver = MapResolver(HashMap::from([
+        ("RAW", "${$.payload.id}"),
+        ("PATH", "$.payload.id"),
+    ]));
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    )
+    .with_resolver(resolver);
+
+    manager
+        .try_register(PipelineDefinition {
+            key: "escaped".to_string(),
+            steps: vec![StepDefinition {
+                key: "call".to_string(),
+                action: "test/echo".to_string(),
+                params: json!({
+                    "literal": "$${env.HOST}",
+                    "mixed": "$${$.payload.id} is ${$.payload.id}",
+                    "raw": "${env.RAW}",
+                    "raw_mixed": "${env.RAW} for ${$.payload.id}",
+                    "path": "${env.PATH}"
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+
+    let result = manager.start("escaped", json!({ "id": 7 })).await.unwrap();
+    let output = result.result.unwrap();
+    assert_eq!(output["literal"], json!("${env.HOST}"));
+    assert_eq!(output["mixed"], json!("${$.payload.id} is 7"));
+    assert_eq!(output["raw"], json!("${$.payload.id}"));
+    assert_eq!(output["raw_mixed"], json!("${$.payload.id} for 7"));
+    assert_eq!(output["path"], json!("$.payload.id"));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use common::EchoAction;
+use ryvus_core::prelude::ExecutionStatus;
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn runs_delay_steps_declared_in_yaml() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: reminder
+steps:
+  - key: signup
+    action: test/echo
+    next: cool_off
+  - key: cool_off
+    sleep_ms: 10
+    next: pause
+  - key: pause
+    wait_until: "$.payload.remind_at"
+    next: remind
+  - key: remind
+    action: test/echo
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+    assert!(def.validate().issues.is_empty());
+
+    let pipeline = ryvus_core::prelude::pipeline::Pipeline::try_from(def.clone()).unwrap();
+    assert_eq!(pipeline.steps[1].sleep_ms, Some(10));
+    assert_eq!(
+        pipeline.steps[2].wait_until.as_deref(),
+        Some("$.payload.remind_at")
+    );
+
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    );
+    manager.try_register(def).unwrap();
+
+    let started = std::time::Instant::now();
+    let result = manager
+        .start("reminder", json!({ "remind_at": 0 }))
+        .await
+        .unwrap();
+    assert_eq!(result.status, ExecutionStatus::Success);
+    assert_eq!(result.steps.len(), 4);
+    assert!(started.elapsed() >= Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn due_timers_continue_runs_through_the_manager() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: follow_up
+steps:
+  - key: signup
+    action: test/echo
+    next: cool_off
+  - key: cool_off
+    sleep_ms: 20
+    next: remind
+  - key: remind
+    action: test/echo
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default()
+            .with_action(EchoAction)
+            .with_state_store(Arc::new(InMemoryStateStore::default()))
+            .with_durable_timer_threshold(Duration::from_millis(10)),
+    );
+    manager.try_register(def).unwrap();
+
+    let started = manager.start("follow_up", json!({})).await.unwrap();
+    assert_eq!(started.status, ExecutionStatus::Suspended);
+    assert!(manager.wake_due().await.unwrap().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let woken = manager.wake_due().await.unwrap();
+    assert_eq!(woken.len(), 1);
+    assert_eq!(woken[0].status, ExecutionStatus::Success);
+
+    let stored = manager
+        .store()
+        .load_result(&started.run_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.status, ExecutionStatus::Success);
+    assert_eq!(stored.steps.len(), 3);
+}
@@ -0,0 +1,183 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use ryvus_core::{
+    environment::{Environment, EnvironmentKind},
+    prelude::{Action, ActionContext, ActionResult, Error, ExecutionContext, RunQuery},
+};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+    trigger::{CronTrigger, IntervalTrigger, MisfirePolicy, OverlapPolicy, Trigger, Tz},
+};
+use serde_json::json;
+
+/// Takes 40ms and records how many runs were in it at once.
+#[derive(Clone, Default)]
+struct Work {
+    active: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+    finished: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Action for Work {
+    async fn execute(&self, _ctx: &mut ActionContext) -> Result<ActionResult, Error> {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        self.finished.fetch_add(1, Ordering::SeqCst);
+        Ok(ActionResult::success(json!({})))
+    }
+
+    fn key(&self) -> &str {
+        "test/work"
+    }
+}
+
+fn manager(work: Work) -> Arc<FlowPipelineManager<InMemoryStateStore>> {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(work),
+    );
+    let def = PipelineLoader::from_str_with_format(
+        "key: sync\nsteps:\n  - key: work\n    action: test/work\n",
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+    manager.try_register(def).unwrap();
+    Arc::new(manager)
+}
+
+async fn stored_runs(manager: &FlowPipelineManager<InMemoryStateStore>) -> usize {
+    manager
+        .store()
+        .list_results(&RunQuery::new().pipeline("sync"))
+        .await
+        .unwrap()
+        .len()
+}
+
+/// Runs an interval trigger firing every 15ms for a while, then shuts it down.
+async fn run_for(overlap: OverlapPolicy, work: &Work) -> usize {
+    let manager = manager(work.clone());
+    let trigger = IntervalTrigger::new(manager.clone(), "sync", Duration::from_millis(15))
+        .unwrap()
+        .with_overlap(overlap);
+    trigger.start().await.unwrap();
+    assert!(trigger.start().await.is_err());
+    tokio::time::sleep(Duration::from_millis(130)).await;
+    trigger.shutdown().await.unwrap();
+
+    // Shutdown waited for every run it started
+    assert_eq!(work.active.load(Ordering::SeqCst), 0);
+    let runs = stored_runs(&manager).await;
+    assert_eq!(runs, work.finished.load(Ordering::SeqCst));
+    runs
+}
+
+#[tokio::test]
+async fn interval_triggers_apply_the_overlap_policy() {
+    let allowed = Work::default();
+    run_for(OverlapPolicy::Allow, &allowed).await;
+    assert!(allowed.peak.load(Ordering::SeqCst) > 1);
+
+    let skipped = Work::default();
+    let skipped_runs = run_for(OverlapPolicy::Skip, &skipped).await;
+    assert_eq!(skipped.peak.load(Ordering::SeqCst), 1);
+    assert!(skipped_runs >= 1);
+
+    // Queued runs go one at a time; the ones still waiting at shutdown are dropped
+    let queued = Work::default();
+    let queued_runs = run_for(OverlapPolicy::Queue, &queued).await;
+    assert_eq!(queued.peak.load(Ordering::SeqCst), 1);
+    assert!(queued_runs >= 1);
+}
+
+#[tokio::test]
+async fn missed_fires_follow_the_misfire_policy() {
+    for (policy, expected) in [(MisfirePolicy::Skip, 0), (MisfirePolicy::CatchUpOnce, 1)] {
+        let work = Work::default();
+        let manager = manager(work.clone());
+
+        // The last run was an hour ago, so several 10-minute fires were missed
+        let mut last =
+            ExecutionContext::new("sync", Environment::new("local", EnvironmentKind::Local))
+                .into_result();
+        last.metrics.started_at = Utc::now() - chrono::Duration::hours(1);
+        manager.store().save_result(&last).await.unwrap();
+
+        let trigger = IntervalTrigger::new(manager.clone(), "sync", Duration::from_secs(600))
+            .unwrap()
+            .with_misfire(policy);
+        trigger.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        trigger.shutdown().await.unwrap();
+
+        assert_eq!(work.finished.load(Ordering::SeqCst), expected, "{policy:?}");
+    }
+}
+
+#[test]
+fn five_field_cron_weekdays_count_from_sunday() {
+    let manager = manager(Work::default());
+    let next = |expression: &str, after: DateTime<Utc>| {
+        CronTrigger::new(manager.clone(), "sync", expression)
+            .unwrap()
+            .next_fire(after)
+    };
+    // Friday evening, then Saturday
+    let friday = Utc.with_ymd_and_hms(2026, 1, 16, 18, 0, 0).unwrap();
+    let saturday = Utc.with_ymd_and_hms(2026, 1, 17, 12, 0, 0).unwrap();
+
+    let monday_at_nine = Utc.with_ymd_and_hms(2026, 1, 19, 9, 0, 0).unwrap();
+    assert_eq!(next("0 9 * * 1-5", friday), Some(monday_at_nine));
+    assert_eq!(next("0 9 * * 1-5", saturday), Some(monday_at_nine));
+    assert_eq!(
+        next("* * * * 0", friday),
+        Some(Utc.with_ymd_and_hms(2026, 1, 18, 0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        next("0 0 * * 5-7", saturday),
+        Some(Utc.with_ymd_and_hms(2026, 1, 18, 0, 0, 0).unwrap())
+    );
+    assert_eq!(next("0 9 * * MON-FRI", saturday), Some(monday_at_nine));
+}
+
+#[tokio::test]
+async fn cron_triggers_follow_their_timezone() {
+    let manager = manager(Work::default());
+    let trigger = CronTrigger::new(manager.clone(), "sync", "0 9 * * MON-FRI")
+        .unwrap()
+        .with_timezone("Europe/Berlin".parse::<Tz>().unwrap());
+
+    // Thursday in winter (UTC+1), then Friday evening in summer (UTC+2)
+    let winter = Utc.with_ymd_and_hms(2026, 1, 15, 0, 0, 0).unwrap();
+    assert_eq!(
+        trigger.next_fire(winter),
+        Some(Utc.with_ymd_and_hms(2026, 1, 15, 8, 0, 0).unwrap())
+    );
+    let summer = Utc.with_ymd_and_hms(2026, 7, 17, 18, 0, 0).unwrap();
+    assert_eq!(
+        trigger.next_fire(summer),
+        Some(Utc.with_ymd_and_hms(2026, 7, 20, 7, 0, 0).unwrap())
+    );
+
+    assert!(CronTrigger::new(manager.clone(), "sync", "every day at 9").is_err());
+
+    // Second-level expressions fire while running
+    let trigger = CronTrigger::new(manager.clone(), "sync", "* * * * * *").unwrap();
+    trigger.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    trigger.shutdown().await.unwrap();
+    assert!(stored_runs(&manager).await >= 1);
+}
@@ -0,0 +1,418 @@
+use std::path::PathBuf;
+
+use ryvus_core::pipeline::pipeline::ExecutionMode;
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    error::FlowError,
+    pipeline::{
+        loader::PipelineLoader, manager::StepDefinition, FlowPipelineManager, PipelineDefinition,
+        PipelineFormat, PipelineValidator, Severity,
+    },
+    store::InMemoryStateStore,
+};
+use serde_json::json;
+
+fn definition(json: &str) -> PipelineDefinition {
+    PipelineLoader::parse(json).unwrap()
+}
+
+fn paths(def: &PipelineDefinition, severity: Severity) -> Vec<String> {
+    def.validate()
+        .issues
+        .into_iter()
+        .filter(|i| i.severity == severity)
+        .map(|i| i.path)
+        .collect()
+}
+
+#[test]
+fn valid_definition_has_no_issues() {
+    let def = definition(
+        r#"{
+            "key": "orders",
+            "steps": [
+                { "key": "fetch", "action": "http/get",
+                  "params": { "id": "$.payload.id" },
+                  "next_when": [{ "when": "$.fetch.output.total >= 100", "next": "review" }],
+                  "otherwise": "ship" },
+                { "key": "review", "action": "log", "next": "ship" },
+                { "key": "ship", "action": "log" }
+            ]
+        }"#,
+    );
+    let report = def.validate();
+    assert!(report.is_valid());
+    assert!(report.issues.is_empty(), "{report}");
+}
+
+#[test]
+fn reports_every_problem_with_its_path() {
+    let def = definition(
+        r#"{
+            "key": "orders",
+            "steps": [
+                { "key": "fetch", "action": "http/get",
+                  "params": { "items": ["$.payload[", "plain"] },
+                  "next_when": [
+                      { "when": "$.fetch.output.total = 100", "next": "ship" },
+                      { "when": "$.fetch.output.total > 1", "next": "missing" }
+                  ],
+                  "on_error": "nowhere" },
+                { "key": "ship" },
+                { "key": "ship", "action": "log" },
+                { "key": "orphan", "action": "log" }
+            ]
+        }"#,
+    );
+
+    assert_eq!(
+        paths(&def, Severity::Error),
+        [
+            "steps[2].key",
+            "steps[0].next_when[1].next",
+            "steps[0].on_error",
+            "steps[0].next_when[0].when",
+            "steps[0].params.items[0]",
+            "steps[1].action",
+        ]
+    );
+    assert_eq!(paths(&def, Severity::Warning), ["steps[3]"]);
+    assert!(!def.validate().is_valid());
+}
+
+#[test]
+fn reports_unknown_actions_when_given_the_registry() {
+    let def = definition(
+        r#"{ "key": "p", "steps": [
+            { "key": "a", "action": "log", "next": "b" },
+            { "key": "b", "action": "http/post" }
+        ] }"#,
+    );
+    let report = PipelineValidator::new()
+        .with_actions(["log", "http/get"])
+        .validate(&def);
+
+    let errors: Vec<_> = report.errors().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "steps[1].action");
+    assert!(errors[0].message.contains("http/post"));
+}
+
+#[test]
+fn reports_dag_cycles() {
+    let mut def = definition(
+        r#"{ "key": "p", "steps": [
+            { "key": "a", "action": "log", "depends_on": ["c"] },
+            { "key": "b", "action": "log", "depends_on": ["a"] },
+            { "key": "c", "action": "log", "depends_on": ["b"] },
+            { "key": "d", "action": "log" }
+        ] }"#,
+    );
+    def.mode = ExecutionMode::Dag;
+
+    assert_eq!(
+        paths(&def, Severity::Error),
+        [
+            "steps[0].depends_on",
+            "steps[1].depends_on",
+            "steps[2].depends_on"
+        ]
+    );
+    // Independent DAG roots are not unreachable
+    assert!(paths(&def, Severity::Warning).is_empty());
+}
+
+#[test]
+fn reports_unconditional_routing_cycles() {
+    let def = definition(
+        r#"{ "key": "p", "max_steps": 0, "steps": [
+            { "key": "start", "action": "log", "next": "poll" },
+            { "key": "poll", "action": "log", "next": "wait" },
+            { "key": "wait", "action": "log", "next": "poll" },
+            { "key": "check", "action": "log",
+              "next_when": [{ "when": "$.check.output.done == true", "next": "end" }],
+              "otherwise": "check", "on_error": "check" },
+            { "key": "end", "action": "log" }
+        ] }"#,
+    );
+
+    let report = def.validate();
+    let cycle = report
+        .errors()
+        .find(|i| i.path == "steps[1]")
+        .expect("cycle is reported");
+    assert!(
+        cycle.message.contains("poll -> wait -> poll"),
+        "{}",
+        cycle.message
+    );
+    // A conditional exit keeps `check` out of the errors
+    assert_eq!(paths(&def, Severity::Error), ["max_steps", "steps[1]"]);
+    assert!(paths(&def, Severity::Warning).contains(&"steps[3].on_error".to_string()));
+}
+
+#[test]
+fn manager_rejects_invalid_definitions_with_the_report() {
+    let manager = FlowPipelineManager::new(InMemoryStateStore::default(), Engine::default());
+    let def = definition(
+        r#"{ "key": "p", "steps": [
+            { "key": "a", "action": "log", "next_when": [{ "when": "$.a = 1", "next": "a" }] }
+        ] }"#,
+    );
+
+    match manager.try_register(def) {
+        Err(FlowError::Invalid(report)) => {
+            assert_eq!(report.issues.len(), 1);
+            assert_eq!(report.issues[0].path, "steps[0].next_when[0].when");
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+    assert!(manager.get("p").is_none());
+}
+
+/// Regenerate with `UPDATE_SCHEMA=1 cargo test -p ryvus-flow --test validation`.
+#[test]
+fn published_schema_is_up_to_date() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schema/pipeline.schema.json");
+    let generated =
+        serde_json::to_string_pretty(&PipelineDefinition::json_schema()).unwrap() + "\n";
+
+    if std::env::var_os("UPDATE_SCHEMA").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &generated).unwrap();
+    }
+    let published = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        published == generated,
+        "{} is stale; rerun with UPDATE_SCHEMA=1",
+        path.display()
+    );
+}
+
+#[test]
+fn validation_reports_bad_templates() {
+    let def = PipelineDefinition {
+        key: "bad".to_string(),
+        steps: vec![StepDefinition {
+            key: "call".to_string(),
+            action: "test/echo".to_string(),
+            params: json!({
+                "a": "${vars.HOST}",
+                "b": "${$.payload.id | shout}",
+                "c": "https://${env.HOST}/${$.payload[}"
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let report = def.validate();
+    let paths: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "steps[0].params.a",
+            "steps[0].params.b",
+            "steps[0].params.c"
+        ]
+    );
+}
+
+#[test]
+fn validation_reports_bad_sub_pipeline_steps() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: bad
+steps:
+  - key: both
+    action: test/echo
+    pipeline: { key: authenticate }
+    next: empty
+  - key: empty
+    pipeline: { key: "", input: { user: "$.payload[" } }
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = def.validate();
+    let paths: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "steps[0].action",
+            "steps[1].pipeline.key",
+            "steps[1].pipeline.input.user"
+        ]
+    );
+}
+
+#[test]
+fn validation_reports_bad_loops() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: bad
+steps:
+  - key: both
+    action: test/echo
+    for_each: { items: "$.payload[", concurrency: 0 }
+    while: { when: "$.index <", max_iterations: 0 }
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = def.validate();
+    let paths: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "steps[0].for_each.items",
+            "steps[0].for_each.concurrency",
+            "steps[0].while",
+            "steps[0].while.when",
+            "steps[0].while.max_iterations"
+        ]
+    );
+}
+
+#[test]
+fn validation_reports_bad_compensating_actions() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: bad
+steps:
+  - key: empty
+    action: test/echo
+    compensate: ""
+    next: unknown
+  - key: unknown
+    action: test/echo
+    compensate: cloud/delete
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = PipelineValidator::new()
+        .with_actions(["test/echo"])
+        .validate(&def);
+    let paths: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, ["steps[0].compensate", "steps[1].compensate"]);
+}
+
+#[test]
+fn validation_reports_bad_wait_for_steps() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: bad
+steps:
+  - key: both
+    action: test/echo
+    wait_for: { signal: "" }
+    otherwise: looping
+  - key: looping
+    wait_for: { signal: approval }
+    for_each: { items: "$.payload.items" }
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = def.validate();
+    let errors: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(
+        errors,
+        [
+            "steps[0].action",
+            "steps[0].wait_for.signal",
+            "steps[1].wait_for"
+        ]
+    );
+    let warnings: Vec<_> = report.warnings().map(|i| i.path.as_str()).collect();
+    assert_eq!(warnings, ["steps[0].otherwise"]);
+}
+
+#[test]
+fn validation_reports_bad_delay_steps() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: bad
+steps:
+  - key: both
+    action: test/echo
+    sleep_ms: 1000
+    next: twice
+  - key: twice
+    sleep_ms: 1000
+    wait_until: "tomorrow"
+    next: path
+  - key: path
+    wait_until: "$.payload[?(@.due"
+    next: fixed
+  - key: fixed
+    wait_until: "2026-12-24T18:00:00Z"
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = def.validate();
+    let errors: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(
+        errors,
+        [
+            "steps[0].action",
+            "steps[1].wait_until",
+            "steps[1].wait_until",
+            "steps[2].wait_until"
+        ]
+    );
+}
+
+#[test]
+fn validation_warns_about_bare_words_in_conditions() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: legacy
+steps:
+  - key: check
+    action: test/echo
+    next_when:
+      - { when: "$.payload.status == approved", next: done }
+  - key: done
+    action: test/echo
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = def.validate();
+    assert!(report.is_valid());
+    let warnings: Vec<_> = report.warnings().map(|i| i.message.as_str()).collect();
+    assert_eq!(
+        warnings,
+        ["Bare word 'approved' is read as the string \"approved\"; quote it"]
+    );
+}
+
+#[test]
+fn validation_leaves_script_sources_alone() {
+    let def = PipelineLoader::from_str_with_format(
+        r#"
+key: greet
+steps:
+  - key: greet
+    action: ryvus/script
+    config:
+      script: "`hello ${input.name}`"
+      label: "${nope}"
+"#,
+        PipelineFormat::Yaml,
+    )
+    .unwrap();
+
+    let report = def.validate();
+    let errors: Vec<_> = report.errors().map(|i| i.path.as_str()).collect();
+    assert_eq!(errors, ["steps[0].config.label"]);
+}
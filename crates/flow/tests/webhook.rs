@@ -0,0 +1,220 @@
+#![cfg(feature = "webhook")]
+
+mod common;
+
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use common::{EchoAction, MapResolver};
+use hmac::{Hmac, Mac};
+use ryvus_engine::Engine;
+use ryvus_flow::{
+    pipeline::{loader::PipelineLoader, PipelineFormat},
+    prelude::*,
+    trigger::{ResponseMode, Trigger, WebhookRoute, WebhookSignature, WebhookTrigger},
+};
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+const ISSUES: &str = r#"
+key: issues
+steps:
+  - key: triage
+    action: test/echo
+    params:
+      issue_action: "$.payload.body.action"
+      event: "$.payload.headers['x-github-event']"
+      source: "$.payload.query.source"
+      token: "${secret.GITHUB_TOKEN}"
+"#;
+
+fn manager() -> Arc<FlowPipelineManager<InMemoryStateStore>> {
+    let manager = FlowPipelineManager::new(
+        InMemoryStateStore::default(),
+        Engine::default().with_action(EchoAction),
+    )
+    .with_resolver(MapResolver(HashMap::from([("GITHUB_TOKEN", "ghp_s3cr3t")])));
+    manager
+        .try_register(PipelineLoader::from_str_with_format(ISSUES, PipelineFormat::Yaml).unwrap())
+        .unwrap();
+    Arc::new(manager)
+}
+
+async fn trigger() -> WebhookTrigger<InMemoryStateStore> {
+    let trigger = WebhookTrigger::new(manager(), "127.0.0.1:0".parse().unwrap())
+        .poll_token("poll-secret")
+        .route(
+            WebhookRoute::new("/github", "issues").signature(WebhookSignature::github("gh-secret")),
+        )
+        .route(
+            WebhookRoute::new("/stripe", "issues")
+                .respond(ResponseMode::Async)
+                .signature(WebhookSignature::stripe("whsec")),
+        );
+    trigger.start().await.unwrap();
+    trigger
+}
+
+fn sign(secret: &str, message: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(message.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Sends one HTTP/1.1 request and returns the status and JSON body.
+async fn send(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    body: &str,
+) -> (u16, Value) {
+    let mut request = format!(
+        "{method} {path} HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\ncontent-length: {}\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn sync_routes_respond_with_the_result() {
+    let trigger = trigger().await;
+    let addr = trigger.local_addr().unwrap();
+    let body = r#"{"action":"opened"}"#;
+
+    let (status, result) = send(
+        addr,
+        "POST",
+        "/github?source=hook",
+        &[
+            ("x-github-event", "issues".into()),
+            (
+                "x-hub-signature-256",
+                format!("sha256={}", sign("gh-secret", body)),
+            ),
+            ("authorization", "Bearer hidden".into()),
+        ],
+        body,
+    )
+    .await;
+    assert_eq!(status, 200, "{result}");
+    assert_eq!(result["status"], "Success");
+    let output = &result["result"];
+    assert_eq!(output["issue_action"], "opened");
+    assert_eq!(output["event"], "issues");
+    assert_eq!(output["source"], "hook");
+    assert_eq!(output["token"], "****");
+    assert!(output["headers"].get("authorization").is_none());
+
+    let (status, _) = send(
+        addr,
+        "POST",
+        "/github",
+        &[(
+            "x-hub-signature-256",
+            format!("sha256={}", sign("wrong", body)),
+        )],
+        body,
+    )
+    .await;
+    assert_eq!(status, 401);
+    let (status, _) = send(addr, "POST", "/github", &[], body).await;
+    assert_eq!(status, 401);
+
+    trigger.shutdown().await.unwrap();
+    assert!(trigger.local_addr().is_none());
+}
+
+#[tokio::test]
+async fn async_routes_hand_out_a_run_id_to_poll() {
+    let trigger = trigger().await;
+    let addr = trigger.local_addr().unwrap();
+    let body = r#"{"action":"paid"}"#;
+    let t = Utc::now().timestamp();
+    let signature = format!("t={t},v1={}", sign("whsec", &format!("{t}.{body}")));
+
+    let (status, accepted) = send(
+        addr,
+        "POST",
+        "/stripe",
+        &[("stripe-signature", signature)],
+        body,
+    )
+    .await;
+    assert_eq!(status, 202, "{accepted}");
+    let run_id = accepted["run_id"].as_str().unwrap().to_string();
+    let status_url = accepted["status_url"].as_str().unwrap().to_string();
+
+    let (status, _) = send(addr, "GET", &status_url, &[], "").await;
+    assert_eq!(status, 401);
+    let wrong = [("authorization", "Bearer guess".to_string())];
+    let (status, _) = send(addr, "GET", &status_url, &wrong, "").await;
+    assert_eq!(status, 401);
+
+    let auth = [("authorization", "Bearer poll-secret".to_string())];
+    let mut result = Value::Null;
+    for _ in 0..50 {
+        let (status, body) = send(addr, "GET", &status_url, &auth, "").await;
+        if status == 200 {
+            result = body;
+            break;
+        }
+        assert_eq!(status, 202);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(result["run_id"], run_id.as_str());
+    assert_eq!(result["result"]["issue_action"], "paid");
+
+    // Stale signatures are refused
+    let t = t - 3600;
+    let signature = format!("t={t},v1={}", sign("whsec", &format!("{t}.{body}")));
+    let (status, _) = send(
+        addr,
+        "POST",
+        "/stripe",
+        &[("stripe-signature", signature)],
+        body,
+    )
+    .await;
+    assert_eq!(status, 401);
+
+    let (status, _) = send(addr, "GET", "/runs/run_unknown", &auth, "").await;
+    assert_eq!(status, 404);
+
+    trigger.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn start_rejects_paths_the_router_cannot_serve() {
+    for paths in [
+        &["github"][..],
+        &["/hooks/{id}"],
+        &["/runs/latest"],
+        &["/github", "/github"],
+    ] {
+        let trigger = paths.iter().fold(
+            WebhookTrigger::new(manager(), "127.0.0.1:0".parse().unwrap()),
+            |trigger, path| trigger.route(WebhookRoute::new(*path, "issues")),
+        );
+        let err = trigger.start().await.unwrap_err();
+        assert!(matches!(err, FlowError::Trigger(_)), "{paths:?}: {err}");
+        assert!(trigger.local_addr().is_none());
+    }
+}
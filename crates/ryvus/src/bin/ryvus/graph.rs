@@ -0,0 +1,207 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use ryvus::core::pipeline::pipeline::{ExecutionMode, JoinMode, Pipeline, PipelineStep};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// One block per step with its outgoing edges
+    #[default]
+    Text,
+    /// Graphviz `dot` source
+    Dot,
+}
+
+/// A transition between two steps, labelled with what takes it.
+struct Edge<'a> {
+    from: &'a str,
+    to: &'a str,
+    label: String,
+}
+
+/// Renders the steps and transitions the engine follows for `pipeline`.
+/// Routed pipelines start at their first step and ignore `depends_on`;
+/// DAG pipelines only follow `depends_on`.
+pub fn render(pipeline: &Pipeline, format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Text => text(pipeline),
+        GraphFormat::Dot => dot(pipeline),
+    }
+}
+
+fn text(pipeline: &Pipeline) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} ({}, {} steps)",
+        pipeline.key,
+        mode_name(pipeline.mode),
+        pipeline.steps.len()
+    );
+
+    for (i, step) in pipeline.steps.iter().enumerate() {
+        let mut notes = notes(step);
+        if i == 0 && pipeline.mode == ExecutionMode::Routed {
+            notes.insert(0, "start".into());
+        }
+        let _ = write!(out, "\n{}  {}", step.key, kind(step));
+        if !notes.is_empty() {
+            let _ = write!(out, "  [{}]", notes.join(", "));
+        }
+        out.push('\n');
+
+        for edge in edges(pipeline, step) {
+            let _ = writeln!(out, "  {} -> {}", edge.label, edge.to);
+        }
+    }
+    out
+}
+
+fn dot(pipeline: &Pipeline) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "digraph {} {{", quote(&pipeline.key));
+    out.push_str("  rankdir=LR;\n  node [shape=box];\n");
+
+    for (i, step) in pipeline.steps.iter().enumerate() {
+        let mut label = format!("{}\n{}", step.key, kind(step));
+        for note in notes(step) {
+            label.push('\n');
+            label.push_str(&note);
+        }
+        let start = i == 0 && pipeline.mode == ExecutionMode::Routed;
+        let _ = writeln!(
+            out,
+            "  {} [label={}{}];",
+            quote(&step.key),
+            quote(&label),
+            if start { ", penwidth=2" } else { "" }
+        );
+    }
+    for step in &pipeline.steps {
+        for edge in edges(pipeline, step) {
+            let style = match edge.label.as_str() {
+                "on_error" => ", color=red",
+                label if label.starts_with("join") => ", style=dashed",
+                _ => "",
+            };
+            let _ = writeln!(
+                out,
+                "  {} -> {} [label={}{}];",
+                quote(edge.from),
+                quote(edge.to),
+                quote(&edge.label),
+                style
+            );
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// What a step does when entered.
+fn kind(step: &PipelineStep) -> String {
+    if let Some(sub) = &step.pipeline {
+        format!("pipeline {}", sub.key)
+    } else if let Some(wait) = &step.wait_for {
+        format!("wait_for {}", wait.signal)
+    } else if let Some(ms) = step.sleep_ms {
+        format!("sleep {} ms", ms)
+    } else if let Some(until) = &step.wait_until {
+        format!("wait_until {}", until)
+    } else if step.action.is_empty() {
+        "fan-out".into()
+    } else {
+        step.action.clone()
+    }
+}
+
+/// Modifiers that change how often or how long a step runs.
+fn notes(step: &PipelineStep) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(for_each) = &step.for_each {
+        notes.push(format!("for_each {}", for_each.items));
+    }
+    if let Some(repeat) = &step.repeat {
+        notes.push(format!("while {}", repeat.when));
+    }
+    if let Some(retry) = &step.retry {
+        notes.push(format!("{} attempts", retry.max_attempts));
+    }
+    if let Some(ms) = step.timeout_ms {
+        notes.push(format!("timeout {} ms", ms));
+    }
+    if let Some(ms) = step.wait_for.as_ref().and_then(|w| w.timeout_ms) {
+        notes.push(format!("signal timeout {} ms", ms));
+    }
+    if let Some(action) = &step.compensate {
+        notes.push(format!("compensate {}", action));
+    }
+    notes
+}
+
+fn edges<'a>(pipeline: &'a Pipeline, step: &'a PipelineStep) -> Vec<Edge<'a>> {
+    let edge = |to: &'a str, label: String| Edge {
+        from: &step.key,
+        to,
+        label,
+    };
+
+    if pipeline.mode == ExecutionMode::Dag {
+        // Steps that wait for this one
+        return pipeline
+            .steps
+            .iter()
+            .filter(|s| s.depends_on.contains(&step.key))
+            .map(|s| edge(&s.key, "then".into()))
+            .collect();
+    }
+
+    let mut edges: Vec<Edge> = step
+        .next_when
+        .iter()
+        .map(|branch| edge(&branch.next, format!("when {}", branch.when)))
+        .collect();
+    if let Some(otherwise) = &step.otherwise {
+        // Signal steps take `otherwise` only when their signal times out
+        let label = if step.wait_for.is_some() {
+            "timeout"
+        } else {
+            "otherwise"
+        };
+        edges.push(edge(otherwise, label.into()));
+    }
+    if let Some(next) = &step.next {
+        edges.push(edge(next, "next".into()));
+    }
+    if let Some(parallel) = &step.parallel {
+        for branch in &parallel.branches {
+            edges.push(edge(branch, "branch".into()));
+        }
+        let wait = match parallel.wait {
+            JoinMode::All => "all".to_string(),
+            JoinMode::Any => "any".to_string(),
+            JoinMode::Count(n) => n.to_string(),
+        };
+        edges.push(edge(&parallel.join, format!("join ({})", wait)));
+    }
+    if let Some(on_error) = &step.on_error {
+        edges.push(edge(on_error, "on_error".into()));
+    }
+    edges
+}
+
+fn mode_name(mode: ExecutionMode) -> &'static str {
+    match mode {
+        ExecutionMode::Routed => "routed",
+        ExecutionMode::Dag => "dag",
+    }
+}
+
+/// A DOT string literal.
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
@@ -0,0 +1,492 @@
+//! `ryvus`: runs pipeline definition files and inspects stored runs.
+
+mod graph;
+mod output;
+mod store;
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ryvus::core::{
+    action::result::ExecutionStatus,
+    prelude::{pipeline::Pipeline, RunQuery},
+};
+use ryvus::engine::engine::EngineApi;
+use ryvus::engine::Engine;
+use ryvus::flow::{
+    pipeline::{loader::PipelineLoader, PipelineDefinition, PipelineDirectory, ReloadEvent},
+    FlowError, FlowPipelineManager, StateStore,
+};
+use serde_json::Value;
+use tracing_subscriber::filter::LevelFilter;
+
+use graph::GraphFormat;
+use output::OutputFormat;
+use store::{with_store, Store};
+
+/// The command could not do its work: bad arguments, an unreadable or
+/// invalid definition, or a store error. Matches clap's usage errors.
+const EXIT_ERROR: u8 = 2;
+
+const EXIT_CODES: &str = "\
+Exit status of `ryvus run`, `ryvus signal` and `ryvus resume`:
+  0  the run succeeded
+  1  the run failed
+  2  the run could not start: bad arguments, an invalid definition or a store error
+  3  the run timed out
+  4  the run was canceled
+  5  the run failed and its completed steps were compensated
+  6  the run is suspended, waiting for a signal or a timer
+
+`ryvus validate` exits with 1 when any definition is invalid.";
+
+#[derive(Parser)]
+#[command(name = "ryvus", version, about, after_help = EXIT_CODES)]
+struct Cli {
+    /// Log more to stderr; repeat for debug output
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a pipeline definition file and print its result
+    Run(RunArgs),
+
+    /// Send a signal to a run suspended at a `wait_for` step and print its result
+    Signal(SignalArgs),
+
+    /// Continue a run that was cut off, from its last checkpoint, and print its result
+    Resume(ResumeArgs),
+
+    /// Check definition files and report every problem found
+    Validate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Print the steps of a definition and the transitions between them
+    Graph {
+        file: PathBuf,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        format: GraphFormat,
+    },
+
+    /// List or show runs recorded in a state store
+    Runs {
+        #[command(subcommand)]
+        command: RunsCommand,
+    },
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Definition file (JSON, YAML or TOML)
+    file: PathBuf,
+
+    /// Payload as inline JSON
+    #[arg(short, long, conflicts_with = "input_file")]
+    input: Option<String>,
+
+    /// Payload read from a JSON file, or `-` for stdin
+    #[arg(long)]
+    input_file: Option<PathBuf>,
+
+    /// Directory of definitions the run can call as sub-pipelines
+    #[arg(short, long)]
+    pipelines: Option<PathBuf>,
+
+    /// State store to record the run in: a directory, or a SQLite database
+    /// (`.db`, `.sqlite`); runs are kept in memory when unset. Suspended runs
+    /// kept in a store can be continued with `ryvus signal`
+    #[arg(short, long, env = "RYVUS_STORE")]
+    store: Option<PathBuf>,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: OutputFormat,
+}
+
+#[derive(Args)]
+struct SignalArgs {
+    /// Definition file the run was started from
+    file: PathBuf,
+
+    run_id: String,
+
+    /// Signal the run waits for
+    signal: String,
+
+    /// Signal payload as inline JSON
+    #[arg(long)]
+    payload: Option<String>,
+
+    /// Directory of definitions the run can call as sub-pipelines
+    #[arg(short, long)]
+    pipelines: Option<PathBuf>,
+
+    #[command(flatten)]
+    store: StoreArgs,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: OutputFormat,
+}
+
+#[derive(Args)]
+struct ResumeArgs {
+    /// Definition file the run was started from
+    file: PathBuf,
+
+    run_id: String,
+
+    /// Directory of definitions the run can call as sub-pipelines
+    #[arg(short, long)]
+    pipelines: Option<PathBuf>,
+
+    #[command(flatten)]
+    store: StoreArgs,
+
+    #[arg(short, long, value_enum, default_value_t)]
+    output: OutputFormat,
+}
+
+#[derive(Subcommand)]
+enum RunsCommand {
+    /// List runs, newest first
+    List {
+        /// Only runs of this pipeline
+        #[arg(short, long)]
+        pipeline: Option<String>,
+
+        /// Only runs with this status
+        #[arg(long, value_enum)]
+        status: Option<Status>,
+
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+
+        #[command(flatten)]
+        store: StoreArgs,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+
+    /// Show one run with its steps
+    Show {
+        run_id: String,
+
+        #[command(flatten)]
+        store: StoreArgs,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Args)]
+struct StoreArgs {
+    /// Existing state store directory, or a SQLite database (`.db`, `.sqlite`)
+    #[arg(short, long, env = "RYVUS_STORE")]
+    store: PathBuf,
+}
+
+/// `ExecutionStatus` as a command line value.
+#[derive(Clone, Copy, ValueEnum)]
+enum Status {
+    Success,
+    Failed,
+    Canceled,
+    Skipped,
+    Timeout,
+    Compensated,
+    Suspended,
+}
+
+impl From<Status> for ExecutionStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Success => ExecutionStatus::Success,
+            Status::Failed => ExecutionStatus::Failed,
+            Status::Canceled => ExecutionStatus::Canceled,
+            Status::Skipped => ExecutionStatus::Skipped,
+            Status::Timeout => ExecutionStatus::Timeout,
+            Status::Compensated => ExecutionStatus::Compensated,
+            Status::Suspended => ExecutionStatus::Suspended,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let level = match cli.verbose {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        _ => LevelFilter::DEBUG,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(io::stderr)
+        .init();
+
+    let outcome = match cli.command {
+        Command::Run(args) => run(args).await,
+        Command::Signal(args) => signal(args).await,
+        Command::Resume(args) => resume(args).await,
+        Command::Validate { files } => Ok(validate(&files)),
+        Command::Graph { file, format } => graph(&file, format),
+        Command::Runs { command } => runs(command).await,
+    };
+    outcome.unwrap_or_else(|e| {
+        report(&e);
+        ExitCode::from(EXIT_ERROR)
+    })
+}
+
+async fn run(args: RunArgs) -> Result<ExitCode, FlowError> {
+    let definition = PipelineLoader::from_file(&args.file)?;
+    let input = read_input(args.input.as_deref(), args.input_file.as_deref())?;
+    let store = Store::open(args.store.as_deref()).await?;
+
+    let key = definition.key.clone();
+    let result = with_store!(store, s => {
+        manager(s, definition, args.pipelines.as_deref())?
+            .start(&key, input)
+            .await?
+    });
+
+    emit(&output::result(&result, args.output));
+    Ok(ExitCode::from(exit_code(&result.status)))
+}
+
+async fn signal(args: SignalArgs) -> Result<ExitCode, FlowError> {
+    let definition = PipelineLoader::from_file(&args.file)?;
+    let payload = match &args.payload {
+        Some(json) => serde_json::from_str(json)
+            .map_err(|e| FlowError::Loader(format!("--payload: invalid JSON input: {}", e)))?,
+        None => Value::Object(Default::default()),
+    };
+    let store = Store::open_existing(&args.store.store).await?;
+
+    let key = definition.key.clone();
+    let result = with_store!(store, s => {
+        manager(s, definition, args.pipelines.as_deref())?
+            .signal(args.run_id, &key, &args.signal, payload)
+            .await?
+    });
+
+    emit(&output::result(&result, args.output));
+    Ok(ExitCode::from(exit_code(&result.status)))
+}
+
+async fn resume(args: ResumeArgs) -> Result<ExitCode, FlowError> {
+    let definition = PipelineLoader::from_file(&args.file)?;
+    let store = Store::open_existing(&args.store.store).await?;
+
+    let key = definition.key.clone();
+    let result = with_store!(store, s => {
+        manager(s, definition, args.pipelines.as_deref())?
+            .resume(args.run_id.clone(), &key)
+            .await?
+    });
+    let Some(result) = result else {
+        eprintln!(
+            "error: run '{}' has no checkpoint to resume from",
+            args.run_id
+        );
+        return Ok(ExitCode::from(EXIT_ERROR));
+    };
+
+    emit(&output::result(&result, args.output));
+    Ok(ExitCode::from(exit_code(&result.status)))
+}
+
+fn validate(files: &[PathBuf]) -> ExitCode {
+    let mut all_valid = true;
+    let mut out = String::new();
+    for path in files {
+        let (valid, problems) = check(path);
+        all_valid &= valid;
+
+        let verdict = if valid { "ok" } else { "invalid" };
+        out.push_str(&format!("{}: {}\n", path.display(), verdict));
+        for problem in problems {
+            out.push_str(&format!("  {}\n", problem));
+        }
+    }
+    emit(&out);
+
+    if all_valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Whether `try_register` would accept the definition at `path`, and every
+/// error and warning found in it.
+fn check(path: &Path) -> (bool, Vec<String>) {
+    let definition = match PipelineLoader::from_file(path) {
+        Ok(definition) => definition,
+        Err(e) => return (false, vec![format!("error: {}", e)]),
+    };
+
+    let report = definition.validate();
+    let mut problems: Vec<String> = report.issues.iter().map(ToString::to_string).collect();
+    if !report.is_valid() {
+        return (false, problems);
+    }
+    match Pipeline::try_from(definition) {
+        Ok(_) => (true, problems),
+        Err(e) => {
+            problems.push(format!("error: {}", e));
+            (false, problems)
+        }
+    }
+}
+
+fn graph(file: &Path, format: GraphFormat) -> Result<ExitCode, FlowError> {
+    let definition = PipelineLoader::from_file(file)?;
+    let pipeline = Pipeline::try_from(definition).map_err(FlowError::Loader)?;
+    emit(&graph::render(&pipeline, format));
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn runs(command: RunsCommand) -> Result<ExitCode, FlowError> {
+    match command {
+        RunsCommand::List {
+            pipeline,
+            status,
+            limit,
+            store,
+            output,
+        } => {
+            let mut query = RunQuery::new().limit(limit);
+            if let Some(pipeline) = pipeline {
+                query = query.pipeline(pipeline);
+            }
+            if let Some(status) = status {
+                query = query.status(status.into());
+            }
+
+            let store = Store::open_existing(&store.store).await?;
+            let results =
+                with_store!(store, s => s.list_results(&query).await).map_err(FlowError::Store)?;
+            emit(&output::runs(&results, output));
+            Ok(ExitCode::SUCCESS)
+        }
+        RunsCommand::Show {
+            run_id,
+            store,
+            output,
+        } => {
+            let store = Store::open_existing(&store.store).await?;
+            let result =
+                with_store!(store, s => s.load_result(&run_id).await).map_err(FlowError::Store)?;
+            let Some(result) = result else {
+                eprintln!("error: run '{}' not found", run_id);
+                return Ok(ExitCode::from(EXIT_ERROR));
+            };
+            emit(&output::result(&result, output));
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+/// A manager with `definition` and the definitions in `pipelines` registered.
+/// Its engine checkpoints into the same store, so suspended runs can be
+/// continued by a later `ryvus signal` or `ryvus resume`.
+fn manager<S: StateStore + 'static>(
+    store: S,
+    definition: PipelineDefinition,
+    pipelines: Option<&Path>,
+) -> Result<FlowPipelineManager<S>, FlowError> {
+    let store = Arc::new(store);
+    let manager = FlowPipelineManager::with_shared_store(store.clone(), engine(store));
+    if let Some(dir) = pipelines {
+        load_pipelines(&manager, dir)?;
+    }
+    manager.try_register(definition)?;
+    Ok(manager)
+}
+
+/// The engine runs execute on: built-in steps only, plus the `ryvus/script`
+/// action when built with the `script` feature.
+fn engine(store: Arc<dyn StateStore>) -> impl EngineApi + 'static {
+    let engine = Engine::default().with_state_store(store);
+    #[cfg(feature = "script")]
+    let engine = engine.with_action(ryvus::engine::actions::ScriptAction::new());
+    engine
+}
+
+fn load_pipelines<S: StateStore>(
+    manager: &FlowPipelineManager<S>,
+    dir: &Path,
+) -> Result<(), FlowError> {
+    for event in PipelineDirectory::new(dir).load_all(manager)? {
+        if let ReloadEvent::Failed { path, error } = event {
+            eprintln!("warning: skipped {}: {}", path.display(), error);
+        }
+    }
+    Ok(())
+}
+
+/// The run payload: `--input`, `--input-file`, or an empty object.
+fn read_input(inline: Option<&str>, file: Option<&Path>) -> Result<Value, FlowError> {
+    let (source, data) = match (inline, file) {
+        (Some(json), _) => ("--input".to_string(), json.to_string()),
+        (None, Some(path)) if path == Path::new("-") => {
+            let mut data = String::new();
+            io::stdin()
+                .read_to_string(&mut data)
+                .map_err(|e| FlowError::Loader(format!("stdin: {}", e)))?;
+            ("stdin".to_string(), data)
+        }
+        (None, Some(path)) => {
+            let data = fs::read_to_string(path)
+                .map_err(|e| FlowError::Loader(format!("{}: {}", path.display(), e)))?;
+            (path.display().to_string(), data)
+        }
+        (None, None) => return Ok(Value::Object(Default::default())),
+    };
+    serde_json::from_str(&data)
+        .map_err(|e| FlowError::Loader(format!("{}: invalid JSON input: {}", source, e)))
+}
+
+fn exit_code(status: &ExecutionStatus) -> u8 {
+    match status {
+        ExecutionStatus::Success | ExecutionStatus::Skipped => 0,
+        ExecutionStatus::Failed => 1,
+        ExecutionStatus::Timeout => 3,
+        ExecutionStatus::Canceled => 4,
+        ExecutionStatus::Compensated => 5,
+        ExecutionStatus::Suspended => 6,
+    }
+}
+
+/// Writes to stdout, ignoring a reader that went away (`ryvus runs list | head`).
+fn emit(out: &str) {
+    let _ = io::stdout().lock().write_all(out.as_bytes());
+}
+
+fn report(error: &FlowError) {
+    match error {
+        FlowError::Invalid(report) => {
+            eprintln!("error: invalid pipeline");
+            for issue in &report.issues {
+                eprintln!("  {}", issue);
+            }
+        }
+        e => eprintln!("error: {}", e),
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use ryvus::core::action::result::{ActionResult, ExecutionResult};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading
+    #[default]
+    Table,
+    /// Pretty-printed JSON, as stored
+    Json,
+}
+
+/// One run: a summary, its steps and the collected output.
+pub fn result(result: &ExecutionResult, format: OutputFormat) -> String {
+    if format == OutputFormat::Json {
+        return json(serde_json::to_string_pretty(result));
+    }
+
+    let mut summary = Table::new();
+    summary.row(["run", &result.run_id]);
+    if let Some(parent) = &result.parent_run_id {
+        summary.row(["parent", parent]);
+    }
+    summary.row(["pipeline", result.pipeline_key.as_deref().unwrap_or("-")]);
+    summary.row(["status", &format!("{:?}", result.status)]);
+    summary.row(["started", &timestamp(&result.metrics.started_at)]);
+    summary.row(["duration", &format!("{} ms", result.metrics.duration_ms)]);
+    if let Some(error) = &result.error {
+        summary.row(["error", error]);
+    }
+    let mut out = summary.to_string();
+
+    if !result.steps.is_empty() {
+        out.push('\n');
+        out.push_str(&steps_table("STEP", &result.steps).to_string());
+    }
+    if !result.compensations.is_empty() {
+        out.push('\n');
+        out.push_str(&steps_table("COMPENSATION", &result.compensations).to_string());
+    }
+    if let Some(output) = result.result.as_ref().filter(|v| !v.is_null()) {
+        out.push_str(&format!("\nresult: {}\n", output));
+    }
+    out
+}
+
+/// One line per run.
+pub fn runs(results: &[ExecutionResult], format: OutputFormat) -> String {
+    if format == OutputFormat::Json {
+        return json(serde_json::to_string_pretty(results));
+    }
+
+    let mut table = Table::new();
+    table.row(["RUN", "PIPELINE", "STATUS", "STARTED", "DURATION", "STEPS"]);
+    for result in results {
+        table.row([
+            result.run_id.as_str(),
+            result.pipeline_key.as_deref().unwrap_or("-"),
+            &format!("{:?}", result.status),
+            &timestamp(&result.metrics.started_at),
+            &format!("{} ms", result.metrics.duration_ms),
+            &format!(
+                "{}/{}",
+                result.metrics.steps_succeeded, result.metrics.steps_total
+            ),
+        ]);
+    }
+    table.to_string()
+}
+
+fn steps_table(heading: &str, steps: &[ActionResult]) -> Table {
+    let mut table = Table::new();
+    table.row([
+        heading, "ACTION", "STATUS", "DURATION", "ATTEMPTS", "MESSAGE",
+    ]);
+    for step in steps {
+        table.row([
+            step.key.as_str(),
+            step.action.as_deref().unwrap_or("-"),
+            &format!("{:?}", step.status),
+            &step
+                .duration_ms
+                .map(|ms| format!("{} ms", ms))
+                .unwrap_or_else(|| "-".into()),
+            &step.attempts.len().max(1).to_string(),
+            step.message.as_deref().unwrap_or(""),
+        ]);
+    }
+    table
+}
+
+fn json(json: serde_json::Result<String>) -> String {
+    // Results hold nothing but strings, numbers and JSON values
+    json.expect("run results serialize to JSON") + "\n"
+}
+
+fn timestamp(at: &DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+}
+
+/// Rows of cells, printed as left-aligned columns.
+struct Table {
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new() -> Self {
+        Self { rows: Vec::new() }
+    }
+
+    fn row<'a>(&mut self, cells: impl IntoIterator<Item = &'a str>) {
+        // Keep every row on one line
+        self.rows.push(
+            cells
+                .into_iter()
+                .map(|c| c.replace(['\n', '\r'], " "))
+                .collect(),
+        );
+    }
+}
+
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let columns = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for row in &self.rows {
+            let line: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+#[cfg(feature = "sqlite")]
+use ryvus::flow::store::SqliteStateStore;
+use ryvus::flow::{
+    store::{FileStateStore, InMemoryStateStore},
+    FlowError,
+};
+
+/// The state store named by `--store`.
+// One per process, so the in-memory variant's size does not matter
+#[allow(clippy::large_enum_variant)]
+pub enum Store {
+    Memory(InMemoryStateStore),
+    File(FileStateStore),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStateStore),
+}
+
+impl Store {
+    /// Opens `path` as a SQLite database when it ends in `.db`, `.sqlite` or
+    /// `.sqlite3`, otherwise as a `FileStateStore` directory. Without a path,
+    /// runs are kept in memory and lost on exit.
+    pub async fn open(path: Option<&Path>) -> Result<Self, FlowError> {
+        let Some(path) = path else {
+            return Ok(Self::Memory(InMemoryStateStore::default()));
+        };
+        if is_sqlite(path) {
+            return open_sqlite(path);
+        }
+        Ok(Self::File(FileStateStore::open(path).await?))
+    }
+
+    /// Opens a store that must already exist, so looking at runs never
+    /// creates an empty one by accident.
+    pub async fn open_existing(path: &Path) -> Result<Self, FlowError> {
+        if !path.exists() {
+            return Err(FlowError::Store(format!(
+                "{}: no such state store",
+                path.display()
+            )));
+        }
+        Self::open(Some(path)).await
+    }
+}
+
+fn is_sqlite(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("db" | "sqlite" | "sqlite3")
+    )
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &Path) -> Result<Store, FlowError> {
+    Ok(Store::Sqlite(SqliteStateStore::open(path)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(path: &Path) -> Result<Store, FlowError> {
+    Err(FlowError::Store(format!(
+        "{}: ryvus was built without the `sqlite` feature",
+        path.display()
+    )))
+}
+
+/// Evaluates `$body` with `$s` bound to the concrete store inside `$store`.
+macro_rules! with_store {
+    ($store:expr, $s:ident => $body:expr) => {
+        match $store {
+            $crate::store::Store::Memory($s) => $body,
+            $crate::store::Store::File($s) => $body,
+            #[cfg(feature = "sqlite")]
+            $crate::store::Store::Sqlite($s) => $body,
+        }
+    };
+}
+
+pub(crate) use with_store;
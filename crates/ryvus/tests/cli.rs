@@ -0,0 +1,235 @@
+use std::{fs, path::Path, process::Command};
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// Routes large orders to a signal gate, which suspends the run.
+const ORDERS: &str = r#"
+key: orders
+steps:
+  - key: check
+    sleep_ms: 1
+    next_when:
+      - when: "$.payload.total >= 100"
+        next: approve
+    otherwise: done
+  - key: approve
+    wait_for:
+      signal: approved
+    next: done
+  - key: done
+    sleep_ms: 1
+"#;
+
+struct Output {
+    code: i32,
+    stdout: String,
+    stderr: String,
+}
+
+fn ryvus(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_ryvus"))
+        .args(args)
+        .env_remove("RYVUS_STORE")
+        .output()
+        .unwrap();
+    Output {
+        code: output.status.code().unwrap(),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
+
+fn write(dir: &TempDir, name: &str, content: &str) -> String {
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    path_arg(&path)
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn validate_reports_every_invalid_file() {
+    let dir = TempDir::new().unwrap();
+    let orders = write(&dir, "orders.yaml", ORDERS);
+    let broken = write(
+        &dir,
+        "broken.yaml",
+        "key: broken\nsteps:\n  - key: a\n    action: acme/a\n    next: missing\n",
+    );
+
+    let ok = ryvus(&["validate", &orders]);
+    assert_eq!(ok.code, 0);
+    assert!(ok.stdout.contains("orders.yaml: ok"));
+
+    let out = ryvus(&["validate", &orders, &broken]);
+    assert_eq!(out.code, 1);
+    assert!(out.stdout.contains("broken.yaml: invalid"));
+    assert!(out
+        .stdout
+        .contains("steps[0].next: References undefined step 'missing'"));
+}
+
+#[test]
+fn graph_prints_the_routing_edges() {
+    let dir = TempDir::new().unwrap();
+    let orders = write(&dir, "orders.yaml", ORDERS);
+
+    let text = ryvus(&["graph", &orders]);
+    assert_eq!(text.code, 0);
+    assert!(text.stdout.starts_with("orders (routed, 3 steps)"));
+    assert!(text.stdout.contains("check  sleep 1 ms  [start]"));
+    assert!(text
+        .stdout
+        .contains("when $.payload.total >= 100 -> approve"));
+    assert!(text.stdout.contains("otherwise -> done"));
+
+    let dot = ryvus(&["graph", &orders, "--format", "dot"]);
+    assert!(dot.stdout.starts_with("digraph \"orders\" {"));
+    assert!(dot
+        .stdout
+        .contains("\"approve\" -> \"done\" [label=\"next\"];"));
+}
+
+#[test]
+fn runs_are_recorded_and_exit_with_their_status() {
+    let dir = TempDir::new().unwrap();
+    let orders = write(&dir, "orders.yaml", ORDERS);
+    let failing = write(
+        &dir,
+        "failing.yaml",
+        "key: failing\nsteps:\n  - key: a\n    action: acme/missing\n",
+    );
+    let input = write(&dir, "input.json", r#"{ "total": 250 }"#);
+    let store = path_arg(&dir.path().join("store"));
+
+    let small = ryvus(&["run", &orders, "--input", r#"{"total": 5}"#, "-s", &store]);
+    assert_eq!(small.code, 0, "{}", small.stderr);
+    assert!(small.stdout.contains("status    Success"));
+
+    let large = ryvus(&[
+        "run",
+        &orders,
+        "--input-file",
+        &input,
+        "-s",
+        &store,
+        "-o",
+        "json",
+    ]);
+    assert_eq!(large.code, 6);
+    let suspended: Value = serde_json::from_str(&large.stdout).unwrap();
+    assert_eq!(suspended["status"], "Suspended");
+
+    let failed = ryvus(&["run", &failing, "-s", &store]);
+    assert_eq!(failed.code, 1);
+
+    let list = ryvus(&["runs", "list", "-s", &store, "-p", "orders", "-o", "json"]);
+    let runs: Vec<Value> = serde_json::from_str(&list.stdout).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["run_id"], suspended["run_id"]);
+
+    let table = ryvus(&["runs", "list", "-s", &store, "--status", "failed"]);
+    assert_eq!(table.stdout.lines().count(), 2);
+    assert!(table.stdout.contains("failing"));
+
+    let run_id = suspended["run_id"].as_str().unwrap();
+    let show = ryvus(&["runs", "show", run_id, "-s", &store]);
+    assert_eq!(show.code, 0);
+    assert!(show.stdout.contains("status    Suspended"));
+    assert!(show.stdout.contains("check"));
+
+    assert_eq!(
+        ryvus(&["runs", "show", "run_missing", "-s", &store]).code,
+        2
+    );
+}
+
+#[test]
+fn suspended_runs_are_continued_by_signal() {
+    let dir = TempDir::new().unwrap();
+    let orders = write(&dir, "orders.yaml", ORDERS);
+    let store = path_arg(&dir.path().join("store"));
+
+    let large = ryvus(&[
+        "run",
+        &orders,
+        "--input",
+        r#"{"total": 250}"#,
+        "-s",
+        &store,
+        "-o",
+        "json",
+    ]);
+    assert_eq!(large.code, 6, "{}", large.stderr);
+    let suspended: Value = serde_json::from_str(&large.stdout).unwrap();
+    let run_id = suspended["run_id"].as_str().unwrap();
+
+    let wrong = ryvus(&["signal", &orders, run_id, "rejected", "-s", &store]);
+    assert_eq!(wrong.code, 2);
+
+    let approved = ryvus(&[
+        "signal",
+        &orders,
+        run_id,
+        "approved",
+        "--payload",
+        r#"{"by": "ada"}"#,
+        "-s",
+        &store,
+        "-o",
+        "json",
+    ]);
+    assert_eq!(approved.code, 0, "{}", approved.stderr);
+    let finished: Value = serde_json::from_str(&approved.stdout).unwrap();
+    assert_eq!(finished["run_id"], run_id);
+    assert_eq!(finished["status"], "Success");
+
+    let show = ryvus(&["runs", "show", run_id, "-s", &store]);
+    assert!(show.stdout.contains("status    Success"));
+
+    // Resuming a finished run prints its result again
+    let resumed = ryvus(&["resume", &orders, run_id, "-s", &store]);
+    assert_eq!(resumed.code, 0, "{}", resumed.stderr);
+    assert!(resumed.stdout.contains("status    Success"));
+    assert_eq!(
+        ryvus(&["resume", &orders, "run_missing", "-s", &store]).code,
+        2
+    );
+}
+
+#[test]
+fn runs_commands_do_not_create_missing_stores() {
+    let dir = TempDir::new().unwrap();
+    for name in ["store", "runs.db"] {
+        let store = dir.path().join(name);
+        let list = ryvus(&["runs", "list", "-s", &path_arg(&store)]);
+        assert_eq!(list.code, 2);
+        assert!(
+            list.stderr.contains("no such state store"),
+            "{}",
+            list.stderr
+        );
+        let show = ryvus(&["runs", "show", "run_x", "-s", &path_arg(&store)]);
+        assert_eq!(show.code, 2);
+        assert!(!store.exists());
+    }
+}
+
+#[test]
+fn unusable_input_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let orders = write(&dir, "orders.yaml", ORDERS);
+
+    let bad_json = ryvus(&["run", &orders, "--input", "{"]);
+    assert_eq!(bad_json.code, 2);
+    assert!(bad_json.stderr.contains("invalid JSON input"));
+
+    let missing = ryvus(&["run", &path_arg(&dir.path().join("missing.yaml"))]);
+    assert_eq!(missing.code, 2);
+
+    let both = ryvus(&["run", &orders, "--input", "{}", "--input-file", "x.json"]);
+    assert_eq!(both.code, 2);
+}